@@ -0,0 +1,40 @@
+// Standard material that discards fragments in front of the section planes
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    forward_io::{VertexOutput, FragmentOutput},
+}
+
+struct SectionClip {
+    // xyz normal, w offset, unused planes are (0, 0, 0, 1)
+    planes: array<vec4<f32>, 4>,
+}
+
+@group(2) @binding(100) var<uniform> section: SectionClip;
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    for (var i = 0u; i < 4u; i++) {
+        let plane = section.planes[i];
+        if dot(plane.xyz, in.world_position.xyz) > plane.w {
+            discard;
+        }
+    }
+
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
@@ -1,211 +1,192 @@
-use std::{error::Error, time::Duration, net::ToSocketAddrs};
-use std::fmt;
-use reqwest::{Client, ClientBuilder};
-use serde_json::{json, Value};
+use std::time::Duration;
+use std::path::{Path, PathBuf};
+use reqwest::{header::RETRY_AFTER, Client, ClientBuilder};
 use bevy::prelude::*;
-
-pub const INIT_MESSAGE: &str = "\
-If you receive a request to create a cube, parse it and return a JSON object with the following structure: \
-{\"command\": \"create cube\", \"parameters\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0, \"width\": 1.0, \"height\": 1.0, \"depth\": 1.0}}
-
-If you receive a request to create multiple cubes, respond with a JSON array like this: \
-{\"command\": \"create cubes\", \"parameters\": [{\"x\": 0.0, \"y\": 0.0, \"z\": 0.0, \"width\": 1.0, \"height\": 1.0, \"depth\": 1.0}, \
-{\"x\": 1.0, \"y\": 1.0, \"z\": 1.0, \"width\": 1.0, \"height\": 1.0, \"depth\": 1.0}]}
-
-Always respond with valid JSON in the exact format shown above. Here is the prompt:";
+use crate::tools::units::LengthUnit;
+use super::commands::{command_prompt, tool_declarations, tool_prompt};
+use super::conversation::Message;
+use super::error::{parse_retry_after, AiError, RetryPolicy};
+use super::fixtures::{Exchange, Fixture, RecordedRequest, RecordedResponse};
+use super::llm_provider::{LlmConfig, LlmProvider, LlmRequest, ModelReply};
 
 #[derive(Resource, Clone)]
 pub struct AiClient {
     api_key: String,
+    config: LlmConfig,
     client: Client,
+    /// Fixture file the exchanges are recorded to, see `Fixture`
+    recording: Option<PathBuf>,
+    retry: RetryPolicy,
 }
 
 impl Default for AiClient {
     fn default() -> Self {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(30))
-            .tcp_keepalive(Some(Duration::from_secs(60)))
-            .pool_max_idle_per_host(0)
-            .build()
-            .expect("Failed to create client");
-        Self {
-            api_key: String::new(),
-            client,
-        }
+        Self::new(String::new())
     }
 }
 
 impl AiClient {
     pub fn new(api_key: String) -> Self {
+        Self::with_config(api_key, LlmConfig::default())
+    }
+
+    pub fn with_config(api_key: String, config: LlmConfig) -> Self {
+        // The timeout is set per request, it can change with the config
         let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(30))
             .tcp_keepalive(Some(Duration::from_secs(60)))
             .pool_max_idle_per_host(0)
             .build()
             .expect("Failed to create client");
         Self {
             api_key,
+            config,
             client,
+            recording: None,
+            retry: RetryPolicy::default(),
         }
     }
 
-    pub async fn test_basic_connectivity(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let host = "generativelanguage.googleapis.com:443";
-        println!("Testing basic DNS resolution for: {}", host);
-        
-        match host.to_socket_addrs() {
-            Ok(addrs) => {
-                for addr in addrs {
-                    println!("Resolved address: {}", addr);
-                }
-                Ok(())
-            },
-            Err(e) => {
-                println!("DNS resolution failed: {}", e);
-                Err(Box::new(e))
-            }
+    pub fn recording(&self) -> Option<&Path> {
+        self.recording.as_deref()
+    }
+
+    /// Records the requests and responses to a fixture file from now on, or stops with `None`
+    pub fn set_recording(&mut self, path: Option<PathBuf>) {
+        self.recording = path;
+    }
+
+    pub fn config(&self) -> &LlmConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: LlmConfig) {
+        self.config = config;
+    }
+
+    pub fn set_api_key(&mut self, api_key: String) {
+        self.api_key = api_key;
+    }
+
+    /// Whether requests can be sent, false while the provider needs a key and none is set
+    pub fn is_ready(&self) -> bool {
+        !self.api_key.is_empty() || !self.config.requires_key()
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    /// Asks the model to answer the last of `messages`, the earlier ones are the conversation
+    /// so far. `scene` describes the parts of the document, see `SceneParts::describe`.
+    /// Failed requests are sent again as `RetryPolicy` allows, `notify` hears about each retry.
+    pub async fn call_llm_api(&self, messages: &[Message], unit: LengthUnit, scene: &str, notify: impl Fn(String)) -> Result<ModelReply, AiError> {
+        let prompt = messages.last().map_or("", |message| message.text.as_str());
+        debug!("Calling {} model {} with prompt: {} ({} earlier messages)", self.config.provider.name(), self.config.model, prompt, messages.len().saturating_sub(1));
+
+        let provider = self.config.provider.provider();
+        // Without function calling the model answers with the JSON command protocol
+        let (protocol, tools) = if self.config.use_tools {
+            (tool_prompt(), tool_declarations())
+        } else {
+            (command_prompt(), Vec::new())
+        };
+        let instructions = format!("{}{}{}", protocol, units_instruction(unit), scene);
+        let request = provider.request(&self.config, &self.api_key, &instructions, messages, &tools);
+
+        let mut retry = 0;
+        loop {
+            let error = match self.send(&request, prompt, provider.as_ref()).await {
+                Ok(reply) => return Ok(reply),
+                Err(error) => error,
+            };
+            let Some(wait) = self.retry.delay(retry, &error) else {
+                return Err(error);
+            };
+            retry += 1;
+            warn!("AI request failed, retry {} in {:?}: {}", retry, wait, error);
+            notify(format!(
+                "AI request failed, retrying in {:.1} s ({} of {}): {}",
+                wait.as_secs_f32(),
+                retry,
+                self.retry.max_retries,
+                error,
+            ));
+            tokio::time::sleep(wait).await;
         }
     }
 
-    pub async fn call_llm_api(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
-        println!("Testing connectivity first...");
-        if let Err(e) = self.test_basic_connectivity().await {
-            println!("Connectivity test failed: {:?}", e);
-            return Err(e);
+    /// Sends `request` once
+    async fn send(&self, request: &LlmRequest, prompt: &str, provider: &dyn LlmProvider) -> Result<ModelReply, AiError> {
+        let mut builder = self.client
+            .post(&request.url)
+            .timeout(self.config.timeout)
+            .json(&request.body);
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
         }
-        println!("Connectivity test passed, proceeding with API call");
-        println!("Calling LLM API with prompt: {}", prompt);
-
-        let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent?key={}",
-            self.api_key
-        );
-
-        let request_body = json!({
-            "contents": [{
-                "parts": [{
-                    "text": format!("{}{}", INIT_MESSAGE, prompt)
-                }],
-                "role": "user"
-            }],
-            "generationConfig": {
-                "temperature": 0.1,
-                "topK": 1,
-                "topP": 1
-            }
-        });
-
-        match self.client
-            .post(&url)
-            .header("Host", "generativelanguage.googleapis.com")
-            .json(&request_body)
-            .send()
-            .await
-        {
-            Ok(response) => {
-                println!("Received response with status: {}", response.status());
-                handle_response(response).await
-            },
-            Err(e) => {
-                println!("Error sending request: {:?}", e);
-                if let Some(status) = e.status() {
-                    println!("HTTP status code: {}", status);
-                }
-                if let Some(url) = e.url() {
-                    println!("Failed URL: {}", url);
-                }
-                Err(Box::new(e))
+
+        let response = builder.send().await.map_err(|e| self.request_error(e))?;
+        let status = response.status();
+        debug!("Received response with status: {}", status);
+        let retry_after = response.headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let text = response.text().await.map_err(|e| self.request_error(e))?;
+        if let Some(path) = &self.recording {
+            if let Err(e) = self.record(path, prompt, request, status.as_u16(), &text) {
+                warn!("Could not record to {}: {}", path.display(), e);
             }
         }
+        handle_response(status.as_u16(), &text, retry_after, provider)
     }
-}
 
-async fn handle_response(res: reqwest::Response) -> Result<String, Box<dyn Error + Send + Sync>> {
-    let status = res.status();
-    if status.is_success() {
-        let response_json: serde_json::Value = res.json().await?;
-        println!("Response: {}", response_json);
-        
-        // Extract the text from Gemini's response format
-        let raw_text = response_json["candidates"][0]["content"]["parts"][0]["text"]
-            .as_str()
-            .ok_or_else(|| ApiError {
-                status,
-                message: "Failed to extract text from response".to_string(),
-            })?;
-
-        // Clean response
-        let trimmed = raw_text.trim();
-        let without_markers = trimmed.trim_start_matches("```json").trim_end_matches("```");
-        let without_newlines = without_markers.replace("\n", "");
-        let text = without_newlines.trim();
-
-        // Parse the text as JSON to validate it's a proper JSON response
-        let json_value: Value = serde_json::from_str(text)
-            .map_err(|e| ApiError {
-                status,
-                message: format!("Invalid JSON in response: {}", e),
-            })?;
-
-        // Verify the JSON has the expected structure
-        if !json_value.is_object() || !json_value["command"].is_string() {
-            return Err(Box::new(ApiError {
-                status,
-                message: "Response JSON missing required fields".to_string(),
-            }));
+    fn request_error(&self, error: reqwest::Error) -> AiError {
+        if error.is_timeout() {
+            AiError::Timeout(self.config.timeout)
+        } else {
+            AiError::Network(error.to_string())
         }
+    }
 
-        // Check parameters based on command type
-        let command = json_value["command"].as_str().unwrap();
-        match command {
-            "create cube" => {
-                if !json_value["parameters"].is_object() {
-                    return Err(Box::new(ApiError {
-                        status,
-                        message: "Single cube parameters must be an object".to_string(),
-                    }));
-                }
-            },
-            "create cubes" => {
-                if !json_value["parameters"].is_array() {
-                    return Err(Box::new(ApiError {
-                        status,
-                        message: "Multiple cubes parameters must be an array".to_string(),
-                    }));
-                }
+    fn record(&self, path: &Path, prompt: &str, request: &LlmRequest, status: u16, text: &str) -> std::io::Result<()> {
+        let base_url = self.config.base_url.trim_end_matches('/');
+        let exchange = Exchange {
+            request: RecordedRequest {
+                path: request.url.strip_prefix(base_url).unwrap_or(&request.url).to_string(),
+                body: request.body.clone(),
             },
-            _ => {
-                return Err(Box::new(ApiError {
-                    status,
-                    message: format!("Unknown command: {}", command),
-                }));
-            }
-        }
-
-        Ok(text.to_string())
-    } else {
-        let error_body = res.text().await.unwrap_or_else(|_| "Error reading response".to_string());
-        println!("Error: {} - {}", status, error_body);
-        Err(Box::new(ApiError {
-            status,
-            message: error_body,
-        }))
+            response: RecordedResponse::from_text(status, text),
+        };
+        Fixture::append(path, self.config.provider.name(), prompt, exchange)
     }
 }
 
-#[derive(Debug)]
-pub struct ApiError {
-    pub status: reqwest::StatusCode,
-    pub message: String,
+// Tells the model which unit the numbers in its answer are in
+fn units_instruction(unit: LengthUnit) -> String {
+    format!(" All positions and sizes are in {} ({}). ", unit.name(), unit.symbol())
 }
 
-unsafe impl Send for ApiError {}
-unsafe impl Sync for ApiError {}
-
-impl fmt::Display for ApiError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "API Error: {} - {}", self.status, self.message)
+fn handle_response(status: u16, text: &str, retry_after: Option<Duration>, provider: &dyn LlmProvider) -> Result<ModelReply, AiError> {
+    if !(200..300).contains(&status) {
+        return Err(AiError::from_status(status, text, retry_after));
     }
-}
+    let response_json: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| AiError::MalformedResponse(e.to_string()))?;
+    debug!("Response: {}", response_json);
+
+    let mut reply = provider.reply(&response_json)
+        .ok_or_else(|| AiError::MalformedResponse("no answer in the response".to_string()))?;
 
-impl Error for ApiError {}
+    // Models answering with the text protocol may wrap the JSON in a code fence
+    let trimmed = reply.text.trim();
+    let without_markers = trimmed.trim_start_matches("```json").trim_end_matches("```");
+    reply.text = without_markers.trim().to_string();
+
+    // Checked against the command protocol when the answer is handled
+    Ok(reply)
+}
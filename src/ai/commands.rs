@@ -0,0 +1,577 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+pub use crate::part::csg::BooleanOp;
+use crate::tools::units::LengthUnit;
+use super::conversation::ToolCall;
+use super::llm_provider::ToolDeclaration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimitiveShape {
+    Box,
+}
+
+impl PrimitiveShape {
+    pub const NAMES: &'static [&'static str] = &["box"];
+}
+
+/// Picks the face of a part by the direction it faces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaceDirection {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    Front,
+    Back,
+}
+
+impl FaceDirection {
+    pub const NAMES: &'static [&'static str] = &["top", "bottom", "left", "right", "front", "back"];
+
+    /// World direction, Y up and the front facing +Z like the standard views
+    pub fn vector(&self) -> Vec3 {
+        match self {
+            FaceDirection::Top => Vec3::Y,
+            FaceDirection::Bottom => Vec3::NEG_Y,
+            FaceDirection::Left => Vec3::NEG_X,
+            FaceDirection::Right => Vec3::X,
+            FaceDirection::Front => Vec3::Z,
+            FaceDirection::Back => Vec3::NEG_Z,
+        }
+    }
+
+    /// The direction closest to `normal`
+    pub fn nearest(normal: Vec3) -> Self {
+        [FaceDirection::Top, FaceDirection::Bottom, FaceDirection::Left, FaceDirection::Right, FaceDirection::Front, FaceDirection::Back]
+            .into_iter()
+            .max_by(|a, b| a.vector().dot(normal).total_cmp(&b.vector().dot(normal)))
+            .unwrap()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FaceDirection::Top => "top",
+            FaceDirection::Bottom => "bottom",
+            FaceDirection::Left => "left",
+            FaceDirection::Right => "right",
+            FaceDirection::Front => "front",
+            FaceDirection::Back => "back",
+        }
+    }
+}
+
+/// What a command field holds, for the schema and for validation
+#[derive(Debug, Clone, Copy)]
+pub enum FieldKind {
+    Number,
+    Vec3,
+    /// Three numbers greater than zero
+    Size,
+    Text,
+    Target,
+    Targets,
+    Choice(&'static [&'static str]),
+    Color,
+}
+
+impl FieldKind {
+    fn schema(&self) -> Value {
+        let numbers = |minimum: Option<f32>| {
+            let mut item = json!({ "type": "number" });
+            if let Some(minimum) = minimum {
+                item["exclusiveMinimum"] = json!(minimum);
+            }
+            json!({ "type": "array", "items": item, "minItems": 3, "maxItems": 3 })
+        };
+        match self {
+            FieldKind::Number => json!({ "type": "number" }),
+            FieldKind::Vec3 => numbers(None),
+            FieldKind::Size => numbers(Some(0.0)),
+            FieldKind::Text | FieldKind::Target => json!({ "type": "string", "minLength": 1 }),
+            FieldKind::Targets => json!({ "type": "array", "items": { "type": "string", "minLength": 1 }, "minItems": 1 }),
+            FieldKind::Choice(values) => json!({ "type": "string", "enum": values }),
+            FieldKind::Color => json!({ "type": "string", "pattern": "^#[0-9a-fA-F]{6}$" }),
+        }
+    }
+
+    fn expected(&self) -> String {
+        match self {
+            FieldKind::Number => "a number".to_string(),
+            FieldKind::Vec3 => "an array of 3 numbers".to_string(),
+            FieldKind::Size => "an array of 3 numbers greater than zero".to_string(),
+            FieldKind::Text => "a non-empty string".to_string(),
+            FieldKind::Target => "a part ID, a part or group name, \"selected\", \"all\" or \"last\"".to_string(),
+            FieldKind::Targets => "a non-empty array of part IDs or names".to_string(),
+            FieldKind::Choice(values) => format!("one of {}", values.join(", ")),
+            FieldKind::Color => "a colour like \"#ff8800\"".to_string(),
+        }
+    }
+
+    fn accepts(&self, value: &Value) -> bool {
+        let numbers = |value: &Value, positive: bool| {
+            value.as_array().is_some_and(|items| {
+                items.len() == 3 && items.iter().all(|item| item.as_f64().is_some_and(|n| !positive || n > 0.0))
+            })
+        };
+        let text = |value: &Value| value.as_str().is_some_and(|text| !text.trim().is_empty());
+        match self {
+            FieldKind::Number => value.is_number(),
+            FieldKind::Vec3 => numbers(value, false),
+            FieldKind::Size => numbers(value, true),
+            FieldKind::Text | FieldKind::Target => text(value),
+            FieldKind::Targets => value.as_array().is_some_and(|items| !items.is_empty() && items.iter().all(text)),
+            FieldKind::Choice(values) => value.as_str().is_some_and(|name| values.contains(&name)),
+            FieldKind::Color => value.as_str().and_then(parse_color).is_some(),
+        }
+    }
+}
+
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+/// One command of the protocol, declared with its `AiCommand` variant. The schema sent to the
+/// model, the prompt text and the validation of answers all come from these.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub fields: &'static [FieldSpec],
+}
+
+/// Whether a command field must be given, from its type: only `Option` fields may be left out
+pub trait CommandField {
+    const REQUIRED: bool = true;
+}
+
+impl<T> CommandField for Option<T> {
+    const REQUIRED: bool = false;
+}
+
+impl CommandField for f32 {}
+impl CommandField for [f32; 3] {}
+impl CommandField for String {}
+impl CommandField for Vec<String> {}
+impl CommandField for PrimitiveShape {}
+impl CommandField for FaceDirection {}
+impl CommandField for BooleanOp {}
+
+// Declares `AiCommand` and its `COMMANDS` specs from one list, so every variant and field has
+// its schema next to it and the two cannot drift apart
+macro_rules! ai_commands {
+    (
+        $(#[$enum_attr:meta])*
+        $(
+            $variant:ident = $name:literal, $description:literal {
+                $(
+                    $(#[$field_attr:meta])*
+                    $field:ident: $ty:ty => $kind:expr, $field_description:literal;
+                )*
+            }
+        )*
+    ) => {
+        $(#[$enum_attr])*
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        #[serde(tag = "command")]
+        pub enum AiCommand {
+            $(
+                #[serde(rename = $name)]
+                $variant {
+                    $($(#[$field_attr])* $field: $ty,)*
+                },
+            )*
+        }
+
+        pub const COMMANDS: &[CommandSpec] = &[
+            $(
+                CommandSpec {
+                    name: $name,
+                    description: $description,
+                    fields: &[
+                        $(FieldSpec {
+                            name: stringify!($field),
+                            kind: $kind,
+                            required: <$ty as CommandField>::REQUIRED,
+                            description: $field_description,
+                        },)*
+                    ],
+                },
+            )*
+        ];
+
+        impl AiCommand {
+            /// The spec the command is checked against
+            pub fn spec(&self) -> &'static CommandSpec {
+                let name = match self {
+                    $(AiCommand::$variant { .. } => $name,)*
+                };
+                COMMANDS.iter().find(|spec| spec.name == name).expect("every command has a spec")
+            }
+        }
+    };
+}
+
+ai_commands! {
+    /// A modelling operation the AI asks for. Lengths are in the document unit of the prompt,
+    /// angles in degrees. Targets are a part ID like `p3`, a part or group name, or one of
+    /// `selected`, `all` and `last` (the part created last).
+    CreatePrimitive = "create_primitive", "create a new part" {
+        shape: PrimitiveShape => FieldKind::Choice(PrimitiveShape::NAMES), "kind of primitive";
+        position: [f32; 3] => FieldKind::Vec3, "x, y, z of the minimum corner";
+        size: [f32; 3] => FieldKind::Size, "width (x), height (y) and depth (z)";
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String> => FieldKind::Text, "name later commands can refer to the part by";
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        relative_to: Option<String> => FieldKind::Target, "part the position is measured from, from the minimum corner of its bounds";
+    }
+    Transform = "transform", "move and/or rotate parts" {
+        target: String => FieldKind::Target, "parts to transform";
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        translate: Option<[f32; 3]> => FieldKind::Vec3, "distance to move along x, y and z";
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rotate: Option<[f32; 3]> => FieldKind::Vec3, "degrees to rotate about x, y and z, around the part centre";
+    }
+    ExtrudeFace = "extrude_face", "pull a face of a part outwards, or push it in with a negative distance" {
+        target: String => FieldKind::Target, "part whose face is extruded";
+        face: FaceDirection => FieldKind::Choice(FaceDirection::NAMES), "which face, by the direction it faces";
+        distance: f32 => FieldKind::Number, "how far to extrude";
+    }
+    Boolean = "boolean", "combine parts, a difference takes the tools away from the target" {
+        operation: BooleanOp => FieldKind::Choice(BooleanOp::NAMES), "how to combine them";
+        target: String => FieldKind::Target, "part that is kept and changed, it keeps its ID";
+        tool: String => FieldKind::Target, "parts combined into the target, they are removed";
+    }
+    Delete = "delete", "delete parts" {
+        target: String => FieldKind::Target, "parts to delete";
+    }
+    SetColor = "set_color", "change the colour of parts" {
+        target: String => FieldKind::Target, "parts to colour";
+        color: String => FieldKind::Color, "hex colour";
+    }
+    Group = "group", "put parts in a named group, targets can then use the group name" {
+        name: String => FieldKind::Text, "name of the group";
+        targets: Vec<String> => FieldKind::Targets, "parts to put in the group";
+    }
+}
+
+/// JSON Schema of an answer: an object with a list of commands
+pub fn command_schema() -> Value {
+    let commands: Vec<Value> = COMMANDS.iter()
+        .map(|spec| {
+            let mut schema = parameters_schema(spec);
+            schema["description"] = json!(spec.description);
+            schema["properties"]["command"] = json!({ "const": spec.name });
+            schema["required"].as_array_mut().expect("required is an array").insert(0, json!("command"));
+            schema
+        })
+        .collect();
+    json!({
+        "type": "object",
+        "properties": {
+            "commands": { "type": "array", "items": { "oneOf": commands } },
+            "question": { "type": "string", "description": "asked instead of running commands when the request is unclear" }
+        },
+        "oneOf": [{ "required": ["commands"] }, { "required": ["question"] }],
+        "additionalProperties": false
+    })
+}
+
+/// JSON Schema of the fields of a command, the parameters of its tool
+fn parameters_schema(spec: &CommandSpec) -> Value {
+    let mut properties = Map::new();
+    for field in spec.fields {
+        let mut schema = field.kind.schema();
+        schema["description"] = json!(field.description);
+        properties.insert(field.name.to_string(), schema);
+    }
+    let required: Vec<&str> = spec.fields.iter().filter(|field| field.required).map(|field| field.name).collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false
+    })
+}
+
+/// The commands as tools for providers with function calling
+pub fn tool_declarations() -> Vec<ToolDeclaration> {
+    COMMANDS.iter()
+        .map(|spec| ToolDeclaration { name: spec.name, description: spec.description, parameters: parameters_schema(spec) })
+        .collect()
+}
+
+const TARGETS_HELP: &str = "Targets are a part ID like \"p3\", a part or group name, \"selected\" for the selected parts, \
+     \"all\" for every part or \"last\" for the part created last. The Y axis points up.\n";
+
+/// Instructions sent with every request when the commands are offered as tools
+pub fn tool_prompt() -> String {
+    format!(
+        "You operate a CAD application through the functions you are given. Carry out each request by calling \
+         them, they run in order and their results come back to you, so you can build step by step. \
+         {}When a request is unclear or misses a size you cannot choose sensibly, ask in text without calling \
+         any function. When done, reply with one short sentence on what you built.\n",
+        TARGETS_HELP,
+    )
+}
+
+/// Instructions sent with every request, ahead of the conversation
+pub fn command_prompt() -> String {
+    let mut prompt = String::from(
+        "You operate a CAD application. Answer every request with one JSON object, and nothing else, \
+         matching this JSON Schema:\n",
+    );
+    prompt.push_str(&command_schema().to_string());
+    prompt.push_str("\n\nCommands, run in order:\n");
+    for spec in COMMANDS {
+        prompt.push_str(&format!("- {}: {}\n", spec.name, spec.description));
+        for field in spec.fields {
+            let optional = if field.required { "" } else { " (optional)" };
+            prompt.push_str(&format!("    {}{}: {}\n", field.name, optional, field.description));
+        }
+    }
+    prompt.push_str(TARGETS_HELP);
+    prompt.push_str(
+        "Example: {\"commands\": [{\"command\": \"create_primitive\", \"shape\": \"box\", \"position\": [0, 0, 0], \
+         \"size\": [2, 1, 1], \"name\": \"base\"}, {\"command\": \"set_color\", \"target\": \"base\", \"color\": \"#3080ff\"}]}\n\
+         When a request is unclear or misses a size you cannot choose sensibly, answer {\"question\": \"...\"} \
+         instead, the next message answers it. Earlier answers are followed by what running them did.\n",
+    );
+    prompt
+}
+
+/// What the model answered a prompt with
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiAnswer {
+    Commands(Vec<AiCommand>),
+    /// The request was unclear, the next prompt answers the question
+    Question(String),
+}
+
+/// Why an answer of the model was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtocolError {
+    InvalidJson(String),
+    MissingCommands,
+    InvalidQuestion,
+    NotAnObject { index: usize },
+    UnknownCommand { index: usize, name: String },
+    MissingField { index: usize, command: &'static str, field: &'static str },
+    UnknownField { index: usize, command: &'static str, field: String },
+    InvalidField { index: usize, command: &'static str, field: &'static str, expected: String },
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Commands are counted from one for people reading the console
+        match self {
+            ProtocolError::InvalidJson(error) => write!(f, "the answer is not valid JSON: {}", error),
+            ProtocolError::MissingCommands => write!(f, "the answer has no \"commands\" list"),
+            ProtocolError::InvalidQuestion => write!(f, "the \"question\" should be a non-empty string"),
+            ProtocolError::NotAnObject { index } => write!(f, "command {} is not an object", index + 1),
+            ProtocolError::UnknownCommand { index, name } => write!(f, "command {}: unknown command '{}'", index + 1, name),
+            ProtocolError::MissingField { index, command, field } => {
+                write!(f, "command {} ({}): missing field '{}'", index + 1, command, field)
+            }
+            ProtocolError::UnknownField { index, command, field } => {
+                write!(f, "command {} ({}): unknown field '{}'", index + 1, command, field)
+            }
+            ProtocolError::InvalidField { index, command, field, expected } => {
+                write!(f, "command {} ({}): '{}' should be {}", index + 1, command, field, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+/// Checks an answer of the model against the protocol, it is either commands or a question
+pub fn parse_answer(text: &str) -> Result<AiAnswer, ProtocolError> {
+    let value: Value = serde_json::from_str(text).map_err(|error| ProtocolError::InvalidJson(error.to_string()))?;
+    if let Some(question) = value.get("question") {
+        return question.as_str()
+            .map(str::trim)
+            .filter(|question| !question.is_empty())
+            .map(|question| AiAnswer::Question(question.to_string()))
+            .ok_or(ProtocolError::InvalidQuestion);
+    }
+    let commands = value.get("commands").and_then(Value::as_array).ok_or(ProtocolError::MissingCommands)?;
+    commands.iter().enumerate().map(|(index, command)| parse_command(index, command)).collect::<Result<_, _>>().map(AiAnswer::Commands)
+}
+
+/// Checks the `index`th call of an answer against the protocol, like a command of the text
+/// protocol named after the tool
+pub fn parse_tool_call(index: usize, call: &ToolCall) -> Result<AiCommand, ProtocolError> {
+    let Value::Object(arguments) = &call.arguments else {
+        return Err(ProtocolError::NotAnObject { index });
+    };
+    let mut command = arguments.clone();
+    command.insert("command".to_string(), json!(call.name));
+    parse_command(index, &Value::Object(command))
+}
+
+fn parse_command(index: usize, value: &Value) -> Result<AiCommand, ProtocolError> {
+    let object = value.as_object().ok_or(ProtocolError::NotAnObject { index })?;
+    let name = object.get("command").and_then(Value::as_str).unwrap_or_default();
+    let spec = COMMANDS.iter()
+        .find(|spec| spec.name == name)
+        .ok_or_else(|| ProtocolError::UnknownCommand { index, name: name.to_string() })?;
+
+    for key in object.keys().filter(|key| *key != "command") {
+        if !spec.fields.iter().any(|field| field.name == key) {
+            return Err(ProtocolError::UnknownField { index, command: spec.name, field: key.clone() });
+        }
+    }
+    for field in spec.fields {
+        match object.get(field.name) {
+            None | Some(Value::Null) if field.required => {
+                return Err(ProtocolError::MissingField { index, command: spec.name, field: field.name });
+            }
+            None | Some(Value::Null) => {}
+            Some(value) if !field.kind.accepts(value) => {
+                return Err(ProtocolError::InvalidField { index, command: spec.name, field: field.name, expected: field.kind.expected() });
+            }
+            Some(_) => {}
+        }
+    }
+
+    let command: AiCommand = serde_json::from_value(value.clone()).map_err(|error| ProtocolError::InvalidField {
+        index,
+        command: spec.name,
+        field: "command",
+        expected: error.to_string(),
+    })?;
+    if let AiCommand::Transform { translate: None, rotate: None, .. } = command {
+        return Err(ProtocolError::InvalidField {
+            index,
+            command: spec.name,
+            field: "translate",
+            expected: "given, or 'rotate'".to_string(),
+        });
+    }
+    Ok(command)
+}
+
+/// A `#rrggbb` colour
+pub fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    Srgba::hex(hex).ok().map(Color::from)
+}
+
+/// What a command target can match a part by
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PartRef<'a> {
+    pub id: Option<u32>,
+    pub name: Option<&'a str>,
+    pub group: Option<&'a str>,
+    pub selected: bool,
+    /// Created by the last `create_primitive`
+    pub last: bool,
+}
+
+/// How parts are referred to in prompts and targets
+pub fn part_id_text(id: u32) -> String {
+    format!("p{}", id)
+}
+
+/// Whether a command target addresses the part
+pub fn target_matches(target: &str, part: &PartRef) -> bool {
+    match target.to_lowercase().as_str() {
+        "all" => true,
+        "selected" => part.selected,
+        "last" => part.last,
+        _ => {
+            part.id.is_some_and(|id| part_id_text(id).eq_ignore_ascii_case(target))
+                || [part.name, part.group].into_iter().flatten().any(|known| known.eq_ignore_ascii_case(target))
+        }
+    }
+}
+
+/// Commands of AI answers waiting to run, one per frame so the parts one command creates
+/// exist when the next refers to them. With `review` set an answer first waits for the user
+/// to accept it.
+#[derive(Resource)]
+pub struct AiCommandQueue {
+    commands: VecDeque<QueuedCommand>,
+    /// Commands of the last answer, previewed until they are accepted or rejected
+    pending: Vec<QueuedCommand>,
+    pub review: bool,
+    /// Part created by the last `create_primitive`, the `last` target
+    pub last_created: Option<Entity>,
+}
+
+impl Default for AiCommandQueue {
+    fn default() -> Self {
+        AiCommandQueue {
+            commands: VecDeque::new(),
+            pending: Vec::new(),
+            review: true,
+            last_created: None,
+        }
+    }
+}
+
+pub struct QueuedCommand {
+    pub command: AiCommand,
+    /// Document unit of the prompt, the lengths of the command are in it
+    pub unit: LengthUnit,
+    /// The tool call the command came from, its result goes back to the model
+    pub call: Option<ToolCall>,
+}
+
+impl AiCommandQueue {
+    /// Queues the commands of one answer, their lengths are in `unit`
+    pub fn extend(&mut self, commands: Vec<AiCommand>, unit: LengthUnit) {
+        self.add(commands.into_iter().map(|command| QueuedCommand { command, unit, call: None }));
+    }
+
+    /// Queues the tool calls of one answer with the commands they were parsed into
+    pub fn extend_calls(&mut self, calls: Vec<(AiCommand, ToolCall)>, unit: LengthUnit) {
+        self.add(calls.into_iter().map(|(command, call)| QueuedCommand { command, unit, call: Some(call) }));
+    }
+
+    fn add(&mut self, commands: impl Iterator<Item = QueuedCommand>) {
+        if self.review {
+            self.pending.extend(commands);
+        } else {
+            self.commands.extend(commands);
+        }
+    }
+
+    /// Commands waiting to be accepted or rejected
+    pub fn pending(&self) -> &[QueuedCommand] {
+        &self.pending
+    }
+
+    /// Lets the pending commands run, returns how many there were
+    pub fn accept(&mut self) -> usize {
+        let count = self.pending.len();
+        self.commands.extend(self.pending.drain(..));
+        count
+    }
+
+    /// Drops the pending commands, they are returned
+    pub fn reject(&mut self) -> Vec<QueuedCommand> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Nothing to run or review
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.pending.is_empty()
+    }
+
+    pub fn pop(&mut self) -> Option<QueuedCommand> {
+        self.commands.pop_front()
+    }
+}
@@ -0,0 +1,242 @@
+use std::{fs, io, path::Path};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What stale part IDs in a loaded conversation are replaced with
+const EARLIER_PART: &str = "a part of an earlier session";
+
+/// How many messages go with a request, older ones are kept but not sent
+pub const MAX_HISTORY_MESSAGES: usize = 20;
+/// How many times the model may call tools and get the results back for one prompt
+pub const MAX_TOOL_ROUNDS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+    /// The result of a tool call, sent back to the model
+    Tool,
+}
+
+/// A modelling operation the model called through the provider's function calling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Given by the provider, or numbered for providers without call IDs
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    pub role: Role,
+    pub text: String,
+    /// What running an answer did to the document
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+    /// Operations an answer called, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// The call a tool result is for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call: Option<ToolCall>,
+}
+
+impl Message {
+    pub fn user(text: impl Into<String>) -> Self {
+        Message::new(Role::User, text.into())
+    }
+
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Message::new(Role::Assistant, text.into())
+    }
+
+    pub fn tool_calls(text: impl Into<String>, tool_calls: Vec<ToolCall>) -> Self {
+        Message { tool_calls, ..Message::assistant(text) }
+    }
+
+    pub fn tool_result(call: ToolCall, result: impl Into<String>) -> Self {
+        Message { tool_call: Some(call), ..Message::new(Role::Tool, result.into()) }
+    }
+
+    fn new(role: Role, text: String) -> Self {
+        Message { role, text, actions: Vec::new(), tool_calls: Vec::new(), tool_call: None }
+    }
+
+    /// The text sent to the model, an answer followed by what came of it
+    pub fn content(&self) -> String {
+        if self.actions.is_empty() {
+            self.text.clone()
+        } else {
+            format!("{}\n[Result: {}]", self.text, self.actions.join("; "))
+        }
+    }
+}
+
+/// The prompts of the console and the answers of the model since the last new
+/// conversation. Each request sends the recent history, so a prompt can refer to earlier
+/// ones and answer a question the model asked.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Conversation {
+    messages: Vec<Message>,
+    /// Session the part IDs in the messages were numbered in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session: Option<u64>,
+}
+
+// Parts are numbered anew every run of the application, so part IDs are only valid in the
+// session that numbered them
+static SESSION: OnceLock<u64> = OnceLock::new();
+
+fn current_session() -> u64 {
+    *SESSION.get_or_init(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos() as u64))
+}
+
+impl Conversation {
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    pub fn push_user(&mut self, text: impl Into<String>) {
+        self.messages.push(Message::user(text));
+    }
+
+    pub fn push_reply(&mut self, text: impl Into<String>) {
+        self.messages.push(Message::assistant(text));
+    }
+
+    pub fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+
+    /// Answers with tool calls since the last prompt, each is a round trip to the model
+    pub fn tool_rounds(&self) -> usize {
+        self.messages.iter()
+            .rev()
+            .take_while(|message| message.role != Role::User)
+            .filter(|message| !message.tool_calls.is_empty())
+            .count()
+    }
+
+    /// Notes what the last answer did, nothing if the model has not answered yet
+    pub fn record_action(&mut self, action: impl Into<String>) {
+        if let Some(message) = self.messages.last_mut().filter(|message| message.role == Role::Assistant) {
+            message.actions.push(action.into());
+        }
+    }
+
+    /// Drops a prompt the model never answered, after a failed or cancelled request
+    pub fn discard_unanswered(&mut self) -> bool {
+        if self.messages.last().is_some_and(|message| message.role == Role::User) {
+            self.messages.pop();
+            return true;
+        }
+        false
+    }
+
+    /// The messages sent with a request, the most recent ones starting with a prompt. The last
+    /// prompt and everything after it are always kept, however many tool calls a build took,
+    /// only older turns are trimmed to `MAX_HISTORY_MESSAGES`.
+    pub fn history(&self) -> &[Message] {
+        let last_prompt = self.messages.iter()
+            .rposition(|message| message.role == Role::User)
+            .unwrap_or(self.messages.len());
+        let cut = self.messages.len().saturating_sub(MAX_HISTORY_MESSAGES);
+        let start = (cut..last_prompt)
+            .find(|&index| self.messages[index].role == Role::User)
+            .unwrap_or(last_prompt);
+        &self.messages[start..]
+    }
+
+    /// Loads a conversation to continue it. The part IDs of an earlier session refer to parts
+    /// that no longer exist, or to different ones, so they are replaced; the second value tells
+    /// whether there were any.
+    pub fn load(path: &Path) -> io::Result<(Self, bool)> {
+        let text = fs::read_to_string(path)?;
+        let mut conversation = Conversation::from_json(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let forgot = conversation.session != Some(current_session()) && conversation.forget_part_ids();
+        conversation.session = None;
+        Ok((conversation, forgot))
+    }
+
+    /// Saves the conversation with the session its part IDs belong to
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let conversation = Conversation { session: Some(current_session()), ..self.clone() };
+        fs::write(path, conversation.to_json())
+    }
+
+    /// Replaces the part IDs in every message, returns whether there were any
+    pub fn forget_part_ids(&mut self) -> bool {
+        let mut forgot = false;
+        for message in &mut self.messages {
+            let texts = std::iter::once(&mut message.text).chain(&mut message.actions);
+            for text in texts {
+                forgot |= replace_part_ids(text);
+            }
+            let calls = message.tool_calls.iter_mut().chain(&mut message.tool_call);
+            for call in calls {
+                forgot |= replace_part_ids_in_value(&mut call.arguments);
+            }
+        }
+        forgot
+    }
+
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("conversation serializes to JSON")
+    }
+}
+
+// Replaces the part IDs like `p3` in a text with EARLIER_PART
+fn replace_part_ids(text: &mut String) -> bool {
+    let mut replaced = String::with_capacity(text.len());
+    let mut found = false;
+    let mut rest = text.as_str();
+    while let Some((index, _)) = rest.char_indices().find(|(index, c)| {
+        let word_start = !rest[..*index].chars().next_back().is_some_and(|before| before.is_alphanumeric() || before == '_');
+        (*c == 'p' || *c == 'P') && word_start && id_length(&rest[index + 1..]) > 0
+    }) {
+        replaced.push_str(&rest[..index]);
+        replaced.push_str(EARLIER_PART);
+        rest = &rest[index + 1 + id_length(&rest[index + 1..])..];
+        found = true;
+    }
+    if found {
+        replaced.push_str(rest);
+        *text = replaced;
+    }
+    found
+}
+
+// Length of the number at the start of `text` when it ends a word, else zero
+fn id_length(text: &str) -> usize {
+    let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let ends_word = !text[digits..].chars().next().is_some_and(|after| after.is_alphanumeric() || after == '_');
+    if ends_word { digits } else { 0 }
+}
+
+fn replace_part_ids_in_value(value: &mut Value) -> bool {
+    match value {
+        Value::String(text) => replace_part_ids(text),
+        Value::Array(items) => items.iter_mut().fold(false, |found, item| replace_part_ids_in_value(item) | found),
+        Value::Object(fields) => fields.values_mut().fold(false, |found, field| replace_part_ids_in_value(field) | found),
+        _ => false,
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::{env, fmt, fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::tools::preferences::config_dir;
+use super::llm_provider::ProviderKind;
+
+const CREDENTIALS_FILE: &str = "credentials.json";
+
+/// API keys per provider. Keys are never printed, `Debug` only lists the providers.
+#[derive(Resource, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Credentials {
+    /// Keys kept in the credentials file, by provider name
+    keys: BTreeMap<String, String>,
+    /// Keys entered in the settings for this session only
+    #[serde(skip)]
+    session: BTreeMap<String, String>,
+}
+
+/// Where the key in use came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySource {
+    Settings,
+    Environment(&'static str),
+    ConfigFile,
+}
+
+impl fmt::Display for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySource::Settings => write!(f, "the AI settings"),
+            KeySource::Environment(variable) => write!(f, "${}", variable),
+            KeySource::ConfigFile => write!(f, "the credentials file"),
+        }
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("keys", &self.keys.keys().collect::<Vec<_>>())
+            .field("session", &self.session.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// Reads the credentials file, no keys if it is missing or unreadable
+    pub fn load() -> Self {
+        let Some(path) = credentials_path() else {
+            return Credentials::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Credentials::from_json(&text).unwrap_or_else(|err| {
+                warn!("Ignoring invalid credentials file {}: {}", path.display(), err);
+                Credentials::default()
+            }),
+            Err(_) => Credentials::default(),
+        }
+    }
+
+    /// Writes the stored keys, readable by the user only
+    pub fn save(&self) -> io::Result<()> {
+        let path = credentials_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_private(&path, &self.to_json())
+    }
+
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("credentials serialize to JSON")
+    }
+
+    /// Uses `key` for this session, and from then on if `remember` is set. An empty key
+    /// forgets the provider's key.
+    pub fn set_key(&mut self, provider: ProviderKind, key: &str, remember: bool) {
+        let key = key.trim();
+        if key.is_empty() {
+            self.session.remove(provider.name());
+            self.keys.remove(provider.name());
+            return;
+        }
+        self.session.insert(provider.name().to_string(), key.to_string());
+        if remember {
+            self.keys.insert(provider.name().to_string(), key.to_string());
+        }
+    }
+
+    /// The key for `provider`: entered in the settings, else from its environment variables,
+    /// else from the credentials file
+    pub fn resolve(&self, provider: ProviderKind, env_var: impl Fn(&str) -> Option<String>) -> Option<(String, KeySource)> {
+        let non_empty = |key: &String| !key.trim().is_empty();
+        if let Some(key) = self.session.get(provider.name()).filter(|key| non_empty(key)) {
+            return Some((key.clone(), KeySource::Settings));
+        }
+        for variable in provider.key_env_vars() {
+            if let Some(key) = env_var(variable).filter(non_empty) {
+                return Some((key, KeySource::Environment(variable)));
+            }
+        }
+        self.keys.get(provider.name())
+            .filter(|key| non_empty(key))
+            .map(|key| (key.clone(), KeySource::ConfigFile))
+    }
+}
+
+/// Reads a variable from the process environment, for `Credentials::resolve`
+pub fn process_env(variable: &str) -> Option<String> {
+    env::var(variable).ok()
+}
+
+fn credentials_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CREDENTIALS_FILE))
+}
+
+#[cfg(unix)]
+fn write_private(path: &std::path::Path, contents: &str) -> io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // The mode only applies to new files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &std::path::Path, contents: &str) -> io::Result<()> {
+    // The per user config directory is private to the user on Windows
+    fs::write(path, contents)
+}
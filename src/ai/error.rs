@@ -0,0 +1,150 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::commands::ProtocolError;
+
+/// Why an AI request gave no usable answer. The messages are written for the console.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiError {
+    /// The service could not be reached
+    Network(String),
+    Timeout(Duration),
+    /// The API key was refused
+    Auth { status: u16, message: String },
+    /// The account is out of quota or credit, waiting does not help
+    Quota(String),
+    /// Too many requests, `retry_after` is how long the service asked to wait
+    RateLimited { retry_after: Option<Duration>, message: String },
+    /// Any other error status
+    Http { status: u16, message: String },
+    /// The response is not what the provider's API sends
+    MalformedResponse(String),
+    /// The answer does not follow the command protocol
+    Validation(ProtocolError),
+}
+
+impl AiError {
+    /// Classifies an error response by its status, the body and the `Retry-After` header
+    pub fn from_status(status: u16, body: &str, retry_after: Option<Duration>) -> Self {
+        let message = error_message(body);
+        match status {
+            401 | 403 => AiError::Auth { status, message },
+            402 => AiError::Quota(message),
+            // Providers answer 429 for both, only a used up quota says so
+            429 if ["insufficient_quota", "quota exceeded", "billing"].iter().any(|hint| body.to_lowercase().contains(hint)) => {
+                AiError::Quota(message)
+            }
+            429 => AiError::RateLimited { retry_after: retry_after.or_else(|| retry_delay(body)), message },
+            _ => AiError::Http { status, message },
+        }
+    }
+
+    /// Whether the same request may succeed later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AiError::Network(_) | AiError::Timeout(_) | AiError::RateLimited { .. } => true,
+            AiError::Http { status, .. } => *status >= 500 || *status == 408,
+            AiError::Auth { .. } | AiError::Quota(_) | AiError::MalformedResponse(_) | AiError::Validation(_) => false,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AiError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiError::Network(error) => write!(f, "could not reach the AI service: {}", error),
+            AiError::Timeout(timeout) => write!(
+                f,
+                "no answer within {} s, the timeout can be raised in Window > AI settings",
+                timeout.as_secs_f32(),
+            ),
+            AiError::Auth { status, message } => write!(
+                f,
+                "the API key was refused ({}): {}. Check it in Window > AI settings",
+                status, message,
+            ),
+            AiError::Quota(message) => write!(f, "the account is out of quota: {}", message),
+            AiError::RateLimited { retry_after: Some(wait), message } => {
+                write!(f, "rate limited, try again in {} s: {}", wait.as_secs_f32().ceil(), message)
+            }
+            AiError::RateLimited { retry_after: None, message } => write!(f, "rate limited: {}", message),
+            AiError::Http { status, message } => write!(f, "the AI service answered {}: {}", status, message),
+            AiError::MalformedResponse(error) => write!(f, "the AI service sent an unreadable response: {}", error),
+            AiError::Validation(error) => write!(f, "the answer was rejected: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for AiError {}
+
+impl From<ProtocolError> for AiError {
+    fn from(error: ProtocolError) -> Self {
+        AiError::Validation(error)
+    }
+}
+
+/// When to send a failed request again
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for every further one
+    pub base_delay: Duration,
+    /// Longest wait, a service asking for more is not retried
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retry number `retry` (from zero) after `error`, `None` to give up
+    pub fn delay(&self, retry: u32, error: &AiError) -> Option<Duration> {
+        if retry >= self.max_retries || !error.is_retryable() {
+            return None;
+        }
+        let wait = error.retry_after().unwrap_or_else(|| self.base_delay.saturating_mul(2u32.saturating_pow(retry)));
+        (wait <= self.max_delay).then_some(wait)
+    }
+}
+
+/// `Retry-After` in seconds, the HTTP date form is not used by the LLM APIs
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<f32>().ok().filter(|seconds| seconds.is_finite() && *seconds >= 0.0).map(Duration::from_secs_f32)
+}
+
+/// The message of a JSON error body, `{"error": {"message": ...}}` for Gemini and OpenAI,
+/// `{"error": ...}` for Ollama, else the body itself
+fn error_message(body: &str) -> String {
+    let value: Value = serde_json::from_str(body).unwrap_or_default();
+    let error = &value["error"];
+    error["message"].as_str()
+        .or_else(|| error.as_str())
+        .unwrap_or(body)
+        .trim()
+        .to_string()
+}
+
+/// The wait Gemini puts in the body of a 429, e.g. `"retryDelay": "7s"`
+fn retry_delay(body: &str) -> Option<Duration> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    value["error"]["details"].as_array()?
+        .iter()
+        .find_map(|detail| detail["retryDelay"].as_str())
+        .and_then(|delay| parse_retry_after(delay.trim_end_matches('s')))
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Recorded HTTP exchanges with an LLM provider. `/ai record <file>` writes them from real
+/// requests, tests replay them from a local mock server so they run without network.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fixture {
+    /// Provider name, see `ProviderKind::name`
+    pub provider: String,
+    /// The prompt that started the recording
+    pub prompt: String,
+    pub exchanges: Vec<Exchange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordedRequest {
+    /// Path and query below the base URL
+    pub path: String,
+    pub body: Value,
+}
+
+/// A response as the server sent it. Headers are kept for replay only, request headers
+/// carry the API key and are never recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// The body when it is JSON
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub body: Value,
+    /// The body when it is not JSON, for malformed answers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    /// Replay only, how long the server waits before answering
+    #[serde(skip_serializing_if = "is_zero")]
+    pub delay_ms: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl Default for RecordedResponse {
+    fn default() -> Self {
+        RecordedResponse {
+            status: 200,
+            headers: BTreeMap::new(),
+            body: Value::Null,
+            raw: None,
+            delay_ms: 0,
+        }
+    }
+}
+
+impl RecordedResponse {
+    pub fn json(status: u16, body: Value) -> Self {
+        RecordedResponse { status, body, ..Default::default() }
+    }
+
+    /// Keeps the body as JSON when it parses, as it was sent otherwise
+    pub fn from_text(status: u16, text: &str) -> Self {
+        match serde_json::from_str(text) {
+            Ok(body) => RecordedResponse::json(status, body),
+            Err(_) => RecordedResponse { status, raw: Some(text.to_string()), ..Default::default() },
+        }
+    }
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Fixture::from_json(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("fixture serializes to JSON")
+    }
+
+    /// Adds an exchange to the fixture in `path`, starting one if there is none yet
+    pub fn append(path: &Path, provider: &str, prompt: &str, exchange: Exchange) -> io::Result<()> {
+        let mut fixture = match Fixture::load(path) {
+            Ok(fixture) => fixture,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Fixture {
+                provider: provider.to_string(),
+                prompt: prompt.to_string(),
+                exchanges: Vec::new(),
+            },
+            Err(err) => return Err(err),
+        };
+        fixture.exchanges.push(exchange);
+        fixture.save(path)
+    }
+}
@@ -0,0 +1,386 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::conversation::{Message, Role, ToolCall};
+
+/// The backends an `AiClient` can talk to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProviderKind {
+    #[default]
+    Gemini,
+    /// Any endpoint speaking the OpenAI chat completions API, local servers like
+    /// llama.cpp and vLLM included
+    OpenAi,
+    Ollama,
+}
+
+impl ProviderKind {
+    pub const ALL: [ProviderKind; 3] = [ProviderKind::Gemini, ProviderKind::OpenAi, ProviderKind::Ollama];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProviderKind::Gemini => "gemini",
+            ProviderKind::OpenAi => "openai",
+            ProviderKind::Ollama => "ollama",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProviderKind::Gemini => "Gemini",
+            ProviderKind::OpenAi => "OpenAI-compatible",
+            ProviderKind::Ollama => "Ollama",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        ProviderKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            ProviderKind::Gemini => "https://generativelanguage.googleapis.com",
+            ProviderKind::OpenAi => "https://api.openai.com/v1",
+            ProviderKind::Ollama => "http://localhost:11434",
+        }
+    }
+
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            ProviderKind::Gemini => "gemini-1.5-flash",
+            ProviderKind::OpenAi => "gpt-4o-mini",
+            ProviderKind::Ollama => "llama3.1",
+        }
+    }
+
+    /// Environment variables the API key is read from, in order
+    pub fn key_env_vars(&self) -> &'static [&'static str] {
+        match self {
+            ProviderKind::Gemini => &["GEMINI_API_KEY", "GOOGLE_API_KEY"],
+            ProviderKind::OpenAi => &["OPENAI_API_KEY"],
+            ProviderKind::Ollama => &["OLLAMA_API_KEY"],
+        }
+    }
+
+    pub fn provider(&self) -> Box<dyn LlmProvider> {
+        match self {
+            ProviderKind::Gemini => Box::new(Gemini),
+            ProviderKind::OpenAi => Box::new(OpenAiCompatible),
+            ProviderKind::Ollama => Box::new(Ollama),
+        }
+    }
+}
+
+/// Which backend to call and how
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmConfig {
+    pub provider: ProviderKind,
+    pub model: String,
+    pub base_url: String,
+    pub temperature: f32,
+    pub timeout: Duration,
+    /// Offer the modelling operations as tools, off for models without function calling,
+    /// which then answer with the JSON command protocol in the text
+    pub use_tools: bool,
+}
+
+impl LlmConfig {
+    /// The provider's default endpoint and model
+    pub fn for_provider(provider: ProviderKind) -> Self {
+        LlmConfig {
+            provider,
+            model: provider.default_model().to_string(),
+            base_url: provider.default_base_url().to_string(),
+            temperature: 0.1,
+            timeout: Duration::from_secs(30),
+            use_tools: true,
+        }
+    }
+
+    /// Switches to another provider's default endpoint and model, they mean nothing to
+    /// other providers, keeping the other settings
+    pub fn with_provider(&self, provider: ProviderKind) -> Self {
+        LlmConfig {
+            temperature: self.temperature,
+            timeout: self.timeout,
+            use_tools: self.use_tools,
+            ..LlmConfig::for_provider(provider)
+        }
+    }
+
+    /// Gemini and the OpenAI service need a key, local OpenAI-compatible servers and
+    /// Ollama do not
+    pub fn requires_key(&self) -> bool {
+        match self.provider {
+            ProviderKind::Gemini => true,
+            ProviderKind::OpenAi => self.base_url.trim_end_matches('/') == ProviderKind::OpenAi.default_base_url(),
+            ProviderKind::Ollama => false,
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path)
+    }
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        LlmConfig::for_provider(ProviderKind::default())
+    }
+}
+
+/// An HTTP request ready to be sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmRequest {
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Value,
+}
+
+/// A function the model may call, `parameters` is the JSON Schema of its arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolDeclaration {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: Value,
+}
+
+/// The model's answer: text, tool calls, or both
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelReply {
+    pub text: String,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Request format and response shape of one LLM API. The client does the sending, so
+/// providers stay plain data transformations.
+pub trait LlmProvider: Send + Sync {
+    /// Request asking the model to answer the last of `messages` following `instructions`,
+    /// it may call `tools`. The key may be empty for local servers.
+    fn request(&self, config: &LlmConfig, api_key: &str, instructions: &str, messages: &[Message], tools: &[ToolDeclaration]) -> LlmRequest;
+
+    /// The model's answer in a successful response
+    fn reply(&self, response: &Value) -> Option<ModelReply>;
+}
+
+pub struct Gemini;
+
+impl LlmProvider for Gemini {
+    fn request(&self, config: &LlmConfig, api_key: &str, instructions: &str, messages: &[Message], tools: &[ToolDeclaration]) -> LlmRequest {
+        let mut contents: Vec<Value> = Vec::new();
+        for message in messages {
+            let text = json!({ "text": message.content() });
+            let (role, mut parts) = match message.role {
+                Role::User => ("user", vec![text]),
+                Role::Assistant => {
+                    let calls = message.tool_calls.iter().map(|call| json!({ "functionCall": { "name": call.name, "args": call.arguments } }));
+                    let text = (!message.text.is_empty() || message.tool_calls.is_empty()).then_some(text);
+                    ("model", text.into_iter().chain(calls).collect())
+                }
+                Role::Tool => {
+                    let name = message.tool_call.as_ref().map_or("", |call| call.name.as_str());
+                    ("user", vec![json!({ "functionResponse": { "name": name, "response": { "result": message.text } } })])
+                }
+            };
+            // The results of one answer's calls go back together in one turn
+            match contents.last_mut() {
+                Some(last) if message.role == Role::Tool && last["parts"][0].get("functionResponse").is_some() => {
+                    last["parts"].as_array_mut().expect("parts is an array").append(&mut parts);
+                }
+                _ => contents.push(json!({ "role": role, "parts": parts })),
+            }
+        }
+        let mut body = json!({
+            "systemInstruction": { "parts": [{ "text": instructions }] },
+            "contents": contents,
+            "generationConfig": {
+                "temperature": config.temperature,
+                "topK": 1,
+                "topP": 1
+            }
+        });
+        if !tools.is_empty() {
+            let declarations: Vec<Value> = tools.iter()
+                .map(|tool| json!({ "name": tool.name, "description": tool.description, "parameters": gemini_schema(&tool.parameters) }))
+                .collect();
+            body["tools"] = json!([{ "functionDeclarations": declarations }]);
+        }
+        LlmRequest {
+            url: config.endpoint(&format!("v1beta/models/{}:generateContent", config.model)),
+            // In a header rather than the query, so the key does not end up in logged URLs
+            headers: vec![("x-goog-api-key", api_key.to_string())],
+            body,
+        }
+    }
+
+    fn reply(&self, response: &Value) -> Option<ModelReply> {
+        let parts = response["candidates"][0]["content"]["parts"].as_array()?;
+        let mut reply = ModelReply::default();
+        for part in parts {
+            if let Some(text) = part["text"].as_str() {
+                reply.text.push_str(text);
+            }
+            if let Some(call) = part.get("functionCall") {
+                reply.tool_calls.push(ToolCall {
+                    // Gemini matches results to calls by order
+                    id: format!("call_{}", reply.tool_calls.len() + 1),
+                    name: call["name"].as_str()?.to_string(),
+                    arguments: call.get("args").cloned().unwrap_or_else(|| json!({})),
+                });
+            }
+        }
+        Some(reply)
+    }
+}
+
+/// Gemini takes an OpenAPI subset for function parameters, other keywords are rejected
+fn gemini_schema(schema: &Value) -> Value {
+    const SUPPORTED: &[&str] = &["type", "description", "enum", "items", "properties", "required", "minItems", "maxItems", "nullable"];
+    match schema {
+        Value::Object(object) => Value::Object(
+            object.iter()
+                .filter(|(key, _)| SUPPORTED.contains(&key.as_str()))
+                .map(|(key, value)| {
+                    let value = match key.as_str() {
+                        "items" => gemini_schema(value),
+                        "properties" => Value::Object(
+                            value.as_object().into_iter().flatten().map(|(name, property)| (name.clone(), gemini_schema(property))).collect(),
+                        ),
+                        _ => value.clone(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+pub struct OpenAiCompatible;
+
+impl LlmProvider for OpenAiCompatible {
+    fn request(&self, config: &LlmConfig, api_key: &str, instructions: &str, messages: &[Message], tools: &[ToolDeclaration]) -> LlmRequest {
+        let headers = if api_key.is_empty() {
+            Vec::new()
+        } else {
+            vec![("Authorization", format!("Bearer {}", api_key))]
+        };
+        let mut body = json!({
+            "model": config.model,
+            "messages": chat_messages(instructions, messages, ChatFormat::OpenAi),
+            "temperature": config.temperature
+        });
+        if !tools.is_empty() {
+            body["tools"] = chat_tools(tools);
+        }
+        LlmRequest {
+            url: config.endpoint("chat/completions"),
+            headers,
+            body,
+        }
+    }
+
+    fn reply(&self, response: &Value) -> Option<ModelReply> {
+        chat_reply(&response["choices"][0]["message"])
+    }
+}
+
+pub struct Ollama;
+
+impl LlmProvider for Ollama {
+    fn request(&self, config: &LlmConfig, _api_key: &str, instructions: &str, messages: &[Message], tools: &[ToolDeclaration]) -> LlmRequest {
+        let mut body = json!({
+            "model": config.model,
+            "messages": chat_messages(instructions, messages, ChatFormat::Ollama),
+            "stream": false,
+            "options": { "temperature": config.temperature }
+        });
+        if !tools.is_empty() {
+            body["tools"] = chat_tools(tools);
+        }
+        LlmRequest {
+            url: config.endpoint("api/chat"),
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    fn reply(&self, response: &Value) -> Option<ModelReply> {
+        chat_reply(&response["message"])
+    }
+}
+
+/// Ollama's chat API follows OpenAI's, except for how tool calls and results look
+#[derive(Clone, Copy, PartialEq)]
+enum ChatFormat {
+    OpenAi,
+    Ollama,
+}
+
+/// Chat messages of the OpenAI and Ollama APIs, the instructions as the system message
+fn chat_messages(instructions: &str, messages: &[Message], format: ChatFormat) -> Vec<Value> {
+    let history = messages.iter().map(|message| match message.role {
+        Role::User => json!({ "role": "user", "content": message.content() }),
+        Role::Assistant if message.tool_calls.is_empty() => json!({ "role": "assistant", "content": message.content() }),
+        Role::Assistant => {
+            let calls: Vec<Value> = message.tool_calls.iter()
+                .map(|call| match format {
+                    // OpenAI sends the arguments as a JSON string
+                    ChatFormat::OpenAi => json!({
+                        "id": call.id,
+                        "type": "function",
+                        "function": { "name": call.name, "arguments": call.arguments.to_string() }
+                    }),
+                    ChatFormat::Ollama => json!({ "function": { "name": call.name, "arguments": call.arguments } }),
+                })
+                .collect();
+            json!({ "role": "assistant", "content": message.content(), "tool_calls": calls })
+        }
+        Role::Tool => {
+            let call = message.tool_call.as_ref();
+            match format {
+                ChatFormat::OpenAi => json!({ "role": "tool", "tool_call_id": call.map(|call| call.id.as_str()), "content": message.text }),
+                ChatFormat::Ollama => json!({ "role": "tool", "tool_name": call.map(|call| call.name.as_str()), "content": message.text }),
+            }
+        }
+    });
+    std::iter::once(json!({ "role": "system", "content": instructions })).chain(history).collect()
+}
+
+fn chat_tools(tools: &[ToolDeclaration]) -> Value {
+    tools.iter()
+        .map(|tool| json!({
+            "type": "function",
+            "function": { "name": tool.name, "description": tool.description, "parameters": tool.parameters }
+        }))
+        .collect()
+}
+
+/// The answer in an OpenAI or Ollama chat message
+fn chat_reply(message: &Value) -> Option<ModelReply> {
+    let text = message["content"].as_str();
+    let calls = message["tool_calls"].as_array();
+    if text.is_none() && calls.is_none() {
+        return None;
+    }
+    let tool_calls = calls.into_iter().flatten()
+        .enumerate()
+        .filter_map(|(index, call)| {
+            let function = &call["function"];
+            let arguments = match &function["arguments"] {
+                // OpenAI gives a JSON string, Ollama an object
+                Value::String(text) => serde_json::from_str(text).unwrap_or(Value::String(text.clone())),
+                Value::Null => json!({}),
+                other => other.clone(),
+            };
+            Some(ToolCall {
+                id: call["id"].as_str().map_or_else(|| format!("call_{}", index + 1), str::to_string),
+                name: function["name"].as_str()?.to_string(),
+                arguments,
+            })
+        })
+        .collect();
+    Some(ModelReply { text: text.unwrap_or_default().to_string(), tool_calls })
+}
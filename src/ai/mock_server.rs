@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+
+use super::fixtures::{Fixture, RecordedRequest, RecordedResponse};
+
+/// Local HTTP server answering with recorded responses in order, so the AI client can be
+/// tested without network. Point `LlmConfig::base_url` at `url`.
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    pub fn start(runtime: &Runtime, responses: Vec<RecordedResponse>) -> Self {
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).expect("a free local port");
+        let url = format!("http://{}", listener.local_addr().expect("a bound address"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(VecDeque::from(responses)));
+        let recorded = requests.clone();
+        runtime.spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                // The client opens a connection per request, so connections and responses pair up
+                let response = responses.lock().unwrap().pop_front();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, response, recorded).await;
+                });
+            }
+        });
+        MockServer { url, requests }
+    }
+
+    /// Answers with the responses of a recorded fixture
+    pub fn replay(runtime: &Runtime, fixture: &Fixture) -> Self {
+        MockServer::start(runtime, fixture.exchanges.iter().map(|exchange| exchange.response.clone()).collect())
+    }
+
+    /// The requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    response: Option<RecordedResponse>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+) -> std::io::Result<()> {
+    let mut data = Vec::new();
+    let mut buffer = [0; 4096];
+    let head_end = loop {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        data.extend_from_slice(&buffer[..read]);
+        if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let content_length = head.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while data.len() < head_end + content_length {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&buffer[..read]);
+    }
+    let path = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let body = serde_json::from_slice(&data[head_end..]).unwrap_or_default();
+    requests.lock().unwrap().push(RecordedRequest { path, body });
+
+    let response = response.unwrap_or_else(|| RecordedResponse {
+        raw: Some("no recorded response left".to_string()),
+        ..RecordedResponse::json(500, serde_json::Value::Null)
+    });
+    tokio::time::sleep(Duration::from_millis(response.delay_ms)).await;
+    let body = match &response.raw {
+        Some(raw) => raw.clone(),
+        None if response.body.is_null() => String::new(),
+        None => response.body.to_string(),
+    };
+    let reason = StatusCode::from_u16(response.status).ok().and_then(|status| status.canonical_reason()).unwrap_or("");
+    let mut reply = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason,
+        body.len(),
+    );
+    for (name, value) in &response.headers {
+        reply.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    reply.push_str("\r\n");
+    reply.push_str(&body);
+    stream.write_all(reply.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -1,10 +1,30 @@
 pub mod ai_client;
-pub mod secretive_secret;
-pub mod json_parser;
-#[cfg(test)]
-pub mod test_json_parser;
-mod ai_command_to_part;
-
-pub use json_parser::parse_cubes_command;
-pub use ai_command_to_part::{process_console_ai_command};
-
+pub mod commands;
+pub mod conversation;
+pub mod credentials;
+pub mod error;
+pub mod fixtures;
+pub mod llm_provider;
+pub mod preview;
+pub mod requests;
+pub mod scene;
+#[cfg(test)]
+pub mod test_commands;
+#[cfg(test)]
+pub mod test_llm_provider;
+#[cfg(test)]
+pub mod test_credentials;
+#[cfg(test)]
+pub mod test_requests;
+#[cfg(test)]
+pub mod test_scene;
+#[cfg(test)]
+pub mod test_conversation;
+#[cfg(test)]
+pub mod test_preview;
+#[cfg(test)]
+pub mod mock_server;
+#[cfg(test)]
+pub mod test_ai_client;
+#[cfg(test)]
+pub mod test_error;
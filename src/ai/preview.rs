@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+use crate::tools::units::DocumentUnits;
+use super::commands::{AiCommand, QueuedCommand};
+
+/// A box an answer would create, in model units
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostBox {
+    pub name: Option<String>,
+    pub min: Vec3,
+    pub size: Vec3,
+}
+
+impl GhostBox {
+    pub fn volume(&self) -> f32 {
+        self.size.x * self.size.y * self.size.z
+    }
+}
+
+/// The boxes `commands` would create. `part_min` gives the minimum corner of an existing
+/// part for `relative_to`, boxes created earlier in the same answer are found by name or
+/// as `last` first.
+pub fn ghost_boxes<'a>(
+    commands: impl IntoIterator<Item = &'a QueuedCommand>,
+    part_min: impl Fn(&str) -> Option<Vec3>,
+) -> Vec<GhostBox> {
+    let mut boxes: Vec<GhostBox> = Vec::new();
+    for queued in commands {
+        let AiCommand::CreatePrimitive { position, size, name, relative_to, .. } = &queued.command else {
+            continue;
+        };
+        let to_model = |v: [f32; 3]| Vec3::from(v.map(|c| queued.unit.unit_to_model(c)));
+        let offset = relative_to.as_deref().and_then(|target| {
+            let created = if target.eq_ignore_ascii_case("last") {
+                boxes.last()
+            } else {
+                boxes.iter().rev().find(|ghost| ghost.name.as_deref().is_some_and(|name| name.eq_ignore_ascii_case(target)))
+            };
+            created.map(|ghost| ghost.min).or_else(|| part_min(target))
+        });
+        boxes.push(GhostBox {
+            name: name.clone(),
+            min: to_model(*position) + offset.unwrap_or_default(),
+            size: to_model(*size),
+        });
+    }
+    boxes
+}
+
+/// What accepting `commands` would do, e.g. "3 boxes, total volume 6.000 mm³; delete p2"
+pub fn preview_summary(commands: &[QueuedCommand], units: &DocumentUnits) -> String {
+    let boxes = ghost_boxes(commands, |_| None);
+    let mut parts = Vec::new();
+    if !boxes.is_empty() {
+        let volume: f32 = boxes.iter().map(GhostBox::volume).sum();
+        let noun = if boxes.len() == 1 { "box" } else { "boxes" };
+        parts.push(format!("{} {}, total volume {}", boxes.len(), noun, units.format_volume(volume)));
+    }
+    parts.extend(commands.iter().filter_map(|queued| match &queued.command {
+        AiCommand::CreatePrimitive { .. } => None,
+        AiCommand::Transform { target, .. } => Some(format!("transform {}", target)),
+        AiCommand::ExtrudeFace { target, face, .. } => Some(format!("extrude the {} face of {}", face.name(), target)),
+        AiCommand::Boolean { operation, target, tool } => Some(format!("{:?} {} with {}", operation, target, tool).to_lowercase()),
+        AiCommand::Delete { target } => Some(format!("delete {}", target)),
+        AiCommand::SetColor { target, color } => Some(format!("colour {} {}", target, color)),
+        AiCommand::Group { name, targets } => Some(format!("group {} as {}", targets.join(", "), name)),
+    }));
+    if parts.is_empty() {
+        return "no changes".to_string();
+    }
+    parts.join("; ")
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+use crate::tools::units::LengthUnit;
+use super::error::AiError;
+use super::llm_provider::ModelReply;
+
+pub type AiResult = Result<ModelReply, AiError>;
+
+/// Where a request in flight sends messages for the console, such as retries
+pub type Notices = UnboundedSender<String>;
+
+const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+const SPINNER_FRAME_SECONDS: f32 = 0.1;
+
+/// A prompt typed into the console, waiting for its turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct AiPrompt {
+    pub input: String,
+    /// Document unit when the prompt was typed, lengths in the answer are in it
+    pub unit: LengthUnit,
+    /// Sends the results of the tool calls of the last answer rather than a new prompt,
+    /// `input` is the prompt they were for
+    pub follow_up: bool,
+}
+
+pub struct RunningRequest {
+    pub prompt: AiPrompt,
+    pub started: Instant,
+    handle: JoinHandle<AiResult>,
+    notices: UnboundedReceiver<String>,
+}
+
+impl RunningRequest {
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// Prompts sent to the AI one at a time in the background. The request in flight is polled
+/// every frame, the others wait in order.
+#[derive(Resource, Default)]
+pub struct AiRequests {
+    queue: VecDeque<AiPrompt>,
+    running: Option<RunningRequest>,
+}
+
+impl AiRequests {
+    pub fn push(&mut self, prompt: AiPrompt) {
+        self.queue.push_back(prompt);
+    }
+
+    /// Queues a prompt ahead of the others, for follow ups that continue the last answer
+    pub fn push_front(&mut self, prompt: AiPrompt) {
+        self.queue.push_front(prompt);
+    }
+
+    pub fn running(&self) -> Option<&RunningRequest> {
+        self.running.as_ref()
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Starts the next queued prompt when nothing is in flight
+    pub fn start_next(&mut self, spawn: impl FnOnce(&AiPrompt, Notices) -> JoinHandle<AiResult>) -> Option<&AiPrompt> {
+        if self.running.is_some() {
+            return None;
+        }
+        let prompt = self.queue.pop_front()?;
+        let (sender, notices) = unbounded_channel();
+        let handle = spawn(&prompt, sender);
+        self.running = Some(RunningRequest { prompt, started: Instant::now(), handle, notices });
+        self.running.as_ref().map(|running| &running.prompt)
+    }
+
+    /// The messages the request in flight sent since the last call
+    pub fn take_notices(&mut self) -> Vec<String> {
+        let mut notices = Vec::new();
+        if let Some(running) = &mut self.running {
+            while let Ok(notice) = running.notices.try_recv() {
+                notices.push(notice);
+            }
+        }
+        notices
+    }
+
+    /// The request in flight once it is done, awaiting its handle gives the answer or error
+    /// without blocking
+    pub fn take_finished(&mut self) -> Option<(AiPrompt, JoinHandle<AiResult>)> {
+        if !self.running.as_ref().is_some_and(|running| running.handle.is_finished()) {
+            return None;
+        }
+        let RunningRequest { prompt, handle, .. } = self.running.take()?;
+        Some((prompt, handle))
+    }
+
+    /// Aborts the request in flight, the prompt it was for is returned
+    pub fn cancel(&mut self) -> Option<AiPrompt> {
+        let running = self.running.take()?;
+        running.handle.abort();
+        Some(running.prompt)
+    }
+
+    /// Drops the waiting follow ups, when the answer they continue was rejected
+    pub fn drop_follow_ups(&mut self) {
+        self.queue.retain(|prompt| !prompt.follow_up);
+    }
+
+    /// Drops the waiting prompts, returns how many there were
+    pub fn clear_queue(&mut self) -> usize {
+        let count = self.queue.len();
+        self.queue.clear();
+        count
+    }
+}
+
+/// Spinner character for a request running this long
+pub fn spinner(elapsed: Duration) -> char {
+    let frame = (elapsed.as_secs_f32() / SPINNER_FRAME_SECONDS) as usize;
+    SPINNER_FRAMES[frame % SPINNER_FRAMES.len()]
+}
@@ -0,0 +1,133 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::Serialize;
+
+use crate::part::components::{Face, Part, PartColor, PartGroup, PartId, PartSelection};
+use crate::tools::units::LengthUnit;
+use super::commands::{part_id_text, target_matches, FaceDirection, PartRef};
+
+/// One part as the model sees it, lengths in the prompt's unit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PartSummary {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// World bounding box corners, left out for a part without faces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<[f32; 3]>,
+    pub translation: [f32; 3],
+    /// Degrees about x, y and z
+    pub rotation: [f32; 3],
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub selected: bool,
+    /// Directions of the selected faces
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub selected_faces: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// Scene description sent along with a prompt
+pub fn scene_instruction(parts: &[PartSummary], unit: LengthUnit) -> String {
+    if parts.is_empty() {
+        return " The scene is empty. ".to_string();
+    }
+    format!(
+        " The scene has these parts, lengths in {} and min/max the corners of their world bounds: {} ",
+        unit.symbol(),
+        serde_json::to_string(parts).expect("scene serializes to JSON"),
+    )
+}
+
+/// Millimetre precision is plenty for placing parts and keeps the prompt short
+fn round(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0
+}
+
+fn rounded(v: Vec3) -> [f32; 3] {
+    v.to_array().map(round)
+}
+
+type SceneQueryData<'a> = (
+    Entity,
+    &'a PartId,
+    &'a Part,
+    &'a Transform,
+    Option<&'a Children>,
+    Option<&'a Name>,
+    Option<&'a PartGroup>,
+    Option<&'a PartColor>,
+    Has<PartSelection>,
+);
+
+/// The parts of the document, for describing them to the model
+#[derive(SystemParam)]
+pub struct SceneParts<'w, 's> {
+    parts: Query<'w, 's, SceneQueryData<'static>>,
+    faces: Query<'w, 's, (&'static Face, &'static GlobalTransform)>,
+}
+
+impl SceneParts<'_, '_> {
+    /// The scene text for a prompt whose lengths are in `unit`
+    pub fn describe(&self, unit: LengthUnit) -> String {
+        scene_instruction(&self.summaries(unit), unit)
+    }
+
+    pub fn summaries(&self, unit: LengthUnit) -> Vec<PartSummary> {
+        let to_unit = |v: Vec3| rounded(v.to_array().map(|c| unit.model_to_unit(c)).into());
+        let mut parts: Vec<_> = self.parts.iter().collect();
+        parts.sort_by_key(|(_, id, ..)| id.0);
+        parts.into_iter()
+            .map(|(_, id, part, transform, children, name, group, color, selected)| {
+                let bounds = self.bounds(children);
+                let (x, y, z) = transform.rotation.to_euler(EulerRot::XYZ);
+                PartSummary {
+                    id: part_id_text(id.0),
+                    name: name.map(|name| name.to_string()),
+                    group: group.map(|group| group.0.clone()),
+                    min: bounds.map(|(min, _)| to_unit(min)),
+                    max: bounds.map(|(_, max)| to_unit(max)),
+                    translation: to_unit(transform.translation),
+                    rotation: [x, y, z].map(|angle| round(angle.to_degrees())),
+                    selected,
+                    selected_faces: part.selected_faces.iter()
+                        .map(|face| FaceDirection::nearest(transform.rotation * face.normal).name())
+                        .collect(),
+                    color: color.map(|PartColor(color)| color.to_srgba().to_hex()),
+                }
+            })
+            .collect()
+    }
+
+    /// Minimum corner of the world bounds of the first part `target` matches, `last` is
+    /// the part the `last` target stands for
+    pub fn min_corner(&self, target: &str, last: Option<Entity>) -> Option<Vec3> {
+        self.parts.iter()
+            .find(|(entity, id, _, _, _, name, group, _, selected)| {
+                let part = PartRef {
+                    id: Some(id.0),
+                    name: name.map(Name::as_str),
+                    group: group.map(|group| group.0.as_str()),
+                    selected: *selected,
+                    last: last == Some(*entity),
+                };
+                target_matches(target, &part)
+            })
+            .and_then(|(.., children, _, _, _, _)| self.bounds(children))
+            .map(|(min, _)| min)
+    }
+
+    /// World bounds of the faces, None when there are none
+    fn bounds(&self, children: Option<&Children>) -> Option<(Vec3, Vec3)> {
+        children.into_iter()
+            .flat_map(|children| self.faces.iter_many(children))
+            .flat_map(|(face, global)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)).collect::<Vec<_>>())
+            .fold(None, |bounds, p| match bounds {
+                Some((min, max)) => Some((Vec3::min(min, p), Vec3::max(max, p))),
+                None => Some((p, p)),
+            })
+    }
+}
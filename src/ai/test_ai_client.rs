@@ -0,0 +1,290 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy::prelude::*;
+    use serde_json::json;
+    use tokio::runtime::Runtime;
+    use super::super::ai_client::AiClient;
+    use super::super::commands::{parse_answer, AiAnswer, AiCommandQueue};
+    use super::super::conversation::{Conversation, Message, Role};
+    use super::super::error::{AiError, RetryPolicy};
+    use super::super::fixtures::{Fixture, RecordedResponse};
+    use super::super::llm_provider::{LlmConfig, ProviderKind};
+    use super::super::mock_server::MockServer;
+    use super::super::requests::{AiPrompt, AiRequests, AiResult};
+    use crate::part::components::{Face, Part};
+    use crate::plugins::ai_command_plugin::AiCommandPlugin;
+    use crate::tools::units::LengthUnit;
+    use crate::ui::output_console::{handle_api_response, AsyncRuntime, OutputConsole};
+
+    fn gemini_fixture() -> Fixture {
+        Fixture::from_json(include_str!("test_fixtures/gemini_tool_calls.json")).unwrap()
+    }
+
+    fn client(provider: ProviderKind, server: &MockServer) -> AiClient {
+        let config = LlmConfig { base_url: server.url.clone(), ..LlmConfig::for_provider(provider) };
+        AiClient::with_config("test-key".to_string(), config)
+    }
+
+    fn ask(runtime: &Runtime, client: &AiClient, prompt: &str) -> AiResult {
+        runtime.block_on(client.call_llm_api(&[Message::user(prompt)], LengthUnit::Millimeter, " The scene is empty. ", |_| {}))
+    }
+
+    fn quick_retries(client: &mut AiClient, max_retries: u32) {
+        client.set_retry_policy(RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(1),
+        });
+    }
+
+    #[test]
+    fn test_replayed_tool_calls() {
+        let runtime = Runtime::new().unwrap();
+        let server = MockServer::replay(&runtime, &gemini_fixture());
+        let reply = ask(&runtime, &client(ProviderKind::Gemini, &server), "make a table").unwrap();
+        let names: Vec<&str> = reply.tool_calls.iter().map(|call| call.name.as_str()).collect();
+        assert_eq!(names, ["create_primitive", "create_primitive"]);
+        assert_eq!(reply.tool_calls[1].arguments["relative_to"], "top");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/v1beta/models/gemini-1.5-flash:generateContent");
+        assert_eq!(requests[0].body["contents"][0]["parts"][0]["text"], "make a table");
+        assert!(requests[0].body["tools"][0]["functionDeclarations"].is_array());
+    }
+
+    #[test]
+    fn test_replayed_text_protocol() {
+        let runtime = Runtime::new().unwrap();
+        let fixture = Fixture::from_json(include_str!("test_fixtures/openai_text_protocol.json")).unwrap();
+        let server = MockServer::replay(&runtime, &fixture);
+        let mut client = client(ProviderKind::OpenAi, &server);
+        client.set_config(LlmConfig { use_tools: false, ..client.config().clone() });
+
+        let reply = ask(&runtime, &client, &fixture.prompt).unwrap();
+        // The code fence is gone, the commands parse
+        assert!(matches!(parse_answer(&reply.text), Ok(AiAnswer::Commands(commands)) if commands.len() == 3));
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert!(requests[0].body.get("tools").is_none());
+        assert!(requests[0].body["messages"][0]["content"].as_str().unwrap().contains("JSON Schema"));
+    }
+
+    #[test]
+    fn test_failed_requests() {
+        let runtime = Runtime::new().unwrap();
+        let malformed = Fixture::from_json(include_str!("test_fixtures/ollama_malformed.json")).unwrap();
+        let server = MockServer::replay(&runtime, &malformed);
+        let error = ask(&runtime, &client(ProviderKind::Ollama, &server), "a box").unwrap_err();
+        assert!(matches!(error, AiError::MalformedResponse(_)));
+
+        let server = MockServer::start(&runtime, vec![
+            RecordedResponse::json(500, json!({"error": {"message": "internal"}})),
+            RecordedResponse::json(200, json!({"unexpected": true})),
+        ]);
+        let mut client = client(ProviderKind::Gemini, &server);
+        quick_retries(&mut client, 0);
+        let error = ask(&runtime, &client, "a box").unwrap_err();
+        assert_eq!(error, AiError::Http { status: 500, message: "internal".to_string() });
+        assert!(matches!(ask(&runtime, &client, "a box"), Err(AiError::MalformedResponse(_))));
+
+        let slow = RecordedResponse { delay_ms: 2000, ..RecordedResponse::json(200, json!({})) };
+        let server = MockServer::start(&runtime, vec![slow]);
+        let mut client = self::client(ProviderKind::Gemini, &server);
+        quick_retries(&mut client, 0);
+        client.set_config(LlmConfig { timeout: Duration::from_millis(200), ..client.config().clone() });
+        let started = Instant::now();
+        let error = ask(&runtime, &client, "a box").unwrap_err();
+        assert_eq!(error, AiError::Timeout(Duration::from_millis(200)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_transient_failures_are_retried() {
+        let runtime = Runtime::new().unwrap();
+        let rate_limited = RecordedResponse {
+            headers: [("Retry-After".to_string(), "0.05".to_string())].into(),
+            ..RecordedResponse::json(429, json!({"error": {"message": "slow down"}}))
+        };
+        let mut responses = vec![RecordedResponse::json(503, json!({"error": {"message": "overloaded"}})), rate_limited];
+        responses.extend(gemini_fixture().exchanges.into_iter().map(|exchange| exchange.response));
+        let server = MockServer::start(&runtime, responses);
+        let mut client = client(ProviderKind::Gemini, &server);
+        quick_retries(&mut client, 3);
+
+        let notices = std::sync::Mutex::new(Vec::new());
+        let reply = runtime.block_on(client.call_llm_api(&[Message::user("make a table")], LengthUnit::Millimeter, "", |notice| {
+            notices.lock().unwrap().push(notice);
+        }));
+        assert_eq!(reply.unwrap().tool_calls.len(), 2);
+        assert_eq!(server.requests().len(), 3);
+        let notices = notices.into_inner().unwrap();
+        assert_eq!(notices.len(), 2);
+        assert!(notices[0].contains("retrying in 0.0 s (1 of 3)") && notices[0].contains("overloaded"));
+        // The wait the server asked for, not the backoff
+        assert!(notices[1].contains("retrying in 0.1 s (2 of 3)") && notices[1].contains("slow down"));
+    }
+
+    #[test]
+    fn test_connection_failures_are_retried() {
+        let runtime = Runtime::new().unwrap();
+        // A port nothing listens on
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let config = LlmConfig { base_url: format!("http://127.0.0.1:{}", port), ..LlmConfig::for_provider(ProviderKind::Gemini) };
+        let mut client = AiClient::with_config("test-key".to_string(), config);
+        quick_retries(&mut client, 2);
+
+        let notices = std::sync::Mutex::new(Vec::new());
+        let error = runtime.block_on(client.call_llm_api(&[Message::user("a box")], LengthUnit::Millimeter, "", |notice| {
+            notices.lock().unwrap().push(notice);
+        })).unwrap_err();
+        assert!(matches!(error, AiError::Network(_)));
+        assert_eq!(notices.into_inner().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_permanent_failures_are_not_retried() {
+        let runtime = Runtime::new().unwrap();
+        let too_long = RecordedResponse {
+            headers: [("Retry-After".to_string(), "60".to_string())].into(),
+            ..RecordedResponse::json(429, json!({"error": {"message": "slow down"}}))
+        };
+        let server = MockServer::start(&runtime, vec![
+            RecordedResponse::json(401, json!({"error": {"message": "API key not valid"}})),
+            too_long,
+        ]);
+        let mut client = client(ProviderKind::Gemini, &server);
+        quick_retries(&mut client, 3);
+
+        assert!(matches!(ask(&runtime, &client, "a box"), Err(AiError::Auth { status: 401, .. })));
+        assert_eq!(server.requests().len(), 1);
+        let error = ask(&runtime, &client, "a box").unwrap_err();
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(60)));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let runtime = Runtime::new().unwrap();
+        let path = std::env::temp_dir().join(format!("rustcad_fixture_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let server = MockServer::replay(&runtime, &gemini_fixture());
+        let mut recording = client(ProviderKind::Gemini, &server);
+        recording.set_recording(Some(path.clone()));
+        let recorded_reply = ask(&runtime, &recording, "make a table").unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!text.contains("test-key"));
+        let fixture = Fixture::from_json(&text).unwrap();
+        assert_eq!(fixture.provider, "gemini");
+        assert_eq!(fixture.prompt, "make a table");
+        assert_eq!(fixture.exchanges.len(), 1);
+        assert_eq!(fixture.exchanges[0].request.path, "/v1beta/models/gemini-1.5-flash:generateContent");
+        assert_eq!(fixture.exchanges[0].response, gemini_fixture().exchanges[0].response);
+
+        let server = MockServer::replay(&runtime, &fixture);
+        assert_eq!(ask(&runtime, &client(ProviderKind::Gemini, &server), "make a table").unwrap(), recorded_reply);
+    }
+
+    /// The console systems with a client talking to `server`
+    fn console_app(runtime: Runtime, server: &MockServer, prompt: &str) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin, AiCommandPlugin))
+            .insert_resource(client(ProviderKind::Gemini, server))
+            .insert_resource(AsyncRuntime(runtime))
+            .insert_resource(OutputConsole::new(100))
+            .init_resource::<AiRequests>()
+            .init_resource::<Conversation>()
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .add_systems(Update, handle_api_response);
+        app.world_mut().resource_mut::<AiCommandQueue>().review = false;
+        app.world_mut().resource_mut::<AiRequests>().push(AiPrompt {
+            input: prompt.to_string(),
+            unit: LengthUnit::Millimeter,
+            follow_up: false,
+        });
+        app
+    }
+
+    /// Updates until no request is running or waiting
+    fn run_until_idle(app: &mut App) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            app.update();
+            let requests = app.world().resource::<AiRequests>();
+            if requests.running().is_none() && requests.queued() == 0 && app.world().resource::<AiCommandQueue>().is_empty() {
+                return;
+            }
+            assert!(Instant::now() < deadline, "the AI requests did not finish");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn part_names(app: &mut App) -> Vec<String> {
+        let mut names: Vec<String> = app.world_mut()
+            .query_filtered::<&Name, With<Part>>()
+            .iter(app.world())
+            .map(|name| name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// World minimum and maximum corner of the faces of the part named `name`
+    fn world_bounds(app: &mut App, name: &str) -> (Vec3, Vec3) {
+        let part = app.world_mut()
+            .query_filtered::<(Entity, &Name), With<Part>>()
+            .iter(app.world())
+            .find(|(_, part_name)| part_name.as_str() == name)
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.world_mut()
+            .query::<(&Face, &GlobalTransform, &Parent)>()
+            .iter(app.world())
+            .filter(|(.., parent)| parent.get() == part)
+            .flat_map(|(face, global, _)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)).collect::<Vec<_>>())
+            .fold((Vec3::MAX, Vec3::MIN), |(min, max), point| (min.min(point), max.max(point)))
+    }
+
+    #[test]
+    fn test_prompt_to_parts_end_to_end() {
+        let runtime = Runtime::new().unwrap();
+        let server = MockServer::replay(&runtime, &gemini_fixture());
+        let mut app = console_app(runtime, &server, "make a table top with one leg under it");
+        run_until_idle(&mut app);
+
+        assert_eq!(part_names(&mut app), ["leg", "top"]);
+        // The parts are where the model placed them, the leg right under the top
+        app.update();
+        assert_eq!(world_bounds(&mut app, "top"), (Vec3::new(0.0, 10.0, 0.0), Vec3::new(40.0, 12.0, 20.0)));
+        assert_eq!(world_bounds(&mut app, "leg"), (Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 10.0, 2.0)));
+        // The results went back to the model, which then finished
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        let results = &requests[1].body["contents"][2]["parts"];
+        assert_eq!(results[0]["functionResponse"]["response"]["result"], "created top");
+        assert_eq!(results[1]["functionResponse"]["response"]["result"], "created leg");
+
+        let conversation = app.world().resource::<Conversation>();
+        let last = conversation.messages().last().unwrap();
+        assert_eq!(last.role, Role::Assistant);
+        assert_eq!(last.text, "Built a table top with a leg.");
+    }
+
+    #[test]
+    fn test_failed_request_end_to_end() {
+        let runtime = Runtime::new().unwrap();
+        let server = MockServer::start(&runtime, vec![RecordedResponse { raw: Some("<html>".to_string()), ..Default::default() }]);
+        let mut app = console_app(runtime, &server, "a box");
+        run_until_idle(&mut app);
+
+        assert!(part_names(&mut app).is_empty());
+        // The unanswered prompt is not kept
+        assert!(app.world().resource::<Conversation>().is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use super::super::commands::{
+        command_prompt, command_schema, parse_answer, parse_color, target_matches, AiAnswer, AiCommand, BooleanOp,
+        FaceDirection, FieldKind, PartRef, PrimitiveShape, ProtocolError, COMMANDS,
+    };
+
+    fn parse_commands(text: &str) -> Result<Vec<AiCommand>, ProtocolError> {
+        parse_answer(text).map(|answer| match answer {
+            AiAnswer::Commands(commands) => commands,
+            AiAnswer::Question(question) => panic!("unexpected question {}", question),
+        })
+    }
+
+    #[test]
+    fn test_parse_multiple_boxes() {
+        let answer = r#"{
+            "commands": [
+                {"command": "create_primitive", "shape": "box", "position": [2.0, 2.0, 2.0], "size": [1.0, 1.0, 1.0]},
+                {"command": "create_primitive", "shape": "box", "position": [3.0, 2.0, 2.0], "size": [1.0, 1.0, 1.0], "name": "middle"},
+                {"command": "create_primitive", "shape": "box", "position": [4.0, 2.0, 2.0], "size": [1.0, 2.0, 1.0]}
+            ]
+        }"#;
+        let commands = parse_commands(answer).unwrap();
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[1], AiCommand::CreatePrimitive {
+            shape: PrimitiveShape::Box,
+            position: [3.0, 2.0, 2.0],
+            size: [1.0, 1.0, 1.0],
+            name: Some("middle".to_string()),
+            relative_to: None,
+        });
+        assert!(matches!(commands[2], AiCommand::CreatePrimitive { size: [1.0, 2.0, 1.0], name: None, .. }));
+    }
+
+    #[test]
+    fn test_parse_every_operation() {
+        let answer = json!({"commands": [
+            {"command": "transform", "target": "selected", "translate": [1, 0, 0], "rotate": [0, 90, 0]},
+            {"command": "extrude_face", "target": "last", "face": "top", "distance": -0.5},
+            {"command": "boolean", "operation": "difference", "target": "base", "tool": "hole"},
+            {"command": "delete", "target": "hole"},
+            {"command": "set_color", "target": "all", "color": "#3080FF"},
+            {"command": "group", "name": "assembly", "targets": ["base", "lid"]}
+        ]});
+        let commands = parse_commands(&answer.to_string()).unwrap();
+        assert_eq!(commands[0], AiCommand::Transform {
+            target: "selected".to_string(),
+            translate: Some([1.0, 0.0, 0.0]),
+            rotate: Some([0.0, 90.0, 0.0]),
+        });
+        assert!(matches!(commands[1], AiCommand::ExtrudeFace { face: FaceDirection::Top, .. }));
+        assert!(matches!(commands[2], AiCommand::Boolean { operation: BooleanOp::Difference, .. }));
+        assert!(matches!(&commands[5], AiCommand::Group { targets, .. } if targets.len() == 2));
+    }
+
+    #[test]
+    fn test_bad_answers_are_reported() {
+        let error = |answer: Value| parse_commands(&answer.to_string()).unwrap_err();
+        assert!(matches!(parse_commands("not json"), Err(ProtocolError::InvalidJson(_))));
+        assert_eq!(error(json!({"command": "create cube"})), ProtocolError::MissingCommands);
+        assert_eq!(
+            error(json!({"commands": [{"command": "delete", "target": "a"}, {"command": "explode"}]})),
+            ProtocolError::UnknownCommand { index: 1, name: "explode".to_string() }
+        );
+        assert_eq!(
+            error(json!({"commands": [{"command": "extrude_face", "target": "a", "face": "top"}]})),
+            ProtocolError::MissingField { index: 0, command: "extrude_face", field: "distance" }
+        );
+        assert_eq!(
+            error(json!({"commands": [{"command": "delete", "target": "a", "force": true}]})),
+            ProtocolError::UnknownField { index: 0, command: "delete", field: "force".to_string() }
+        );
+        assert!(matches!(
+            error(json!({"commands": [{"command": "create_primitive", "shape": "box", "position": [0, 0, 0], "size": [1, 0, 1]}]})),
+            ProtocolError::InvalidField { field: "size", .. }
+        ));
+        assert!(matches!(
+            error(json!({"commands": [{"command": "set_color", "target": "a", "color": "blue"}]})),
+            ProtocolError::InvalidField { field: "color", .. }
+        ));
+        assert!(matches!(
+            error(json!({"commands": [{"command": "transform", "target": "a"}]})),
+            ProtocolError::InvalidField { field: "translate", .. }
+        ));
+        let message = error(json!({"commands": [{"command": "extrude_face", "target": "a", "face": "up", "distance": 1}]})).to_string();
+        assert_eq!(message, "command 1 (extrude_face): 'face' should be one of top, bottom, left, right, front, back");
+    }
+
+    #[test]
+    fn test_schema_covers_every_command() {
+        let schema = command_schema();
+        let variants = schema["properties"]["commands"]["items"]["oneOf"].as_array().unwrap();
+        assert_eq!(variants.len(), COMMANDS.len());
+        for (variant, spec) in variants.iter().zip(COMMANDS) {
+            assert_eq!(variant["properties"]["command"]["const"], spec.name);
+            assert_eq!(variant["additionalProperties"], false);
+            let required = variant["required"].as_array().unwrap();
+            for field in spec.fields {
+                assert!(variant["properties"][field.name].is_object());
+                assert_eq!(required.contains(&json!(field.name)), field.required);
+            }
+        }
+        let prompt = command_prompt();
+        assert!(prompt.contains(&schema.to_string()));
+        assert!(COMMANDS.iter().all(|spec| prompt.contains(spec.name)));
+        assert!(prompt.contains("\"p3\""));
+    }
+
+    // One command of every variant with every field given. The match has no catch-all, so a new
+    // variant does not compile until it is added here and so checked against its spec.
+    fn every_command() -> Vec<AiCommand> {
+        let commands = vec![
+            AiCommand::CreatePrimitive {
+                shape: PrimitiveShape::Box,
+                position: [0.0, 1.0, 2.0],
+                size: [1.0, 2.0, 3.0],
+                name: Some("base".to_string()),
+                relative_to: Some("p1".to_string()),
+            },
+            AiCommand::Transform { target: "base".to_string(), translate: Some([1.0, 0.0, 0.0]), rotate: Some([0.0, 90.0, 0.0]) },
+            AiCommand::ExtrudeFace { target: "last".to_string(), face: FaceDirection::Front, distance: -0.5 },
+            AiCommand::Boolean { operation: BooleanOp::Intersection, target: "base".to_string(), tool: "p2".to_string() },
+            AiCommand::Delete { target: "p2".to_string() },
+            AiCommand::SetColor { target: "all".to_string(), color: "#3080ff".to_string() },
+            AiCommand::Group { name: "assembly".to_string(), targets: vec!["base".to_string(), "lid".to_string()] },
+        ];
+        for command in &commands {
+            match command {
+                AiCommand::CreatePrimitive { .. }
+                | AiCommand::Transform { .. }
+                | AiCommand::ExtrudeFace { .. }
+                | AiCommand::Boolean { .. }
+                | AiCommand::Delete { .. }
+                | AiCommand::SetColor { .. }
+                | AiCommand::Group { .. } => {}
+            }
+        }
+        commands
+    }
+
+    #[test]
+    fn test_every_command_round_trips_through_the_schema() {
+        let mut covered = Vec::new();
+        for command in every_command() {
+            let value = serde_json::to_value(&command).unwrap();
+            let name = value["command"].as_str().unwrap().to_string();
+            let spec = command.spec();
+            assert_eq!(spec.name, name);
+            // The serde fields and the spec fields are the same
+            let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).filter(|key| *key != "command").collect();
+            let mut fields: Vec<&str> = spec.fields.iter().map(|field| field.name).collect();
+            keys.sort();
+            fields.sort();
+            assert_eq!(keys, fields, "fields of {}", name);
+            let answer = json!({ "commands": [value] }).to_string();
+            assert_eq!(parse_commands(&answer).unwrap(), vec![command]);
+            // Every choice the schema offers deserializes
+            for field in spec.fields {
+                let FieldKind::Choice(choices) = field.kind else {
+                    continue;
+                };
+                for choice in choices {
+                    let mut changed = value.clone();
+                    changed[field.name] = json!(choice);
+                    let answer = json!({ "commands": [changed] }).to_string();
+                    assert!(parse_commands(&answer).is_ok(), "{} {} {}", name, field.name, choice);
+                }
+            }
+            covered.push(name);
+        }
+        assert!(COMMANDS.iter().all(|spec| covered.iter().any(|name| name == spec.name)));
+    }
+
+    #[test]
+    fn test_targets_and_colors() {
+        let base = PartRef { id: Some(3), name: Some("base"), ..Default::default() };
+        let wheel = PartRef { id: Some(4), name: Some("front left"), group: Some("wheels"), selected: true, last: true };
+        assert!(target_matches("all", &PartRef::default()));
+        assert!(target_matches("Selected", &wheel));
+        assert!(!target_matches("selected", &base));
+        assert!(target_matches("last", &wheel));
+        assert!(target_matches("BASE", &base));
+        assert!(target_matches("p3", &base));
+        assert!(target_matches("P3", &base));
+        assert!(!target_matches("p4", &base));
+        assert!(!target_matches("p3", &PartRef { name: Some("base"), ..Default::default() }));
+        assert!(target_matches("wheels", &wheel));
+        assert!(!target_matches("lid", &wheel));
+
+        assert!(parse_color("#ff8800").is_some());
+        assert!(parse_color("ff8800").is_none());
+        assert!(parse_color("#ff88").is_none());
+    }
+
+    #[test]
+    fn test_relative_placement_and_face_names() {
+        let answer = json!({"commands": [
+            {"command": "create_primitive", "shape": "box", "position": [0, 0, 1], "size": [1, 1, 1], "relative_to": "p2"}
+        ]});
+        let commands = parse_commands(&answer.to_string()).unwrap();
+        assert!(matches!(&commands[0], AiCommand::CreatePrimitive { relative_to: Some(part), .. } if part == "p2"));
+        assert!(matches!(
+            parse_commands(&json!({"commands": [
+                {"command": "create_primitive", "shape": "box", "position": [0, 0, 0], "size": [1, 1, 1], "relative_to": 2}
+            ]}).to_string()),
+            Err(ProtocolError::InvalidField { field: "relative_to", .. })
+        ));
+
+        assert_eq!(FaceDirection::nearest(bevy::math::Vec3::new(0.1, 0.9, 0.0)), FaceDirection::Top);
+        assert_eq!(FaceDirection::nearest(bevy::math::Vec3::NEG_Z).name(), FaceDirection::Back.name());
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::super::commands::{parse_answer, AiAnswer, ProtocolError};
+    use super::super::conversation::{Conversation, Message, Role, ToolCall, MAX_HISTORY_MESSAGES};
+
+    #[test]
+    fn test_actions_follow_the_answer() {
+        let mut conversation = Conversation::default();
+        // Nothing to attach to before the model answered
+        conversation.record_action("created a box");
+        conversation.push_user("make a box");
+        conversation.record_action("created a box");
+        assert!(conversation.messages()[0].actions.is_empty());
+
+        conversation.push_reply("{\"commands\": []}");
+        conversation.record_action("created base");
+        conversation.record_action("coloured 1 parts");
+        assert_eq!(conversation.messages()[1].content(), "{\"commands\": []}\n[Result: created base; coloured 1 parts]");
+    }
+
+    #[test]
+    fn test_unanswered_prompts_are_discarded() {
+        let mut conversation = Conversation::default();
+        conversation.push_user("make a box");
+        conversation.push_reply("{\"question\": \"How big?\"}");
+        assert!(!conversation.discard_unanswered());
+        conversation.push_user("2 cm");
+        assert!(conversation.discard_unanswered());
+        assert_eq!(conversation.messages().len(), 2);
+        assert_eq!(conversation.messages()[1].role, Role::Assistant);
+    }
+
+    #[test]
+    fn test_history_starts_with_a_prompt() {
+        let mut conversation = Conversation::default();
+        for i in 0..MAX_HISTORY_MESSAGES {
+            conversation.push_user(format!("prompt {}", i));
+            conversation.push_reply("{\"commands\": []}");
+        }
+        conversation.push_user("last");
+        let history = conversation.history();
+        assert!(history.len() <= MAX_HISTORY_MESSAGES);
+        assert_eq!(history[0].role, Role::User);
+        assert_eq!(history.last().unwrap().text, "last");
+    }
+
+    #[test]
+    fn test_history_keeps_a_long_build() {
+        let mut conversation = Conversation::default();
+        conversation.push_user("an older prompt");
+        conversation.push_reply("{\"commands\": []}");
+        conversation.push_user("make a table with four legs");
+        // Three rounds of six tool calls, more messages than the history holds
+        for round in 0..3 {
+            let calls: Vec<ToolCall> = (0..6)
+                .map(|i| ToolCall { id: format!("call_{}_{}", round, i), name: "create_primitive".to_string(), arguments: json!({}) })
+                .collect();
+            conversation.push(Message::tool_calls("", calls.clone()));
+            for call in calls {
+                conversation.push(Message::tool_result(call, "created leg"));
+            }
+        }
+        assert!(conversation.messages().len() > MAX_HISTORY_MESSAGES + 2);
+
+        let history = conversation.history();
+        assert_eq!(history[0].text, "make a table with four legs");
+        assert_eq!(history.len(), 1 + 3 * 7);
+        assert_eq!(history.last().unwrap().role, Role::Tool);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut conversation = Conversation::default();
+        conversation.push_user("make a box");
+        conversation.push_reply("{\"commands\": []}");
+        conversation.record_action("created a box");
+        let json = conversation.to_json();
+        assert_eq!(Conversation::from_json(&json).unwrap(), conversation);
+        assert_eq!(Conversation::from_json("{}").unwrap(), Conversation::default());
+    }
+
+    #[test]
+    fn test_clarifying_questions() {
+        assert_eq!(
+            parse_answer(&json!({"question": " How tall should the table be? "}).to_string()),
+            Ok(AiAnswer::Question("How tall should the table be?".to_string()))
+        );
+        assert_eq!(parse_answer(&json!({"question": ""}).to_string()), Err(ProtocolError::InvalidQuestion));
+        assert_eq!(parse_answer(&json!({"commands": []}).to_string()), Ok(AiAnswer::Commands(Vec::new())));
+        assert_eq!(parse_answer(&json!({"answer": "ok"}).to_string()), Err(ProtocolError::MissingCommands));
+    }
+
+    #[test]
+    fn test_tool_rounds_count_since_the_prompt() {
+        let call = ToolCall { id: "call_1".to_string(), name: "delete".to_string(), arguments: json!({"target": "p1"}) };
+        let mut conversation = Conversation::default();
+        conversation.push_user("remove p1");
+        conversation.push(Message::tool_calls("", vec![call.clone()]));
+        conversation.push(Message::tool_result(call.clone(), "deleted 1 parts"));
+        conversation.push(Message::tool_calls("", vec![call.clone()]));
+        assert_eq!(conversation.tool_rounds(), 2);
+        // Saved along with the calls and results
+        assert_eq!(Conversation::from_json(&conversation.to_json()).unwrap(), conversation);
+
+        conversation.push_user("now make a box");
+        assert_eq!(conversation.tool_rounds(), 0);
+    }
+
+    #[test]
+    fn test_part_ids_of_an_earlier_session_are_taken_out() {
+        let call = ToolCall { id: "call_1".to_string(), name: "group".to_string(), arguments: json!({"name": "p2s", "targets": ["p2", "P10"]}) };
+        let mut conversation = Conversation::default();
+        conversation.push_user("move p2 next to p10, keep top2 as it is");
+        conversation.push(Message::tool_calls("", vec![call.clone()]));
+        conversation.push(Message::tool_result(call, "grouped 2 parts as p2s"));
+
+        let path = std::env::temp_dir().join(format!("rustcad_conversation_{}.json", std::process::id()));
+        conversation.save(&path).unwrap();
+        // The same session still has the parts
+        let (same, forgot) = Conversation::load(&path).unwrap();
+        assert!(!forgot);
+        assert_eq!(same, conversation);
+
+        let mut saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        saved["session"] = json!(1);
+        std::fs::write(&path, saved.to_string()).unwrap();
+        let (later, forgot) = Conversation::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(forgot);
+        let messages = later.messages();
+        assert_eq!(messages[0].text, "move a part of an earlier session next to a part of an earlier session, keep top2 as it is");
+        assert_eq!(messages[1].tool_calls[0].arguments["targets"], json!(["a part of an earlier session", "a part of an earlier session"]));
+        // Names that only start like an ID are left alone
+        assert_eq!(messages[1].tool_calls[0].arguments["name"], "p2s");
+        assert_eq!(messages[2].text, "grouped 2 parts as p2s");
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use super::super::credentials::{Credentials, KeySource};
+    use super::super::llm_provider::{LlmConfig, ProviderKind};
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_key_sources_in_order() {
+        let env = |variable: &str| (variable == "GOOGLE_API_KEY").then(|| "from-env".to_string());
+        let mut credentials = Credentials::from_json(r#"{"keys": {"gemini": "from-file"}}"#).unwrap();
+        assert_eq!(credentials.resolve(ProviderKind::Gemini, no_env), Some(("from-file".to_string(), KeySource::ConfigFile)));
+        assert_eq!(
+            credentials.resolve(ProviderKind::Gemini, env),
+            Some(("from-env".to_string(), KeySource::Environment("GOOGLE_API_KEY")))
+        );
+        credentials.set_key(ProviderKind::Gemini, " typed ", false);
+        assert_eq!(credentials.resolve(ProviderKind::Gemini, env), Some(("typed".to_string(), KeySource::Settings)));
+        assert_eq!(credentials.resolve(ProviderKind::OpenAi, env), None);
+    }
+
+    #[test]
+    fn test_only_remembered_keys_are_saved() {
+        let mut credentials = Credentials::default();
+        credentials.set_key(ProviderKind::Gemini, "session-only", false);
+        credentials.set_key(ProviderKind::OpenAi, "kept", true);
+        let json = credentials.to_json();
+        assert!(!json.contains("session-only"));
+        let loaded = Credentials::from_json(&json).unwrap();
+        assert_eq!(loaded.resolve(ProviderKind::OpenAi, no_env), Some(("kept".to_string(), KeySource::ConfigFile)));
+        assert_eq!(loaded.resolve(ProviderKind::Gemini, no_env), None);
+
+        // An empty key forgets the provider everywhere
+        credentials.set_key(ProviderKind::OpenAi, "", false);
+        assert_eq!(credentials.resolve(ProviderKind::OpenAi, no_env), None);
+        assert!(!credentials.to_json().contains("kept"));
+    }
+
+    #[test]
+    fn test_debug_hides_keys() {
+        let mut credentials = Credentials::default();
+        credentials.set_key(ProviderKind::Gemini, "secret", true);
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("gemini"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn test_local_servers_need_no_key() {
+        assert!(LlmConfig::for_provider(ProviderKind::Gemini).requires_key());
+        assert!(LlmConfig::for_provider(ProviderKind::OpenAi).requires_key());
+        assert!(!LlmConfig::for_provider(ProviderKind::Ollama).requires_key());
+        let local = LlmConfig {
+            base_url: "http://localhost:8080/v1".to_string(),
+            ..LlmConfig::for_provider(ProviderKind::OpenAi)
+        };
+        assert!(!local.requires_key());
+    }
+}
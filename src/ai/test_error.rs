@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::commands::ProtocolError;
+    use super::super::error::{parse_retry_after, AiError, RetryPolicy};
+
+    #[test]
+    fn test_error_responses_are_classified() {
+        let body = r#"{"error": {"message": "Incorrect API key provided"}}"#;
+        assert_eq!(
+            AiError::from_status(401, body, None),
+            AiError::Auth { status: 401, message: "Incorrect API key provided".to_string() },
+        );
+        let quota = r#"{"error": {"message": "You exceeded your current quota", "type": "insufficient_quota"}}"#;
+        assert_eq!(AiError::from_status(429, quota, None), AiError::Quota("You exceeded your current quota".to_string()));
+        assert_eq!(
+            AiError::from_status(429, "slow down", Some(Duration::from_secs(3))),
+            AiError::RateLimited { retry_after: Some(Duration::from_secs(3)), message: "slow down".to_string() },
+        );
+        // Gemini puts the wait in the body
+        let gemini = r#"{"error": {"message": "Resource exhausted", "details": [{"@type": "RetryInfo", "retryDelay": "7s"}]}}"#;
+        assert_eq!(AiError::from_status(429, gemini, None).retry_after(), Some(Duration::from_secs(7)));
+        // Ollama sends the message as a plain string
+        assert_eq!(
+            AiError::from_status(404, r#"{"error": "model 'llama9' not found"}"#, None),
+            AiError::Http { status: 404, message: "model 'llama9' not found".to_string() },
+        );
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        assert!(AiError::Network("connection refused".to_string()).is_retryable());
+        assert!(AiError::Timeout(Duration::from_secs(30)).is_retryable());
+        assert!(AiError::from_status(503, "", None).is_retryable());
+        assert!(AiError::from_status(429, "", None).is_retryable());
+        assert!(!AiError::from_status(400, "", None).is_retryable());
+        assert!(!AiError::from_status(403, "", None).is_retryable());
+        assert!(!AiError::Quota(String::new()).is_retryable());
+        assert!(!AiError::MalformedResponse(String::new()).is_retryable());
+        assert!(!AiError::from(ProtocolError::InvalidJson(String::new())).is_retryable());
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_honours_retry_after() {
+        let policy = RetryPolicy::default();
+        let server_error = AiError::from_status(500, "", None);
+        assert_eq!(policy.delay(0, &server_error), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(1, &server_error), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay(2, &server_error), Some(Duration::from_secs(4)));
+        assert_eq!(policy.delay(3, &server_error), None);
+
+        let rate_limited = AiError::from_status(429, "", Some(Duration::from_secs(10)));
+        assert_eq!(policy.delay(0, &rate_limited), Some(Duration::from_secs(10)));
+        // Waiting longer than the policy allows is left to the user
+        let long_wait = AiError::from_status(429, "", Some(Duration::from_secs(120)));
+        assert_eq!(policy.delay(0, &long_wait), None);
+        assert_eq!(policy.delay(0, &AiError::from_status(401, "", None)), None);
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        assert_eq!(parse_retry_after("5"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after(" 0.5 "), Some(Duration::from_millis(500)));
+        assert_eq!(parse_retry_after("-1"), None);
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_messages_say_what_to_do() {
+        let auth = AiError::from_status(401, "bad key", None).to_string();
+        assert!(auth.contains("401") && auth.contains("AI settings"));
+        let rate_limited = AiError::from_status(429, "slow down", Some(Duration::from_millis(1500))).to_string();
+        assert_eq!(rate_limited, "rate limited, try again in 2 s: slow down");
+    }
+}
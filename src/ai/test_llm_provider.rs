@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::super::commands::{parse_tool_call, tool_declarations, AiCommand};
+    use super::super::conversation::{Message, ToolCall};
+    use super::super::llm_provider::{LlmConfig, ModelReply, ProviderKind};
+
+    #[test]
+    fn test_gemini_request_and_response() {
+        let config = LlmConfig::default();
+        let provider = ProviderKind::Gemini.provider();
+        let request = provider.request(&config, "key", "be brief", &[Message::user("make a cube")], &[]);
+        assert_eq!(request.url, "https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent");
+        // The key goes in a header, never in the URL
+        assert!(!request.url.contains("key"));
+        assert_eq!(request.headers, vec![("x-goog-api-key", "key".to_string())]);
+        assert_eq!(request.body["systemInstruction"]["parts"][0]["text"], "be brief");
+        assert_eq!(request.body["contents"][0]["parts"][0]["text"], "make a cube");
+
+        let response = json!({"candidates": [{"content": {"parts": [{"text": "{}"}]}}]});
+        assert_eq!(provider.reply(&response).unwrap().text, "{}");
+        assert_eq!(provider.reply(&json!({"error": "quota"})), None);
+    }
+
+    #[test]
+    fn test_openai_compatible_local_server() {
+        let config = LlmConfig {
+            base_url: "http://localhost:8080/v1/".to_string(),
+            model: "qwen".to_string(),
+            temperature: 0.5,
+            ..LlmConfig::for_provider(ProviderKind::OpenAi)
+        };
+        let provider = ProviderKind::OpenAi.provider();
+        let request = provider.request(&config, "", "be brief", &[Message::user("hello")], &[]);
+        assert_eq!(request.url, "http://localhost:8080/v1/chat/completions");
+        // Local servers run without a key
+        assert!(request.headers.is_empty());
+        assert_eq!(request.body["model"], "qwen");
+        assert_eq!(request.body["temperature"], 0.5);
+        assert_eq!(request.body["messages"][0]["role"], "system");
+        assert_eq!(request.body["messages"][1]["content"], "hello");
+
+        let with_key = provider.request(&config, "secret", "be brief", &[Message::user("hello")], &[]);
+        assert_eq!(with_key.headers, vec![("Authorization", "Bearer secret".to_string())]);
+
+        let response = json!({"choices": [{"message": {"role": "assistant", "content": "ok"}}]});
+        assert_eq!(provider.reply(&response).unwrap().text, "ok");
+    }
+
+    #[test]
+    fn test_ollama_request_and_response() {
+        let config = LlmConfig::for_provider(ProviderKind::Ollama);
+        let provider = ProviderKind::Ollama.provider();
+        let request = provider.request(&config, "", "be brief", &[Message::user("hello")], &[]);
+        assert_eq!(request.url, "http://localhost:11434/api/chat");
+        assert_eq!(request.body["stream"], false);
+        assert_eq!(request.body["model"], "llama3.1");
+
+        let response = json!({"message": {"role": "assistant", "content": "ok"}, "done": true});
+        assert_eq!(provider.reply(&response).unwrap().text, "ok");
+    }
+
+    #[test]
+    fn test_history_roles() {
+        let mut answer = Message::assistant("{\"question\": \"How big?\"}");
+        answer.actions.push("asked a question".to_string());
+        let messages = [Message::user("make a table"), answer, Message::user("1 m wide")];
+        let config = LlmConfig::default();
+
+        let gemini = ProviderKind::Gemini.provider().request(&config, "key", "rules", &messages, &[]);
+        let roles: Vec<_> = gemini.body["contents"].as_array().unwrap().iter().map(|content| content["role"].clone()).collect();
+        assert_eq!(roles, vec![json!("user"), json!("model"), json!("user")]);
+        assert!(gemini.body["contents"][1]["parts"][0]["text"].as_str().unwrap().ends_with("[Result: asked a question]"));
+
+        let ollama = ProviderKind::Ollama.provider().request(&config, "", "rules", &messages, &[]);
+        let roles: Vec<_> = ollama.body["messages"].as_array().unwrap().iter().map(|message| message["role"].clone()).collect();
+        assert_eq!(roles, vec![json!("system"), json!("user"), json!("assistant"), json!("user")]);
+        assert_eq!(ollama.body["messages"][3]["content"], "1 m wide");
+    }
+
+    fn call(id: &str, name: &str, arguments: serde_json::Value) -> ToolCall {
+        ToolCall { id: id.to_string(), name: name.to_string(), arguments }
+    }
+
+    #[test]
+    fn test_gemini_function_calling() {
+        let config = LlmConfig::default();
+        let provider = ProviderKind::Gemini.provider();
+        let box_call = call("call_1", "delete", json!({"target": "p1"}));
+        let messages = [
+            Message::user("remove p1 and p2"),
+            Message::tool_calls("", vec![box_call.clone(), call("call_2", "delete", json!({"target": "p2"}))]),
+            Message::tool_result(box_call, "deleted 1 parts"),
+            Message::tool_result(call("call_2", "delete", json!({"target": "p2"})), "deleted 1 parts"),
+        ];
+        let request = provider.request(&config, "key", "rules", &messages, &tool_declarations());
+        let declarations = request.body["tools"][0]["functionDeclarations"].as_array().unwrap();
+        assert!(declarations.iter().any(|declaration| declaration["name"] == "create_primitive"));
+        // Keywords Gemini does not know are left out
+        assert!(!request.body.to_string().contains("additionalProperties"));
+        assert!(!request.body.to_string().contains("exclusiveMinimum"));
+
+        let contents = request.body["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[1]["parts"][1]["functionCall"]["args"]["target"], "p2");
+        // Both results in one turn
+        assert_eq!(contents[2]["parts"].as_array().unwrap().len(), 2);
+        assert_eq!(contents[2]["parts"][0]["functionResponse"]["name"], "delete");
+
+        let response = json!({"candidates": [{"content": {"parts": [
+            {"functionCall": {"name": "delete", "args": {"target": "last"}}},
+            {"functionCall": {"name": "group", "args": {"name": "a", "targets": ["p1"]}}}
+        ]}}]});
+        let reply = provider.reply(&response).unwrap();
+        assert_eq!(reply.text, "");
+        assert_eq!(reply.tool_calls, vec![
+            call("call_1", "delete", json!({"target": "last"})),
+            call("call_2", "group", json!({"name": "a", "targets": ["p1"]})),
+        ]);
+    }
+
+    #[test]
+    fn test_openai_and_ollama_function_calling() {
+        let config = LlmConfig::for_provider(ProviderKind::OpenAi);
+        let delete = call("abc", "delete", json!({"target": "p1"}));
+        let messages = [Message::user("remove p1"), Message::tool_calls("", vec![delete.clone()]), Message::tool_result(delete, "deleted 1 parts")];
+
+        let openai = ProviderKind::OpenAi.provider();
+        let request = openai.request(&config, "", "rules", &messages, &tool_declarations());
+        assert_eq!(request.body["tools"][0]["type"], "function");
+        assert_eq!(request.body["tools"][0]["function"]["parameters"]["type"], "object");
+        assert_eq!(request.body["messages"][2]["tool_calls"][0]["function"]["arguments"], "{\"target\":\"p1\"}");
+        assert_eq!(request.body["messages"][3], json!({"role": "tool", "tool_call_id": "abc", "content": "deleted 1 parts"}));
+        // No tools, no tools field
+        assert!(openai.request(&config, "", "rules", &messages, &[]).body.get("tools").is_none());
+
+        let response = json!({"choices": [{"message": {"role": "assistant", "content": null, "tool_calls": [
+            {"id": "xyz", "type": "function", "function": {"name": "delete", "arguments": "{\"target\": \"all\"}"}}
+        ]}}]});
+        assert_eq!(openai.reply(&response), Some(ModelReply { text: String::new(), tool_calls: vec![call("xyz", "delete", json!({"target": "all"}))] }));
+
+        let ollama = ProviderKind::Ollama.provider();
+        let request = ollama.request(&config, "", "rules", &messages, &tool_declarations());
+        assert_eq!(request.body["messages"][2]["tool_calls"][0]["function"]["arguments"], json!({"target": "p1"}));
+        assert_eq!(request.body["messages"][3]["tool_name"], "delete");
+        let response = json!({"message": {"role": "assistant", "content": "", "tool_calls": [
+            {"function": {"name": "delete", "arguments": {"target": "all"}}}
+        ]}});
+        assert_eq!(ollama.reply(&response).unwrap().tool_calls, vec![call("call_1", "delete", json!({"target": "all"}))]);
+    }
+
+    #[test]
+    fn test_tool_calls_follow_the_protocol() {
+        assert_eq!(
+            parse_tool_call(0, &call("1", "delete", json!({"target": "p1"}))),
+            Ok(AiCommand::Delete { target: "p1".to_string() })
+        );
+        assert!(parse_tool_call(0, &call("1", "explode", json!({}))).is_err());
+        assert!(parse_tool_call(0, &call("1", "delete", json!({"target": "p1", "command": "group"}))).is_ok_and(|command| matches!(command, AiCommand::Delete { .. })));
+        assert!(parse_tool_call(0, &call("1", "delete", json!("p1"))).is_err());
+        for declaration in tool_declarations() {
+            assert!(declaration.parameters["properties"].get("command").is_none());
+        }
+    }
+
+    #[test]
+    fn test_provider_names() {
+        for kind in ProviderKind::ALL {
+            assert_eq!(ProviderKind::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(ProviderKind::from_name("OpenAI"), Some(ProviderKind::OpenAi));
+        assert_eq!(ProviderKind::from_name("claude"), None);
+    }
+}
@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use serde_json::json;
+    use super::super::commands::{parse_answer, AiAnswer, AiCommand, AiCommandQueue};
+    use super::super::conversation::Conversation;
+    use super::super::preview::{ghost_boxes, preview_summary};
+    use crate::part::components::{Face, Part};
+    use crate::plugins::ai_command_plugin::AiCommandPlugin;
+    use crate::tools::units::{DocumentUnits, LengthUnit};
+    use crate::ui::output_console::OutputConsole;
+
+    fn commands(answer: serde_json::Value) -> Vec<AiCommand> {
+        match parse_answer(&answer.to_string()).unwrap() {
+            AiAnswer::Commands(commands) => commands,
+            AiAnswer::Question(question) => panic!("unexpected question {}", question),
+        }
+    }
+
+    fn table() -> Vec<AiCommand> {
+        commands(json!({"commands": [
+            {"command": "create_primitive", "shape": "box", "position": [0, 1, 0], "size": [4, 1, 2], "name": "top"},
+            {"command": "create_primitive", "shape": "box", "position": [0, -1, 0], "size": [1, 1, 1], "relative_to": "top"},
+            {"command": "create_primitive", "shape": "box", "position": [1, 0, 0], "size": [1, 1, 1], "relative_to": "base"},
+            {"command": "delete", "target": "p2"}
+        ]}))
+    }
+
+    #[test]
+    fn test_answers_wait_for_review() {
+        let mut queue = AiCommandQueue::default();
+        queue.extend(table(), LengthUnit::Millimeter);
+        assert!(queue.pop().is_none());
+        assert!(!queue.is_empty());
+        assert_eq!(queue.pending().len(), 4);
+
+        assert_eq!(queue.accept(), 4);
+        assert!(queue.pending().is_empty());
+        assert!(matches!(queue.pop().map(|queued| queued.command), Some(AiCommand::CreatePrimitive { .. })));
+
+        queue.extend(table(), LengthUnit::Millimeter);
+        assert_eq!(queue.reject().len(), 4);
+        // Only the accepted answer is left to run
+        assert_eq!(std::iter::from_fn(|| queue.pop()).count(), 3);
+        assert!(queue.is_empty());
+
+        queue.review = false;
+        queue.extend(table(), LengthUnit::Millimeter);
+        assert!(queue.pending().is_empty());
+        assert!(queue.pop().is_some());
+    }
+
+    #[test]
+    fn test_ghosts_resolve_relative_placement() {
+        let mut queue = AiCommandQueue::default();
+        queue.extend(table(), LengthUnit::Centimeter);
+        let existing = |target: &str| (target == "base").then_some(Vec3::new(100.0, 0.0, 0.0));
+        let ghosts = ghost_boxes(queue.pending(), existing);
+        assert_eq!(ghosts.len(), 3);
+        // Lengths in centimetres, ghosts in millimetres
+        assert_eq!(ghosts[0].min, Vec3::new(0.0, 10.0, 0.0));
+        assert_eq!(ghosts[0].size, Vec3::new(40.0, 10.0, 20.0));
+        // Relative to the box created before it, then to an existing part
+        assert_eq!(ghosts[1].min, Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(ghosts[2].min, Vec3::new(110.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut queue = AiCommandQueue::default();
+        queue.extend(table(), LengthUnit::Millimeter);
+        let units = DocumentUnits { precision: 1, ..Default::default() };
+        assert_eq!(preview_summary(queue.pending(), &units), "3 boxes, total volume 10.0 mm³; delete p2");
+        assert_eq!(preview_summary(&[], &units), "no changes");
+    }
+
+    #[test]
+    fn test_accepted_ghosts_match_the_created_parts() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin, AiCommandPlugin))
+            .insert_resource(OutputConsole::new(100))
+            .init_resource::<Conversation>()
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>();
+        let answer = commands(json!({"commands": [
+            {"command": "create_primitive", "shape": "box", "position": [0, 1, 0], "size": [4, 1, 2], "name": "top"},
+            {"command": "create_primitive", "shape": "box", "position": [0, -1, 0], "size": [1, 1, 1], "name": "leg", "relative_to": "top"}
+        ]}));
+        let mut queue = app.world_mut().resource_mut::<AiCommandQueue>();
+        queue.extend(answer, LengthUnit::Centimeter);
+        let ghosts = ghost_boxes(queue.pending(), |_| None);
+        queue.accept();
+        // One command per frame, then one more so the last part's transforms propagate
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let mut parts = app.world_mut().query_filtered::<(Entity, &Name), With<Part>>();
+        let parts: Vec<(Entity, String)> = parts.iter(app.world()).map(|(entity, name)| (entity, name.to_string())).collect();
+        assert_eq!(parts.len(), ghosts.len());
+        for ghost in &ghosts {
+            let (part, _) = parts.iter().find(|(_, name)| Some(name) == ghost.name.as_ref()).unwrap();
+            let (min, max) = app.world_mut()
+                .query::<(&Face, &GlobalTransform, &Parent)>()
+                .iter(app.world())
+                .filter(|(.., parent)| parent.get() == *part)
+                .flat_map(|(face, global, _)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)).collect::<Vec<_>>())
+                .fold((Vec3::MAX, Vec3::MIN), |(min, max), point| (min.min(point), max.max(point)));
+            assert!((min - ghost.min).length() < 1e-4, "{:?}: {} != {}", ghost.name, min, ghost.min);
+            assert!((max - (ghost.min + ghost.size)).length() < 1e-4, "{:?}: {} != {}", ghost.name, max, ghost.min + ghost.size);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::runtime::Runtime;
+    use super::super::llm_provider::ModelReply;
+    use super::super::requests::{spinner, AiPrompt, AiRequests};
+    use crate::tools::units::LengthUnit;
+
+    fn prompt(input: &str) -> AiPrompt {
+        AiPrompt { input: input.to_string(), unit: LengthUnit::Millimeter, follow_up: false }
+    }
+
+    #[test]
+    fn test_prompts_run_one_at_a_time_in_order() {
+        let runtime = Runtime::new().unwrap();
+        let mut requests = AiRequests::default();
+        requests.push(prompt("first"));
+        requests.push(prompt("second"));
+
+        let started = requests.start_next(|prompt, _| {
+            let input = prompt.input.clone();
+            runtime.spawn(async move { Ok(ModelReply { text: input, ..Default::default() }) })
+        });
+        assert_eq!(started.map(|prompt| prompt.input.as_str()), Some("first"));
+        // Nothing else starts while a request is in flight
+        assert!(requests.start_next(|_, _| unreachable!()).is_none());
+        assert_eq!(requests.queued(), 1);
+
+        let finished = loop {
+            if let Some(finished) = requests.take_finished() {
+                break finished;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(finished.0.input, "first");
+        assert_eq!(runtime.block_on(finished.1).unwrap().unwrap().text, "first");
+        assert!(requests.running().is_none());
+
+        // Follow ups go ahead of the waiting prompts
+        requests.push_front(AiPrompt { follow_up: true, ..prompt("first") });
+        requests.start_next(|_, _| runtime.spawn(async { Ok(ModelReply::default()) }));
+        assert!(requests.running().is_some_and(|running| running.prompt.follow_up));
+        assert_eq!(requests.queued(), 1);
+    }
+
+    #[test]
+    fn test_cancel_aborts_the_running_request() {
+        let runtime = Runtime::new().unwrap();
+        let mut requests = AiRequests::default();
+        requests.push(prompt("slow"));
+        requests.push(prompt("waiting"));
+        requests.start_next(|_, _| {
+            runtime.spawn(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(ModelReply::default())
+            })
+        });
+
+        assert_eq!(requests.clear_queue(), 1);
+        assert_eq!(requests.cancel(), Some(prompt("slow")));
+        assert!(requests.running().is_none());
+        assert_eq!(requests.queued(), 0);
+        assert_eq!(requests.cancel(), None);
+    }
+
+    #[test]
+    fn test_spinner_turns() {
+        assert_eq!(spinner(Duration::ZERO), '|');
+        assert_eq!(spinner(Duration::from_millis(150)), '/');
+        assert_eq!(spinner(Duration::from_millis(450)), spinner(Duration::ZERO));
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use serde_json::{json, Value};
+    use super::super::scene::{scene_instruction, PartSummary, SceneParts};
+    use crate::part::components::{Face, Part, PartId, Vertex};
+    use crate::part::naming::TopoName;
+    use crate::tools::units::LengthUnit;
+
+    fn summary(id: &str) -> PartSummary {
+        PartSummary {
+            id: id.to_string(),
+            name: None,
+            group: None,
+            min: Some([0.0, 0.0, 0.0]),
+            max: Some([1.0, 2.0, 1.0]),
+            translation: [0.0, 0.0, 0.0],
+            rotation: [0.0, 0.0, 0.0],
+            selected: false,
+            selected_faces: Vec::new(),
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_empty_scene() {
+        assert!(scene_instruction(&[], LengthUnit::Millimeter).contains("The scene is empty."));
+    }
+
+    #[test]
+    fn test_scene_lists_parts_compactly() {
+        let lid = PartSummary {
+            name: Some("lid".to_string()),
+            selected: true,
+            selected_faces: vec!["top"],
+            color: Some("#FF8800".to_string()),
+            ..summary("p2")
+        };
+        let text = scene_instruction(&[summary("p1"), lid], LengthUnit::Millimeter);
+        assert!(text.contains("in mm"));
+        let start = text.find('[').unwrap();
+        let end = text.rfind(']').unwrap();
+        let parts: Value = serde_json::from_str(&text[start..=end]).unwrap();
+        // Unset fields are left out to keep the prompt short
+        assert_eq!(parts[0], json!({
+            "id": "p1", "min": [0.0, 0.0, 0.0], "max": [1.0, 2.0, 1.0],
+            "translation": [0.0, 0.0, 0.0], "rotation": [0.0, 0.0, 0.0]
+        }));
+        assert_eq!(parts[1]["name"], "lid");
+        assert_eq!(parts[1]["selected"], true);
+        assert_eq!(parts[1]["selected_faces"], json!(["top"]));
+        assert_eq!(parts[1]["color"], "#FF8800");
+    }
+
+    #[test]
+    fn test_parts_are_listed_by_id() {
+        let mut world = World::new();
+        let face = Face {
+            vertices: vec![Vertex::new(0.0, 0.0, 0.0), Vertex::new(2.0, 0.0, 0.0), Vertex::new(2.0, 1.0, 0.0)],
+            edges: Vec::new(),
+            normal: Vec3::Z,
+            name: TopoName::default(),
+        };
+        for id in [10, 9, 2] {
+            let part = world.spawn((Part::new(), PartId(id), Transform::default())).id();
+            // Part 10 has no faces
+            if id != 10 {
+                world.spawn((face.clone(), GlobalTransform::from_xyz(0.0, id as f32, 0.0))).set_parent(part);
+            }
+        }
+        let summaries = world.run_system_once(|scene: SceneParts| scene.summaries(LengthUnit::Millimeter)).unwrap();
+        let ids: Vec<&str> = summaries.iter().map(|summary| summary.id.as_str()).collect();
+        assert_eq!(ids, ["p2", "p9", "p10"]);
+        assert_eq!(summaries[1].min, Some([0.0, 9.0, 0.0]));
+        assert_eq!(summaries[1].max, Some([2.0, 10.0, 0.0]));
+        // Without faces there are no bounds to give
+        let text = serde_json::to_value(&summaries[2]).unwrap();
+        assert!(text.get("min").is_none() && text.get("max").is_none());
+    }
+}
@@ -5,23 +5,33 @@ mod part;
 mod plugins;
 mod ai;
 
-use std::f32::consts::PI;
-
-use crate::plugins::global_gizmo_plugin::GlobalGizmoPlugin;
-// use crate::plugins::ai_console::AiConsolePlugin;
+use crate::plugins::display_plugin::DisplayPlugin;
+use crate::plugins::grid_plugin::GridPlugin;
+use crate::plugins::measure_plugin::MeasurePlugin;
+use crate::plugins::nav_cube_plugin::NavCubePlugin;
+use crate::plugins::section_plugin::SectionPlugin;
+use crate::plugins::region_select_plugin::RegionSelectPlugin;
+use crate::plugins::part_selection_plugin::PartSelectionPlugin;
+use crate::plugins::smart_select_plugin::SmartSelectPlugin;
+use crate::plugins::ai_settings_plugin::AiSettingsPlugin;
+use crate::plugins::ai_preview_plugin::AiPreviewPlugin;
+use crate::plugins::ai_command_plugin::AiCommandPlugin;
+use crate::plugins::navigation_plugin::NavigationPlugin;
+use crate::plugins::move_plugin::MovePlugin;
+use crate::plugins::snap_plugin::SnapPlugin;
+use crate::plugins::view_plugin::ViewPlugin;
+use crate::plugins::viewport_plugin::ViewportPlugin;
 use tokio::runtime::Runtime;
 
-use crate::{ai::{
-                ai_client::AiClient,
-                secretive_secret::API_KEY,
-            }};
+use crate::ai::{ai_client::AiClient, conversation::Conversation, requests::AiRequests};
 
 use bevy::prelude::*;
 use ui::output_console::OutputConsole;
 use part::primitives;
-use tools::colors;
 use part::components::ExtrusionParams;
-use ui::{ui_elements::ToolbarAction, EditorMode, output_console::AsyncRuntime};
+use part::mass_properties::MaterialDensity;
+use tools::units::DocumentUnits;
+use ui::{ui_elements::ToolbarAction, EditorMode, output_console::{AsyncRuntime, ConsoleCommand}};
 use bevy_egui::{EguiPlugin, EguiContexts};
 
 // #[derive(Component)]
@@ -43,19 +53,35 @@ fn main() {
         // MeshPickingPlugin is not a default plugin
         .add_plugins((DefaultPlugins, 
                     MeshPickingPlugin,
-                    GlobalGizmoPlugin,
-                    // AiConsolePlugin,
+                    GridPlugin,
+                    ViewPlugin,
+                    ViewportPlugin,
+                    DisplayPlugin,
+                    SectionPlugin,
+                    NavCubePlugin,
+                    NavigationPlugin,
+                    SnapPlugin,
+                    MeasurePlugin,
+                    MovePlugin,
+                    (RegionSelectPlugin, PartSelectionPlugin, SmartSelectPlugin),
+                    (AiSettingsPlugin, AiCommandPlugin, AiPreviewPlugin),
                     EguiPlugin))
         .add_event::<ToolbarAction>()
+        .add_event::<ConsoleCommand>()
         .insert_resource(ExtrusionParams {
             direction: Vec3::Y,
             distance: 1.0,
         })
-        .insert_resource(AiClient::new(API_KEY.to_string()))
+        .init_resource::<AiClient>()
+        .init_resource::<AiRequests>()
+        .init_resource::<Conversation>()
         .insert_resource(AsyncRuntime(Runtime::new().expect("Failed to create Tokio runtime")))
         .insert_resource(OutputConsole::new(100))
         .init_gizmo_group::<MyRoundGizmos>()
         .init_resource::<EditorMode>()
+        .init_resource::<MaterialDensity>()
+        .init_resource::<DocumentUnits>()
+        .init_resource::<ui::MenuItems>()
         // .init_resource::<GizmoState>()
         // .add_plugins(WorldInspectorPlugin::new())
         .add_systems(Startup, 
@@ -69,13 +95,21 @@ fn main() {
             ui::button_action_system,
             ui::handle_toolbar_actions,
             ui::update_selection_mode_buttons,
+            (ui::menu_button_system, ui::menu_ui_system),
             // Part interaction systems in specific order
             part::mouse_part_systems::handle_face_selection,
+            part::mouse_part_systems::handle_part_selection,
             part::mouse_part_systems::update_materials_system.after(part::mouse_part_systems::handle_face_selection),
             part::mouse_part_systems::draw_mesh_intersections,
             part::mouse_part_systems::rotate,
-            draw_gizmos,
             ui::console_ui_system,
+            ui::document_settings_ui_system,
+            (
+                ui::console_help_system,
+                ui::mass_properties_console_system,
+                ui::units_console_system,
+            ),
+            ui::update_properties_panel,
             ui::handle_api_response,
         ).chain())
         .run();
@@ -92,13 +126,6 @@ fn setup_scene(
     let points = primitives::CubePoints::get_points();
 
     part::create_3d_object_system(&mut commands, &mut meshes, &mut materials, points);
-    let no_change_matl = materials.add(colors::NO_CHANGE_COLOR);
-    // Ground
-    commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0).subdivisions(10))),
-        MeshMaterial3d(no_change_matl.clone()),
-        PickingBehavior::IGNORE, // Disable picking for the ground plane.
-    ));
 
     // Light
     commands.spawn((
@@ -113,11 +140,7 @@ fn setup_scene(
     ));
 
     // Camera that can be panned and orbited
-    let (camera_bundle, pan_orbit) = view::spawn_camera();
-    commands.spawn((
-        camera_bundle,
-        pan_orbit,
-    ));
+    commands.spawn((view::spawn_camera(), view::viewports::ActiveViewport));
 
     // Instructions
     // commands.spawn((
@@ -199,39 +222,6 @@ fn setup_scene(
 #[derive(Default, Reflect, GizmoConfigGroup)]
 struct MyRoundGizmos {}
 
-fn draw_gizmos(
-    mut gizmos: Gizmos,
-    _time: Res<Time>,
-    camera_q: Query<(&Camera, &GlobalTransform), With<Camera>>,
-    windows: Query<&Window>,
-) {
-    // Draw orientation gizmo in top-left corner
-    if let (Ok((camera, camera_transform)), Ok(_window)) = (camera_q.get_single(), windows.get_single()) {
-        let screen_pos = Vec2::new(
-            100.0,  // 100 pixels from left
-            100.0,  // 100 pixels from top
-        );
-        
-        if let Ok(ray) = camera.viewport_to_world(camera_transform, screen_pos) {
-            let distance = 5.0;
-            let gizmo_pos = ray.origin + ray.direction * distance;
-            // Use identity rotation to keep axes aligned with world space
-            let gizmo_transform = Transform::from_translation(gizmo_pos).with_rotation(Quat::IDENTITY);
-            gizmos.axes(gizmo_transform, 0.3);
-        }
-    }
-
-    // Draw world origin marker
-    gizmos.cross(Vec3::new(0., 0., 0.), 0.5, colors::RED);
-    gizmos.grid(
-        Quat::from_rotation_x(PI / 2.),
-        UVec2::splat(20),
-        Vec2::new(2., 2.),
-        // Light gray
-        LinearRgba::gray(0.65),
-    );
-}
-
 // fn update_gizmo_state(
 //     mut gizmo_state: ResMut<GizmoState>,
 //     part_q: Query<(&Part, &GlobalTransform)>,
@@ -1,46 +1,75 @@
 use bevy::prelude::*;
+use super::naming::{FeatureId, TopoName};
 
 /// A marker component for our shapes so we can query them separately from the ground plane.
-#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[derive(Component, Clone, Copy, Debug)]
 pub struct Vertex {
     pub coordinates: Vec3,
+    pub name: TopoName,
 }
 
 impl Vertex {
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         Vertex {
             coordinates: Vec3::new(x, y, z),
+            name: TopoName::default(),
         }
     }
 
+    pub fn named(point: Vec3, name: TopoName) -> Self {
+        Vertex { coordinates: point, name }
+    }
+
     pub fn get_coordinates(&self) -> Vec3 {
         self.coordinates
     }
 
     pub fn point_to_vertex(point: Vec3) -> Vertex {
-        Vertex { coordinates: point }
+        Vertex { coordinates: point, name: TopoName::default() }
     }
 
     pub fn points_to_vertices(points: &Vec<Vec3>) -> Vec<Vertex> {
         points.into_iter().map(|p| Vertex::point_to_vertex(*p)).collect()
     }
+
+    /// The same vertex in a copy made by `feature`
+    pub fn copied(&self, feature: FeatureId) -> Self {
+        Vertex { coordinates: self.coordinates, name: self.name.copied(feature) }
+    }
 }
 
-#[derive(Component, Clone, Debug, PartialEq)]
+impl PartialEq for Vertex {
+    fn eq(&self, other: &Self) -> bool {
+        // Named vertices are the same vertex wherever they moved to,
+        // unnamed ones can only be told apart by their position
+        if self.name.is_named() || other.name.is_named() {
+            return self.name == other.name;
+        }
+        (self.coordinates - other.coordinates).length_squared() < 0.0001
+    }
+}
+
+#[derive(Component, Clone, Debug)]
 pub struct Edge {
     pub start: Vertex,
     pub end: Vertex,
+    pub name: TopoName,
 }
 
 impl Edge {
     pub fn new(start: Vertex, end: Vertex) -> Self {
-        Edge { start, end }
+        Edge { start, end, name: TopoName::default() }
+    }
+
+    pub fn named(start: Vertex, end: Vertex, name: TopoName) -> Self {
+        Edge { start, end, name }
     }
 
     pub fn with_points(points: Vec<Vec3>) -> Self {
         Edge {
             start: Vertex::point_to_vertex(points[0]),
             end: Vertex::point_to_vertex(points[1]),
+            name: TopoName::default(),
         }
     }
 
@@ -48,13 +77,32 @@ impl Edge {
         Edge {
             start: vertices[0],
             end: vertices[1],
+            name: TopoName::default(),
         }
     }
 
     pub fn get_vertices(&self) -> Vec<Vertex> {
         vec![self.start, self.end]
     }
-    
+
+    /// The same edge walked in the opposite direction
+    pub fn reversed(&self) -> Self {
+        Edge { start: self.end, end: self.start, name: self.name }
+    }
+
+    pub fn copied(&self, feature: FeatureId) -> Self {
+        Edge { start: self.start.copied(feature), end: self.end.copied(feature), name: self.name.copied(feature) }
+    }
+}
+
+impl PartialEq for Edge {
+    fn eq(&self, other: &Self) -> bool {
+        if self.name.is_named() || other.name.is_named() {
+            return self.name == other.name;
+        }
+        (self.start == other.start && self.end == other.end)
+            || (self.start == other.end && self.end == other.start)
+    }
 }
 
 #[derive(Component, Clone, Debug)]
@@ -62,6 +110,7 @@ pub struct Face {
     pub vertices: Vec<Vertex>,
     pub edges: Vec<Edge>,
     pub normal: Vec3,
+    pub name: TopoName,
 }
 
 impl Face {
@@ -74,13 +123,29 @@ impl Face {
             vertices: self.vertices.clone(),
             edges: self.edges.clone(),
             normal: self.normal.clone(),
+            name: self.name,
+        }
+    }
+
+    pub fn copied(&self, feature: FeatureId) -> Self {
+        Face {
+            vertices: self.vertices.iter().map(|vertex| vertex.copied(feature)).collect(),
+            edges: self.edges.iter().map(|edge| edge.copied(feature)).collect(),
+            normal: self.normal,
+            name: self.name.copied(feature),
         }
     }
 }
 
 impl PartialEq for Face {
     fn eq(&self, other: &Self) -> bool {
-        // Two faces are equal if they have the same vertices (in any order)
+        // Faces created by a feature keep their name however the geometry changes,
+        // so a selection still matches after the face was moved or re-extruded
+        if self.name.is_named() || other.name.is_named() {
+            return self.name == other.name;
+        }
+
+        // Unnamed faces are equal if they have the same vertices (in any order)
         // and the same normal direction
         if self.vertices.len() != other.vertices.len() {
             return false;
@@ -141,10 +206,53 @@ impl Part {
             selected_faces: Vec::new(),
         }
     }
-}
 
-#[derive(Component)]
-pub struct FaceSelection;
+    /// A copy with every entity renamed after `feature`, so it never matches the original.
+    /// Nothing is selected in it.
+    pub fn copied(&self, feature: FeatureId) -> Self {
+        Part {
+            vertices: self.vertices.iter().map(|vertex| vertex.copied(feature)).collect(),
+            edges: self.edges.iter().map(|edge| edge.copied(feature)).collect(),
+            faces: self.faces.iter().map(|face| face.copied(feature)).collect(),
+            ..Part::new()
+        }
+    }
+
+    /// Re-resolves the selection by name against the current topology.
+    /// Selected entities pick up their new geometry and those that no longer exist are dropped.
+    pub fn refresh_selection(&mut self) {
+        let faces = &self.faces;
+        self.selected_faces = self.selected_faces.iter()
+            .filter_map(|selected| faces.iter().find(|face| *face == selected).cloned())
+            .collect();
+        let edges = &self.edges;
+        self.selected_edges = self.selected_edges.iter()
+            .filter_map(|selected| edges.iter().find(|edge| *edge == selected).cloned())
+            .collect();
+        let vertices = &self.vertices;
+        self.selected_vertices = self.selected_vertices.iter()
+            .filter_map(|selected| vertices.iter().find(|vertex| *vertex == selected).copied())
+            .collect();
+    }
+}
 
-#[derive(Component)]
+/// A part selected as a whole in part selection mode, part operations work on these
+#[derive(Component, Debug, Clone, Copy)]
 pub struct PartSelection;
+
+/// Number a part keeps for the whole session, the AI refers to parts by it
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartId(pub u32);
+
+/// Colour of a part's faces when they are neither selected nor hovered
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PartColor(pub Color);
+
+/// Named group a part belongs to, commands can address the group's parts by it
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct PartGroup(pub String);
+
+/// Shading of a face, showing selection and hover. The face entity itself only serves
+/// picking, each display mode draws it through its own child entity.
+#[derive(Component, Clone, Debug)]
+pub struct FaceMaterial(pub Handle<StandardMaterial>);
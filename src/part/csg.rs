@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::tools::polygon::newell_normal;
+
+// Boolean operations on closed polygon meshes with BSP trees, after csg.js by Evan Wallace.
+// Polygons are wound counter-clockwise seen from outside. The results are split along the
+// planes of the other solid, so they may have T-junctions; part_from_polygons welds them.
+
+/// Distance below which a point counts as on a plane
+const EPSILON: f32 = 1e-5;
+
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BooleanOp {
+    Union,
+    Difference,
+    Intersection,
+}
+
+impl BooleanOp {
+    pub const NAMES: &'static [&'static str] = &["union", "difference", "intersection"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "union" => Some(BooleanOp::Union),
+            "difference" => Some(BooleanOp::Difference),
+            "intersection" => Some(BooleanOp::Intersection),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BooleanOp::Union => "union",
+            BooleanOp::Difference => "difference",
+            BooleanOp::Intersection => "intersection",
+        }
+    }
+
+    /// Combines the solid `a` with `b`, a difference takes `b` away from `a`
+    pub fn apply(self, a: &[Vec<Vec3>], b: &[Vec<Vec3>]) -> Vec<Vec<Vec3>> {
+        let mut a = Node::new(to_polygons(a));
+        let mut b = Node::new(to_polygons(b));
+        match self {
+            BooleanOp::Union => {
+                a.clip_to(&b);
+                b.clip_to(&a);
+                b.invert();
+                b.clip_to(&a);
+                b.invert();
+                a.build(b.all_polygons());
+            }
+            BooleanOp::Difference => {
+                a.invert();
+                a.clip_to(&b);
+                b.clip_to(&a);
+                b.invert();
+                b.clip_to(&a);
+                b.invert();
+                a.build(b.all_polygons());
+                a.invert();
+            }
+            BooleanOp::Intersection => {
+                a.invert();
+                b.clip_to(&a);
+                b.invert();
+                a.clip_to(&b);
+                b.clip_to(&a);
+                a.build(b.all_polygons());
+                a.invert();
+            }
+        }
+        a.all_polygons().into_iter().map(|polygon| polygon.vertices).collect()
+    }
+}
+
+fn to_polygons(polygons: &[Vec<Vec3>]) -> Vec<Polygon> {
+    polygons.iter().filter_map(|vertices| Polygon::new(vertices.clone())).collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Plane {
+    normal: Vec3,
+    w: f32,
+}
+
+impl Plane {
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+
+    /// Sorts `polygon` into the lists by the side of the plane it lies on, splitting it when it spans the plane
+    fn split(
+        &self,
+        polygon: Polygon,
+        coplanar_front: &mut Vec<Polygon>,
+        coplanar_back: &mut Vec<Polygon>,
+        front: &mut Vec<Polygon>,
+        back: &mut Vec<Polygon>,
+    ) {
+        let types: Vec<u8> = polygon.vertices.iter()
+            .map(|vertex| {
+                let t = self.normal.dot(*vertex) - self.w;
+                if t < -EPSILON {
+                    BACK
+                } else if t > EPSILON {
+                    FRONT
+                } else {
+                    COPLANAR
+                }
+            })
+            .collect();
+        match types.iter().fold(COPLANAR, |all, t| all | t) {
+            COPLANAR if self.normal.dot(polygon.plane.normal) > 0.0 => coplanar_front.push(polygon),
+            COPLANAR => coplanar_back.push(polygon),
+            FRONT => front.push(polygon),
+            BACK => back.push(polygon),
+            _ => {
+                let (mut f, mut b) = (Vec::new(), Vec::new());
+                let count = polygon.vertices.len();
+                for i in 0..count {
+                    let j = (i + 1) % count;
+                    let (ti, tj) = (types[i], types[j]);
+                    let (vi, vj) = (polygon.vertices[i], polygon.vertices[j]);
+                    if ti != BACK {
+                        f.push(vi);
+                    }
+                    if ti != FRONT {
+                        b.push(vi);
+                    }
+                    if ti | tj == SPANNING {
+                        let t = (self.w - self.normal.dot(vi)) / self.normal.dot(vj - vi);
+                        let v = vi.lerp(vj, t);
+                        f.push(v);
+                        b.push(v);
+                    }
+                }
+                // The pieces lie in the plane of the whole
+                if f.len() >= 3 {
+                    front.push(Polygon { vertices: f, plane: polygon.plane });
+                }
+                if b.len() >= 3 {
+                    back.push(Polygon { vertices: b, plane: polygon.plane });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Polygon {
+    vertices: Vec<Vec3>,
+    plane: Plane,
+}
+
+impl Polygon {
+    /// None for polygons without area
+    fn new(vertices: Vec<Vec3>) -> Option<Self> {
+        let normal = newell_normal(&vertices).try_normalize()?;
+        let w = normal.dot(vertices[0]);
+        Some(Polygon { vertices, plane: Plane { normal, w } })
+    }
+
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane.flip();
+    }
+}
+
+/// BSP tree node, the polygons in front of its plane go to `front` and those behind to `back`
+#[derive(Debug, Default)]
+struct Node {
+    plane: Option<Plane>,
+    front: Option<Box<Node>>,
+    back: Option<Box<Node>>,
+    polygons: Vec<Polygon>,
+}
+
+impl Node {
+    fn new(polygons: Vec<Polygon>) -> Self {
+        let mut node = Node::default();
+        node.build(polygons);
+        node
+    }
+
+    /// Turns the solid inside out
+    fn invert(&mut self) {
+        for polygon in &mut self.polygons {
+            polygon.flip();
+        }
+        if let Some(plane) = &mut self.plane {
+            plane.flip();
+        }
+        if let Some(front) = &mut self.front {
+            front.invert();
+        }
+        if let Some(back) = &mut self.back {
+            back.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// The parts of `polygons` outside this solid
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let Some(plane) = self.plane else {
+            return polygons;
+        };
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        for polygon in polygons {
+            let (mut coplanar_front, mut coplanar_back) = (Vec::new(), Vec::new());
+            plane.split(polygon, &mut coplanar_front, &mut coplanar_back, &mut front, &mut back);
+            front.append(&mut coplanar_front);
+            back.append(&mut coplanar_back);
+        }
+        let front = match &self.front {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        let back = match &self.back {
+            Some(node) => node.clip_polygons(back),
+            None => Vec::new(),
+        };
+        [front, back].concat()
+    }
+
+    /// Removes the polygons of this tree inside `bsp`
+    fn clip_to(&mut self, bsp: &Node) {
+        self.polygons = bsp.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(front) = &mut self.front {
+            front.clip_to(bsp);
+        }
+        if let Some(back) = &mut self.back {
+            back.clip_to(bsp);
+        }
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        let mut polygons = self.polygons.clone();
+        if let Some(front) = &self.front {
+            polygons.extend(front.all_polygons());
+        }
+        if let Some(back) = &self.back {
+            polygons.extend(back.all_polygons());
+        }
+        polygons
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        if polygons.is_empty() {
+            return;
+        }
+        let plane = *self.plane.get_or_insert(polygons[0].plane);
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        let (mut coplanar_front, mut coplanar_back) = (Vec::new(), Vec::new());
+        for polygon in polygons {
+            plane.split(polygon, &mut coplanar_front, &mut coplanar_back, &mut front, &mut back);
+        }
+        self.polygons.append(&mut coplanar_front);
+        self.polygons.append(&mut coplanar_back);
+        if !front.is_empty() {
+            self.front.get_or_insert_with(Default::default).build(front);
+        }
+        if !back.is_empty() {
+            self.back.get_or_insert_with(Default::default).build(back);
+        }
+    }
+}
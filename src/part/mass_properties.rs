@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use bevy::prelude::*;
+
+use super::components::{Face, Part};
+use super::naming::TopoName;
+use crate::tools::units::DocumentUnits;
+use crate::tools::vec3_rounded::Vec3Rounded;
+
+/// Density used for mass calculations, in mass per cubic model unit
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct MaterialDensity(pub f32);
+
+impl Default for MaterialDensity {
+    fn default() -> Self {
+        MaterialDensity(1.0)
+    }
+}
+
+/// Mass properties of a closed part, expressed in the part's local coordinates
+#[derive(Debug, Clone)]
+pub struct MassProperties {
+    pub volume: f32,
+    pub mass: f32,
+    pub surface_area: f32,
+    pub face_areas: Vec<(TopoName, f32)>,
+    pub centroid: Vec3,
+    /// Inertia tensor about the centre of mass
+    pub inertia_centroid: Mat3,
+    /// Inertia tensor about the part origin
+    pub inertia_origin: Mat3,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MassPropertiesError {
+    NoFaces,
+    /// Some edge is not shared by exactly two faces walking it in opposite directions
+    NotClosed,
+    ZeroVolume,
+}
+
+impl fmt::Display for MassPropertiesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MassPropertiesError::NoFaces => write!(f, "part has no faces"),
+            MassPropertiesError::NotClosed => write!(f, "part is not a closed solid"),
+            MassPropertiesError::ZeroVolume => write!(f, "part has no volume"),
+        }
+    }
+}
+
+impl std::error::Error for MassPropertiesError {}
+
+pub fn compute_mass_properties(part: &Part, density: f32) -> Result<MassProperties, MassPropertiesError> {
+    compute_faces_mass_properties(&part.faces, density)
+}
+
+/// Computes volume, centroid and inertia with the divergence theorem.
+///
+/// Every face is fanned into triangles and each triangle forms a signed tetrahedron with
+/// the origin, so the faces have to form a closed shell wound counter-clockwise seen from outside.
+pub fn compute_faces_mass_properties(faces: &[Face], density: f32) -> Result<MassProperties, MassPropertiesError> {
+    if faces.is_empty() {
+        return Err(MassPropertiesError::NoFaces);
+    }
+    if !is_closed(faces) {
+        return Err(MassPropertiesError::NotClosed);
+    }
+
+    let mut volume = 0.0;
+    let mut first_moment = Vec3::ZERO;
+    // Second moment (covariance) of the volume about the origin, integral of x x^T
+    let mut covariance = Mat3::ZERO;
+    let mut face_areas = Vec::with_capacity(faces.len());
+
+    for face in faces {
+        let points: Vec<Vec3> = face.vertices.iter().map(|v| v.coordinates).collect();
+        face_areas.push((face.name, polygon_area(&points)));
+
+        for i in 1..points.len().saturating_sub(1) {
+            let (a, b, c) = (points[0], points[i], points[i + 1]);
+            let det = a.dot(b.cross(c));
+            let sum = a + b + c;
+
+            volume += det / 6.0;
+            first_moment += sum * det / 24.0;
+            covariance += (outer(a) + outer(b) + outer(c) + outer(sum)) * (det / 120.0);
+        }
+    }
+
+    if volume.abs() < f32::EPSILON {
+        return Err(MassPropertiesError::ZeroVolume);
+    }
+
+    let centroid = first_moment / volume;
+    let centroid_covariance = covariance - outer(centroid) * volume;
+
+    Ok(MassProperties {
+        volume,
+        mass: volume * density,
+        surface_area: face_areas.iter().map(|(_, area)| area).sum(),
+        face_areas,
+        centroid,
+        inertia_centroid: inertia_from_covariance(centroid_covariance) * density,
+        inertia_origin: inertia_from_covariance(covariance) * density,
+    })
+}
+
+pub fn polygon_area(points: &[Vec3]) -> f32 {
+    let mut sum = Vec3::ZERO;
+    for i in 0..points.len() {
+        sum += points[i].cross(points[(i + 1) % points.len()]);
+    }
+    sum.length() / 2.0
+}
+
+// Every directed edge of a closed, consistently wound shell is walked backwards by a neighbour
+fn is_closed(faces: &[Face]) -> bool {
+    let mut edges: HashMap<(Vec3Rounded, Vec3Rounded), i32> = HashMap::new();
+    for face in faces {
+        let count = face.vertices.len();
+        for i in 0..count {
+            let start = Vec3Rounded::from(face.vertices[i].coordinates);
+            let end = Vec3Rounded::from(face.vertices[(i + 1) % count].coordinates);
+            *edges.entry((start, end)).or_default() += 1;
+        }
+    }
+    edges.iter().all(|((start, end), count)| edges.get(&(*end, *start)) == Some(count))
+}
+
+fn outer(v: Vec3) -> Mat3 {
+    Mat3::from_cols(v * v.x, v * v.y, v * v.z)
+}
+
+// I = tr(C) E - C
+fn inertia_from_covariance(covariance: Mat3) -> Mat3 {
+    let trace = covariance.x_axis.x + covariance.y_axis.y + covariance.z_axis.z;
+    Mat3::IDENTITY * trace - covariance
+}
+
+impl MassProperties {
+    /// The full report, one line each, in the document units and precision
+    pub fn report(&self, units: &DocumentUnits) -> Vec<String> {
+        let row = |m: &Mat3, i: usize| {
+            let r = m.row(i);
+            format!("  [{} {} {}]", units.format_inertia(r.x), units.format_inertia(r.y), units.format_inertia(r.z))
+        };
+        let mut lines = vec![
+            format!("Volume: {}", units.format_volume(self.volume)),
+            format!("Mass: {}", units.format_number(self.mass)),
+            format!("Surface area: {} ({} faces)", units.format_area(self.surface_area), self.face_areas.len()),
+            format!("Centre of mass: ({})", units.format_point(self.centroid)),
+            format!("Inertia about centroid ({}):", units.inertia_symbol()),
+        ];
+        lines.extend((0..3).map(|i| row(&self.inertia_centroid, i)));
+        lines.push(format!("Inertia about origin ({}):", units.inertia_symbol()));
+        lines.extend((0..3).map(|i| row(&self.inertia_origin, i)));
+        lines
+    }
+}
@@ -2,5 +2,19 @@ pub mod components;
 pub mod part_edit_systems;
 pub mod mouse_part_systems;
 pub mod primitives;
+pub mod naming;
+pub mod mass_properties;
+pub mod selection;
+pub mod csg;
+#[cfg(test)]
+pub mod test_naming;
+#[cfg(test)]
+pub mod test_mass_properties;
+#[cfg(test)]
+pub mod test_selection;
+#[cfg(test)]
+pub mod test_extrude;
+#[cfg(test)]
+pub mod test_csg;
 
-pub use part_edit_systems::*;
\ No newline at end of file
+pub use part_edit_systems::*;
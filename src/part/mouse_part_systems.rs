@@ -1,47 +1,57 @@
+use std::collections::HashMap;
+
 use bevy::{color::palettes::tailwind::*, picking::pointer::PointerInteraction, prelude::*};
 use crate::tools::{colors::{HOVER_COLOR, NO_CHANGE_COLOR, PRESSED_COLOR}, components::Shape};
-use super::components::{Face, Part};
+use super::components::{Face, FaceMaterial, Part, PartColor, PartSelection};
 use crate::ui::ui_button_systems::EditorMode;
 
 pub fn update_materials_system(
     pointers: Query<&PointerInteraction>,
-    mut mesh_query: Query<(Entity, &mut MeshMaterial3d<StandardMaterial>, &Face, &Parent)>,
-    part_query: Query<&Part>,
+    mut mesh_query: Query<(Entity, &mut FaceMaterial, &Face, &Parent)>,
+    part_query: Query<(&Part, Has<PartSelection>, Option<&PartColor>)>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     selection_mode: Res<EditorMode>,
+    mut palette: Local<Option<[Handle<StandardMaterial>; 3]>>,
+    mut part_colors: Local<HashMap<[u8; 4], Handle<StandardMaterial>>>,
 ) {
-    if *selection_mode != EditorMode::SelectFace {
-        return;
-    }
+    let whole_parts = match *selection_mode {
+        EditorMode::SelectFace => false,
+        EditorMode::SelectPart => true,
+        _ => return,
+    };
 
-    let no_change_matl = materials.add(NO_CHANGE_COLOR);
-    let hover_matl = materials.add(HOVER_COLOR);
-    let pressed_matl = materials.add(PRESSED_COLOR);
-
-    // First set all materials to their default state
-    for (_entity, mut material, face, parent) in mesh_query.iter_mut() {
-        if let Ok(part) = part_query.get(parent.get()) {
-            // Check if this face is selected
-            let is_selected = part.selected_faces.iter().any(|selected_face| selected_face == face);
-            material.0 = if is_selected {
-                pressed_matl.clone()
-            } else {
-                no_change_matl.clone()
-            };
-        }
-    }
+    // Created once, the display modes derive their own materials from these
+    let [no_change_matl, hover_matl, pressed_matl] = palette
+        .get_or_insert_with(|| [materials.add(NO_CHANGE_COLOR), materials.add(HOVER_COLOR), materials.add(PRESSED_COLOR)])
+        .clone();
 
-    // Then handle hover states
-    for interaction in pointers.iter() {
-        if let Some((hovered_entity, _)) = interaction.get_nearest_hit() {
-            if let Ok((_, mut material, face, parent)) = mesh_query.get_mut(*hovered_entity) {
-                if let Ok(part) = part_query.get(parent.get()) {
-                    // Only show hover if not selected
-                    if !part.selected_faces.iter().any(|selected_face| selected_face == face) {
-                        material.0 = hover_matl.clone();
-                    }
-                }
-            }
+    // Part selection highlights every face of the part
+    let is_selected = |part: &Part, part_selected: bool, face: &Face| {
+        if whole_parts { part_selected } else { part.selected_faces.iter().any(|selected_face| selected_face == face) }
+    };
+    let hovered = pointers.iter()
+        .filter_map(|interaction| interaction.get_nearest_hit())
+        .find_map(|(entity, _)| mesh_query.get(*entity).ok().map(|(entity, _, _, parent)| (entity, parent.get())));
+
+    for (entity, mut material, face, parent) in mesh_query.iter_mut() {
+        let Ok((part, part_selected, color)) = part_query.get(parent.get()) else {
+            continue;
+        };
+        let is_hovered = hovered.is_some_and(|(hovered_face, hovered_part)| {
+            if whole_parts { hovered_part == parent.get() } else { hovered_face == entity }
+        });
+        // Only show hover if not selected
+        let new_material = if is_selected(part, part_selected, face) {
+            &pressed_matl
+        } else if is_hovered {
+            &hover_matl
+        } else if let Some(PartColor(color)) = color {
+            part_colors.entry(color.to_srgba().to_u8_array()).or_insert_with(|| materials.add(*color))
+        } else {
+            &no_change_matl
+        };
+        if material.0 != *new_material {
+            material.0 = new_material.clone();
         }
     }
 }
@@ -89,6 +99,50 @@ pub fn handle_face_selection(
     }
 }
 
+/// Click selection of whole parts. Shift toggles the clicked part, Ctrl removes it.
+pub fn handle_part_selection(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    pointers: Query<&PointerInteraction>,
+    face_query: Query<&Parent, With<Face>>,
+    part_query: Query<(Entity, Has<PartSelection>), With<Part>>,
+    selection_mode: Res<EditorMode>,
+) {
+    if *selection_mode != EditorMode::SelectPart || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(clicked) = pointers.iter()
+        .filter_map(|interaction| interaction.get_nearest_hit())
+        .find_map(|(entity, _)| face_query.get(*entity).ok().map(|parent| parent.get()))
+    else {
+        return;
+    };
+    let Ok((_, selected)) = part_query.get(clicked) else {
+        return;
+    };
+
+    if keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        set_part_selected(&mut commands, clicked, false);
+    } else if keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        set_part_selected(&mut commands, clicked, !selected);
+    } else {
+        for (entity, selected) in part_query.iter() {
+            if selected != (entity == clicked) {
+                set_part_selected(&mut commands, entity, entity == clicked);
+            }
+        }
+    }
+}
+
+pub fn set_part_selected(commands: &mut Commands, part: Entity, selected: bool) {
+    if selected {
+        commands.entity(part).insert(PartSelection);
+    } else {
+        commands.entity(part).remove::<PartSelection>();
+    }
+}
+
 /// A system that draws hit indicators for every pointer.
 pub fn draw_mesh_intersections(pointers: Query<&PointerInteraction>, mut gizmos: Gizmos) {
     for (point, normal) in pointers
@@ -108,8 +162,12 @@ pub fn rotate(mut query: Query<&mut Transform, With<Shape>>, time: Res<Time>) {
     }
 }
 
-/// An observer to rotate an entity when it is dragged
-pub fn rotate_on_drag(drag: Trigger<Pointer<Drag>>, mut transforms: Query<&mut Transform>) {
+/// An observer to rotate an entity when it is dragged with the rotate tool
+pub fn rotate_on_drag(drag: Trigger<Pointer<Drag>>, mut transforms: Query<&mut Transform>, mode: Res<EditorMode>) {
+    // Left drags in the other modes draw selection regions
+    if *mode != EditorMode::RotatePart || drag.button != PointerButton::Primary {
+        return;
+    }
     let mut transform = transforms.get_mut(drag.entity()).unwrap();
     transform.rotate_y(drag.delta.x * 0.02);
     transform.rotate_x(drag.delta.y * 0.02);
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Feature ids are handed out globally so names never collide between parts
+static NEXT_FEATURE_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Id of the modelling operation (feature) that created a piece of topology.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FeatureId(pub u32);
+
+impl FeatureId {
+    pub fn next() -> Self {
+        FeatureId(NEXT_FEATURE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Makes sure ids handed out from now on are larger than `id`,
+    /// e.g. after loading names from a saved file.
+    pub fn reserve(id: FeatureId) {
+        NEXT_FEATURE_ID.fetch_max(id.0 + 1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TopoKind {
+    Vertex,
+    Edge,
+    Face,
+}
+
+impl TopoKind {
+    fn tag(&self) -> char {
+        match self {
+            TopoKind::Vertex => 'v',
+            TopoKind::Edge => 'e',
+            TopoKind::Face => 'f',
+        }
+    }
+
+    fn from_tag(tag: char) -> Option<Self> {
+        match tag {
+            'v' => Some(TopoKind::Vertex),
+            'e' => Some(TopoKind::Edge),
+            'f' => Some(TopoKind::Face),
+            _ => None,
+        }
+    }
+}
+
+/// How a feature generated an entity from its inputs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TopoRole {
+    /// Extruded copy of an input entity
+    Cap,
+    /// Face swept by an input edge
+    Side,
+    /// Edge swept by an input vertex
+    Lateral,
+    /// Duplicate of an input entity
+    Copy,
+}
+
+impl TopoRole {
+    fn tag(&self) -> u8 {
+        match self {
+            TopoRole::Cap => 1,
+            TopoRole::Side => 2,
+            TopoRole::Lateral => 3,
+            TopoRole::Copy => 4,
+        }
+    }
+}
+
+/// Persistent name of a vertex, edge or face.
+///
+/// Names are derived from the feature that created the entity and from the names of
+/// the entities it was generated from, never from coordinates. Changing a parameter of
+/// an upstream feature (e.g. an extrusion distance) therefore keeps every name intact,
+/// while two coincident faces created by different features stay distinguishable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TopoName {
+    pub feature: FeatureId,
+    pub kind: Option<TopoKind>,
+    pub hash: u64,
+}
+
+impl TopoName {
+    /// Name of the `index`-th entity of a primitive created by `feature`.
+    pub fn primitive(feature: FeatureId, kind: TopoKind, index: usize) -> Self {
+        let mut hasher = Fnv1a::new();
+        hasher.write(&feature.0.to_le_bytes());
+        hasher.write(&[kind.tag() as u8, 0]);
+        hasher.write(&(index as u64).to_le_bytes());
+        TopoName {
+            feature,
+            kind: Some(kind),
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Name of an entity that `feature` generated from the entities named `from`.
+    pub fn generated(feature: FeatureId, kind: TopoKind, role: TopoRole, from: &[TopoName]) -> Self {
+        let mut hasher = Fnv1a::new();
+        hasher.write(&feature.0.to_le_bytes());
+        hasher.write(&[kind.tag() as u8, role.tag()]);
+        for name in from {
+            hasher.write(&name.feature.0.to_le_bytes());
+            hasher.write(&name.hash.to_le_bytes());
+        }
+        TopoName {
+            feature,
+            kind: Some(kind),
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Name of the copy `feature` made of this entity, unnamed entities stay unnamed
+    pub fn copied(&self, feature: FeatureId) -> Self {
+        match self.kind {
+            Some(kind) => TopoName::generated(feature, kind, TopoRole::Copy, &[*self]),
+            None => *self,
+        }
+    }
+
+    pub fn is_named(&self) -> bool {
+        self.kind.is_some()
+    }
+}
+
+impl fmt::Display for TopoName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Some(kind) => write!(f, "F{}:{}{:016x}", self.feature.0, kind.tag(), self.hash),
+            None => write!(f, "unnamed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseTopoNameError(String);
+
+impl fmt::Display for ParseTopoNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid topological name '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseTopoNameError {}
+
+impl FromStr for TopoName {
+    type Err = ParseTopoNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "unnamed" {
+            return Ok(TopoName::default());
+        }
+        let err = || ParseTopoNameError(s.to_string());
+        let (feature, rest) = s.strip_prefix('F').and_then(|s| s.split_once(':')).ok_or_else(err)?;
+        let mut chars = rest.chars();
+        let kind = chars.next().and_then(TopoKind::from_tag).ok_or_else(err)?;
+        Ok(TopoName {
+            feature: FeatureId(feature.parse().map_err(|_| err())?),
+            kind: Some(kind),
+            hash: u64::from_str_radix(chars.as_str(), 16).map_err(|_| err())?,
+        })
+    }
+}
+
+impl Serialize for TopoName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TopoName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let name: TopoName = s.parse().map_err(serde::de::Error::custom)?;
+        FeatureId::reserve(name.feature);
+        Ok(name)
+    }
+}
+
+// std's DefaultHasher is not guaranteed to be stable between releases,
+// names end up in saved files so we use a fixed FNV-1a instead
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -1,71 +1,85 @@
+use std::collections::HashMap;
+
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::render::mesh::PrimitiveTopology;
 use bevy::asset::RenderAssetUsages;
 use super::components::*;
+use super::naming::{FeatureId, TopoKind, TopoName, TopoRole};
 use bevy::render::mesh::Indices;
 use super::mouse_part_systems::*;
+use crate::tools::polygon::{newell_normal, triangulate};
+use crate::tools::vec3_rounded::Vec3Rounded;
+
+/// Mesh and material assets for systems that create part geometry
+#[derive(SystemParam)]
+pub struct PartAssets<'w> {
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<StandardMaterial>>,
+}
 
 fn create_mesh_for_face(vertices: &[Vec3]) -> Mesh {
     let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
     
-    // Convert quad vertices to two triangles
+    // Ear clipped, so faces with any number of vertices and concave ones render too
     let positions = vertices.to_vec();
-    let indices = vec![0, 1, 2, 2, 3, 0];
+    let indices = triangulate(vertices).into_iter().flatten().map(|i| i as u32).collect();
     
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     mesh.insert_indices(Indices::U32(indices));
     
     // Calculate normal for the face
     let normal = calculate_face_normal(vertices);
-    let normals: Vec<[f32; 3]> = vec![[normal.x, normal.y, normal.z]; 4];
+    let normals: Vec<[f32; 3]> = vec![[normal.x, normal.y, normal.z]; vertices.len()];
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     
     mesh
 }
 
+// Vertex indices of each cube face, wound counter-clockwise when seen from outside
+const CUBE_FACES: [[usize; 4]; 6] = [
+    [3, 2, 1, 0], // Back face
+    [4, 5, 6, 7], // Front face
+    [1, 2, 6, 5], // Right face
+    [4, 7, 3, 0], // Left face
+    [7, 6, 2, 3], // Top face
+    [0, 1, 5, 4], // Bottom face
+];
+
 pub fn create_3d_object_system(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     points: Vec<Vec3>,
-) {
-    
-    let vertices = Vertex::points_to_vertices(&points);
-    let edges = create_edges_from_vertices(&vertices);
-    let faces = create_faces_from_edges(&edges);
+) -> Entity {
+    let feature = FeatureId::next();
+
+    let vertices: Vec<Vertex> = points.iter()
+        .enumerate()
+        .map(|(i, point)| Vertex::named(*point, TopoName::primitive(feature, TopoKind::Vertex, i)))
+        .collect();
+    let edges = create_edges_from_vertices(&vertices, feature);
+    let faces = create_faces_from_edges(&vertices, &edges, feature);
 
     // Create parent entity with Part component
-    let part = Part::new();
+    let mut part = Part::new();
+    part.vertices = vertices;
+    part.edges = edges;
+    part.faces = faces.clone();
 
     let parent = commands.spawn((
-        Transform::from_xyz(0.0, 0.5, 0.0),
+        Transform::IDENTITY,
         Visibility::default(),
         part,
     )).id();
 
-    // Define face vertices for each face of the cube
-    let face_vertices = vec![
-        // Back face
-        vec![points[3], points[2], points[1], points[0]],
-        // Front face
-        vec![points[4], points[5], points[6], points[7]],
-        // Right face
-        vec![points[1], points[2], points[6], points[5]],
-        // Left face
-        vec![points[4], points[7], points[3], points[0]],
-        // Top face
-        vec![points[7], points[6], points[2], points[3]],
-        // Bottom face
-        vec![points[0], points[1], points[5], points[4]],
-    ];
-
     // Spawn each face as a separate entity
-    for (i, vertices) in face_vertices.iter().enumerate() {
-        let face = faces[i].clone();
+    for face in faces {
+        let coordinates: Vec<Vec3> = face.vertices.iter().map(|v| v.coordinates).collect();
         commands.spawn((
-            Mesh3d(meshes.add(create_mesh_for_face(vertices))),
+            Mesh3d(meshes.add(create_mesh_for_face(&coordinates))),
             MeshMaterial3d(materials.add(Color::WHITE)),
-            Transform::from_xyz(0.0, 0.5, 0.0),
+            Transform::IDENTITY,
             Visibility::default(),
             face,
         ))
@@ -75,6 +89,109 @@ pub fn create_3d_object_system(
     // Add rotation to parent entity
     commands.entity(parent)
         .observe(rotate_on_drag);
+    parent
+}
+
+/// Spawns a part with the faces `polygons` in world coordinates, see `part_from_polygons`
+pub fn create_part_from_polygons(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    polygons: &[Vec<Vec3>],
+) -> Entity {
+    let part = part_from_polygons(polygons, FeatureId::next());
+    let faces = part.faces.clone();
+    let parent = commands.spawn((Transform::IDENTITY, Visibility::default(), part)).id();
+    for face in faces {
+        let coordinates: Vec<Vec3> = face.vertices.iter().map(|v| v.coordinates).collect();
+        commands.spawn((
+            Mesh3d(meshes.add(create_mesh_for_face(&coordinates))),
+            MeshMaterial3d(materials.add(Color::WHITE)),
+            Transform::IDENTITY,
+            Visibility::default(),
+            face,
+        ))
+        .set_parent(parent);
+    }
+    commands.entity(parent).observe(rotate_on_drag);
+    parent
+}
+
+/// Part made by `feature` with the faces `polygons`, wound counter-clockwise seen from outside.
+/// Coincident vertices are merged and a face's edge is split where another face has a vertex
+/// on it, so that neighbouring faces share their edges.
+pub fn part_from_polygons(polygons: &[Vec<Vec3>], feature: FeatureId) -> Part {
+    let mut vertices: Vec<Vertex> = Vec::new();
+    let mut welded: HashMap<Vec3Rounded, usize> = HashMap::new();
+    let mut loops: Vec<Vec<usize>> = Vec::new();
+    for polygon in polygons {
+        let mut indices: Vec<usize> = Vec::new();
+        for point in polygon {
+            let index = *welded.entry(Vec3Rounded::from(*point)).or_insert_with(|| {
+                vertices.push(Vertex::named(*point, TopoName::primitive(feature, TopoKind::Vertex, vertices.len())));
+                vertices.len() - 1
+            });
+            if indices.last() != Some(&index) {
+                indices.push(index);
+            }
+        }
+        if indices.len() > 1 && indices.first() == indices.last() {
+            indices.pop();
+        }
+        if indices.len() >= 3 {
+            loops.push(indices);
+        }
+    }
+
+    let mut part = Part::new();
+    let mut edge_names: HashMap<(usize, usize), TopoName> = HashMap::new();
+    for indices in loops {
+        let indices = split_at_t_junctions(&indices, &vertices);
+        let face_vertices: Vec<Vertex> = indices.iter().map(|&i| vertices[i]).collect();
+        let face_edges = (0..indices.len())
+            .map(|j| {
+                let (start, end) = (indices[j], indices[(j + 1) % indices.len()]);
+                let name = *edge_names.entry((start.min(end), start.max(end))).or_insert_with(|| {
+                    let name = TopoName::primitive(feature, TopoKind::Edge, part.edges.len());
+                    part.edges.push(Edge::named(vertices[start], vertices[end], name));
+                    name
+                });
+                Edge::named(vertices[start], vertices[end], name)
+            })
+            .collect();
+        let points: Vec<Vec3> = face_vertices.iter().map(|v| v.coordinates).collect();
+        part.faces.push(Face {
+            normal: calculate_face_normal(&points),
+            vertices: face_vertices,
+            edges: face_edges,
+            name: TopoName::primitive(feature, TopoKind::Face, part.faces.len()),
+        });
+    }
+    part.vertices = vertices;
+    part
+}
+
+// Adds the vertices lying inside the edges of a face loop, in order along each edge
+fn split_at_t_junctions(indices: &[usize], vertices: &[Vertex]) -> Vec<usize> {
+    let mut split = Vec::with_capacity(indices.len());
+    for (j, &start) in indices.iter().enumerate() {
+        let end = indices[(j + 1) % indices.len()];
+        let (a, b) = (vertices[start].coordinates, vertices[end].coordinates);
+        let length_squared = (b - a).length_squared();
+        let mut inside: Vec<(f32, usize)> = vertices.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != start && *i != end)
+            .filter_map(|(i, vertex)| {
+                let t = (vertex.coordinates - a).dot(b - a) / length_squared;
+                let on_edge = (a + (b - a) * t - vertex.coordinates).length_squared() < 1e-8;
+                (on_edge && t > 1e-4 && t < 1.0 - 1e-4).then_some((t, i))
+            })
+            .collect();
+        inside.sort_by(|x, y| x.0.total_cmp(&y.0));
+        split.push(start);
+        split.extend(inside.into_iter().map(|(_, i)| i));
+    }
+    split
 }
 
 fn create_mesh_for_object(points: Vec<Vec3>) -> Mesh {
@@ -93,7 +210,7 @@ fn create_mesh_for_object(points: Vec<Vec3>) -> Mesh {
 }
 
 // Algorithm to create edges from vertices
-fn create_edges_from_vertices(vertices: &[Vertex]) -> Vec<Edge> {
+fn create_edges_from_vertices(vertices: &[Vertex], feature: FeatureId) -> Vec<Edge> {
     let mut edges = Vec::new();
 
     // Define the connections between vertices (cube example)
@@ -103,11 +220,12 @@ fn create_edges_from_vertices(vertices: &[Vertex]) -> Vec<Edge> {
         (0, 4), (1, 5), (2, 6), (3, 7), // Side edges
     ];
 
-    for (start_idx, end_idx) in connections {
-        edges.push(Edge {
-            start: vertices[start_idx].clone(), 
-            end: vertices[end_idx].clone(),
-        });
+    for (i, (start_idx, end_idx)) in connections.into_iter().enumerate() {
+        edges.push(Edge::named(
+            vertices[start_idx],
+            vertices[end_idx],
+            TopoName::primitive(feature, TopoKind::Edge, i),
+        ));
     }
 
     edges
@@ -118,74 +236,34 @@ fn calculate_face_normal(vertices: &[Vec3]) -> Vec3 {
     if vertices.len() < 3 {
         return Vec3::Z; // Default normal if not enough vertices
     }
-    let v1 = vertices[1] - vertices[0];
-    let v2 = vertices[2] - vertices[0];
-    v1.cross(v2).normalize()
+    // Newell's normal, the first three vertices may be in line
+    newell_normal(vertices).normalize_or(Vec3::Z)
 }
 
-fn create_faces_from_edges(edges: &Vec<Edge>) -> Vec<Face> {
+fn create_faces_from_edges(vertices: &[Vertex], edges: &[Edge], feature: FeatureId) -> Vec<Face> {
     let mut faces = Vec::new();
-    
-    // Define face configurations for all six faces
-    let face_configs = vec![
-        // Front face (0,1,2,3)
-        (vec![0, 1, 2, 3], vec![
-            edges[0].start.coordinates,
-            edges[1].start.coordinates,
-            edges[2].start.coordinates,
-            edges[3].start.coordinates,
-        ]),
-        // Back face (4,5,6,7)
-        (vec![4, 5, 6, 7], vec![
-            edges[4].start.coordinates,
-            edges[5].start.coordinates,
-            edges[6].start.coordinates,
-            edges[7].start.coordinates,
-        ]),
-        // Right face (1,2,6,5)
-        (vec![1, 2, 10, 9], vec![
-            edges[1].start.coordinates,
-            edges[2].start.coordinates,
-            edges[6].start.coordinates,
-            edges[5].start.coordinates,
-        ]),
-        // Left face (0,3,7,4)
-        (vec![0, 3, 11, 8], vec![
-            edges[0].start.coordinates,
-            edges[3].start.coordinates,
-            edges[7].start.coordinates,
-            edges[4].start.coordinates,
-        ]),
-        // Top face (3,2,6,7)
-        (vec![2, 3, 11, 10], vec![
-            edges[2].start.coordinates,
-            edges[3].start.coordinates,
-            edges[7].start.coordinates,
-            edges[6].start.coordinates,
-        ]),
-        // Bottom face (0,1,5,4)
-        (vec![0, 1, 9, 8], vec![
-            edges[0].start.coordinates,
-            edges[1].start.coordinates,
-            edges[5].start.coordinates,
-            edges[4].start.coordinates,
-        ]),
-    ];
-    
-    for (edge_indices, points) in face_configs {
-        let mut vertices: Vec<Vertex> = Vec::new();
-        points.iter().for_each(|&point| {
-            vertices.push(Vertex { coordinates: point });
-        });
-        
-        let face_edges = edge_indices.iter()
-            .map(|&i| edges[i].clone())
+
+    for (i, indices) in CUBE_FACES.iter().enumerate() {
+        let face_vertices: Vec<Vertex> = indices.iter().map(|&i| vertices[i]).collect();
+
+        // Face edges follow the winding of the face, edge i runs from vertex i to vertex i + 1
+        let face_edges = (0..face_vertices.len())
+            .map(|j| {
+                let start = face_vertices[j];
+                let end = face_vertices[(j + 1) % face_vertices.len()];
+                let edge = edges.iter()
+                    .find(|e| (e.start == start && e.end == end) || (e.start == end && e.end == start))
+                    .expect("cube face edge missing");
+                Edge::named(start, end, edge.name)
+            })
             .collect();
-        
+
+        let points: Vec<Vec3> = face_vertices.iter().map(|v| v.coordinates).collect();
         faces.push(Face {
-            vertices: vertices.clone(),
-            edges: face_edges,
             normal: calculate_face_normal(&points),
+            vertices: face_vertices,
+            edges: face_edges,
+            name: TopoName::primitive(feature, TopoKind::Face, i),
         });
     }
 
@@ -201,6 +279,7 @@ pub fn extrude_faces(
     materials: &mut ResMut<Assets<StandardMaterial>>,
     parent_entity: Entity,
 ) {
+    let feature = FeatureId::next();
     let mut new_vertices = Vec::new();
     let mut new_edges = Vec::new();
     let mut new_faces = Vec::new();
@@ -211,44 +290,54 @@ pub fn extrude_faces(
         let mut face_new_vertices = Vec::new();
         let mut face_vertices_coords = Vec::new();
 
+        // Everything generated here is named after the source face and the source entity,
+        // so the names do not depend on the extrusion distance
+        let source_edges = face_edges_in_winding(face);
+
         // Create new vertices for this face
         for vertex in &face.vertices {
             let new_coord = vertex.coordinates + extrusion_vector;
-            let new_vertex = Vertex { coordinates: new_coord };
-            face_new_vertices.push(new_vertex.clone());
+            let name = TopoName::generated(feature, TopoKind::Vertex, TopoRole::Cap, &[face.name, vertex.name]);
+            let new_vertex = Vertex::named(new_coord, name);
+            face_new_vertices.push(new_vertex);
             face_vertices_coords.push(new_coord);
-            
+
             // Only add to global new_vertices if not already present
-            if !new_vertices.iter().any(|v: &Vertex| (v.coordinates - new_coord).length() < 0.0001) {
+            if !new_vertices.contains(&new_vertex) {
                 new_vertices.push(new_vertex);
             }
         }
 
         // Create new edges for the extruded face
         let mut new_face_edges = Vec::new();
+        let mut lateral_edges = Vec::new();
         for i in 0..face.vertices.len() {
             let next_i = (i + 1) % face.vertices.len();
-            
-            let new_edge = Edge {
-                start: face_new_vertices[i].clone(),
-                end: face_new_vertices[next_i].clone(),
-            };
+
+            let new_edge = Edge::named(
+                face_new_vertices[i],
+                face_new_vertices[next_i],
+                TopoName::generated(feature, TopoKind::Edge, TopoRole::Cap, &[face.name, source_edges[i].name, face.vertices[i].name]),
+            );
             new_edges.push(new_edge.clone());
             new_face_edges.push(new_edge);
 
             // Create edge connecting original to extruded vertex
-            let connecting_edge = Edge {
-                start: face.vertices[i].clone(),
-                end: face_new_vertices[i].clone(),
-            };
-            new_edges.push(connecting_edge);
+            let connecting_edge = Edge::named(
+                face.vertices[i],
+                face_new_vertices[i],
+                TopoName::generated(feature, TopoKind::Edge, TopoRole::Lateral, &[face.name, face.vertices[i].name]),
+            );
+            new_edges.push(connecting_edge.clone());
+            lateral_edges.push(connecting_edge);
         }
 
         // Create and spawn the extruded face
         let extruded_face = Face {
             vertices: face_new_vertices.clone(),
-            edges: new_face_edges,
+            edges: new_face_edges.clone(),
             normal: face.normal,
+            name: TopoName::generated(feature, TopoKind::Face, TopoRole::Cap, &[face.name]),
         };
         new_faces.push(extruded_face.clone());
 
@@ -256,19 +345,19 @@ pub fn extrude_faces(
         commands.spawn((
             Mesh3d(meshes.add(create_mesh_for_face(&face_vertices_coords))),
             MeshMaterial3d(materials.add(Color::WHITE)),
-            Transform::from_xyz(0.0, 0.5, 0.0),
+            Transform::IDENTITY,
             extruded_face,
         )).set_parent(parent_entity);
 
         // Create and spawn side faces
         for i in 0..face.vertices.len() {
             let next_i = (i + 1) % face.vertices.len();
-            
+
             let side_vertices = vec![
-                face.vertices[i].clone(),
-                face.vertices[next_i].clone(),
-                face_new_vertices[next_i].clone(),
-                face_new_vertices[i].clone(),
+                face.vertices[i],
+                face.vertices[next_i],
+                face_new_vertices[next_i],
+                face_new_vertices[i],
             ];
 
             let side_vertices_coords: Vec<Vec3> = side_vertices.iter()
@@ -276,16 +365,17 @@ pub fn extrude_faces(
                 .collect();
 
             let side_edges = vec![
-                Edge { start: face.vertices[i].clone(), end: face.vertices[next_i].clone() },
-                Edge { start: face.vertices[next_i].clone(), end: face_new_vertices[next_i].clone() },
-                Edge { start: face_new_vertices[next_i].clone(), end: face_new_vertices[i].clone() },
-                Edge { start: face_new_vertices[i].clone(), end: face.vertices[i].clone() },
+                source_edges[i].clone(),
+                lateral_edges[next_i].clone(),
+                new_face_edges[i].reversed(),
+                lateral_edges[i].reversed(),
             ];
 
             let side_face = Face {
                 vertices: side_vertices,
                 edges: side_edges,
                 normal: calculate_face_normal(&side_vertices_coords),
+                name: TopoName::generated(feature, TopoKind::Face, TopoRole::Side, &[face.name, source_edges[i].name, face.vertices[i].name]),
             };
             new_faces.push(side_face.clone());
 
@@ -293,15 +383,37 @@ pub fn extrude_faces(
             commands.spawn((
                 Mesh3d(meshes.add(create_mesh_for_face(&side_vertices_coords))),
                 MeshMaterial3d(materials.add(Color::WHITE)),
-                Transform::from_xyz(0.0, 0.5, 0.0),
+                Transform::IDENTITY,
                 side_face,
             ))
             .set_parent(parent_entity);
         }
     }
 
+    // The extruded faces end up inside the solid, the caps replace them so the part stays closed.
+    // Their meshes are despawned by the caller.
+    let extruded = &part.selected_faces;
+    part.faces.retain(|face| !extruded.contains(face));
+
     // Add the new vertices, edges, and faces to the part
     part.vertices.extend(new_vertices);
     part.edges.extend(new_edges);
     part.faces.extend(new_faces);
+    part.refresh_selection();
+}
+
+// Edges of a face ordered so that edge i runs from vertex i to vertex i + 1.
+// Faces without (matching) edge data get unnamed edges built from their vertices.
+fn face_edges_in_winding(face: &Face) -> Vec<Edge> {
+    let count = face.vertices.len();
+    (0..count)
+        .map(|i| {
+            let start = face.vertices[i];
+            let end = face.vertices[(i + 1) % count];
+            face.edges.iter()
+                .find(|e| (e.start == start && e.end == end) || (e.start == end && e.end == start))
+                .map(|e| Edge::named(start, end, e.name))
+                .unwrap_or_else(|| Edge::new(start, end))
+        })
+        .collect()
 }
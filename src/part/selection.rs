@@ -0,0 +1,310 @@
+use std::collections::VecDeque;
+
+use super::components::{Edge, Face, Vertex};
+
+/// Faces closer to parallel than this count as having the same normal
+const NORMAL_TOLERANCE: f32 = 1e-4;
+/// Distance within which a vertex lies on a plane
+const PLANE_TOLERANCE: f32 = 1e-4;
+
+/// Selection commands of the Edit menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionOp {
+    All,
+    None,
+    Invert,
+    Grow,
+    Shrink,
+    Connected,
+    Coplanar,
+    SameNormal,
+    EdgeLoop,
+    EdgeRing,
+}
+
+impl SelectionOp {
+    pub const ALL: [SelectionOp; 10] = [
+        SelectionOp::All,
+        SelectionOp::None,
+        SelectionOp::Invert,
+        SelectionOp::Grow,
+        SelectionOp::Shrink,
+        SelectionOp::Connected,
+        SelectionOp::Coplanar,
+        SelectionOp::SameNormal,
+        SelectionOp::EdgeLoop,
+        SelectionOp::EdgeRing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelectionOp::All => "all",
+            SelectionOp::None => "none",
+            SelectionOp::Invert => "invert",
+            SelectionOp::Grow => "grow",
+            SelectionOp::Shrink => "shrink",
+            SelectionOp::Connected => "connected",
+            SelectionOp::Coplanar => "coplanar",
+            SelectionOp::SameNormal => "normal",
+            SelectionOp::EdgeLoop => "loop",
+            SelectionOp::EdgeRing => "ring",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SelectionOp::All => "All",
+            SelectionOp::None => "None",
+            SelectionOp::Invert => "Invert",
+            SelectionOp::Grow => "Grow",
+            SelectionOp::Shrink => "Shrink",
+            SelectionOp::Connected => "Connected",
+            SelectionOp::Coplanar => "Coplanar faces",
+            SelectionOp::SameNormal => "Faces with the same normal",
+            SelectionOp::EdgeLoop => "Edge loop",
+            SelectionOp::EdgeRing => "Edge ring",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        SelectionOp::ALL.into_iter().find(|op| op.name() == name)
+    }
+
+    pub fn works_on_faces(&self) -> bool {
+        !matches!(self, SelectionOp::EdgeLoop | SelectionOp::EdgeRing)
+    }
+
+    pub fn works_on_edges(&self) -> bool {
+        !matches!(self, SelectionOp::Coplanar | SelectionOp::SameNormal)
+    }
+}
+
+/// Faces and edges of a part with the adjacency the selection commands walk. Faces are
+/// neighbours when they share an edge, edges when they share a vertex.
+pub struct Topology {
+    pub faces: Vec<Face>,
+    pub edges: Vec<Edge>,
+    face_edges: Vec<Vec<usize>>,
+    edge_faces: Vec<Vec<usize>>,
+    edge_vertices: Vec<[usize; 2]>,
+    vertex_edges: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    pub fn new(faces: &[Face]) -> Self {
+        let mut edges: Vec<Edge> = Vec::new();
+        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut face_edges = Vec::new();
+        let mut edge_faces: Vec<Vec<usize>> = Vec::new();
+        let mut edge_vertices = Vec::new();
+        let mut vertex_edges: Vec<Vec<usize>> = Vec::new();
+
+        for (face_index, face) in faces.iter().enumerate() {
+            let outline: Vec<Edge>;
+            let face_edge_list = if face.edges.is_empty() {
+                // No edge data, walk the outline
+                let n = face.vertices.len();
+                outline = (0..n).map(|i| Edge::new(face.vertices[i], face.vertices[(i + 1) % n])).collect();
+                &outline
+            } else {
+                &face.edges
+            };
+
+            let mut indices = Vec::new();
+            for edge in face_edge_list {
+                let edge_index = match edges.iter().position(|known| known == edge) {
+                    Some(index) => index,
+                    None => {
+                        edges.push(edge.clone());
+                        edge_faces.push(Vec::new());
+                        let ends = [edge.start, edge.end].map(|vertex| {
+                            vertices.iter().position(|known| *known == vertex).unwrap_or_else(|| {
+                                vertices.push(vertex);
+                                vertex_edges.push(Vec::new());
+                                vertices.len() - 1
+                            })
+                        });
+                        for end in ends {
+                            vertex_edges[end].push(edges.len() - 1);
+                        }
+                        edge_vertices.push(ends);
+                        edges.len() - 1
+                    }
+                };
+                if !edge_faces[edge_index].contains(&face_index) {
+                    edge_faces[edge_index].push(face_index);
+                }
+                indices.push(edge_index);
+            }
+            face_edges.push(indices);
+        }
+
+        Topology { faces: faces.to_vec(), edges, face_edges, edge_faces, edge_vertices, vertex_edges }
+    }
+
+    /// New face selection, None when the command does not work on faces
+    pub fn select_faces(&self, op: SelectionOp, selected: &[Face]) -> Option<Vec<Face>> {
+        let mut chosen: Vec<bool> = self.faces.iter().map(|face| selected.contains(face)).collect();
+        match op {
+            SelectionOp::All => chosen.fill(true),
+            SelectionOp::None => chosen.fill(false),
+            SelectionOp::Invert => chosen.iter_mut().for_each(|chosen| *chosen = !*chosen),
+            SelectionOp::Grow => {
+                let before = chosen.clone();
+                for face in (0..self.faces.len()).filter(|face| before[*face]) {
+                    for neighbour in self.face_neighbours(face) {
+                        chosen[neighbour] = true;
+                    }
+                }
+            }
+            SelectionOp::Shrink => {
+                let before = chosen.clone();
+                for (face, chosen) in chosen.iter_mut().enumerate() {
+                    *chosen &= self.face_neighbours(face).iter().all(|neighbour| before[*neighbour]);
+                }
+            }
+            SelectionOp::Connected => chosen = flood(chosen, |face| self.face_neighbours(face)),
+            SelectionOp::Coplanar | SelectionOp::SameNormal => {
+                let seeds: Vec<usize> = (0..self.faces.len()).filter(|face| chosen[*face]).collect();
+                for seed in seeds {
+                    let normal = self.faces[seed].normal.normalize_or_zero();
+                    let offset = self.faces[seed].vertices.first().map_or(0.0, |v| normal.dot(v.coordinates));
+                    for (face, chosen) in chosen.iter_mut().enumerate() {
+                        let other = &self.faces[face];
+                        let parallel = normal.dot(other.normal.normalize_or_zero()) > 1.0 - NORMAL_TOLERANCE;
+                        let on_plane = other.vertices.iter().all(|v| (normal.dot(v.coordinates) - offset).abs() < PLANE_TOLERANCE);
+                        if parallel && (op == SelectionOp::SameNormal || on_plane) {
+                            *chosen = true;
+                        }
+                    }
+                }
+            }
+            SelectionOp::EdgeLoop | SelectionOp::EdgeRing => return None,
+        }
+        Some(pick(&self.faces, &chosen))
+    }
+
+    /// New edge selection, None when the command does not work on edges
+    pub fn select_edges(&self, op: SelectionOp, selected: &[Edge]) -> Option<Vec<Edge>> {
+        let mut chosen: Vec<bool> = self.edges.iter().map(|edge| selected.contains(edge)).collect();
+        match op {
+            SelectionOp::All => chosen.fill(true),
+            SelectionOp::None => chosen.fill(false),
+            SelectionOp::Invert => chosen.iter_mut().for_each(|chosen| *chosen = !*chosen),
+            SelectionOp::Grow => {
+                let before = chosen.clone();
+                for edge in (0..self.edges.len()).filter(|edge| before[*edge]) {
+                    for neighbour in self.edge_neighbours(edge) {
+                        chosen[neighbour] = true;
+                    }
+                }
+            }
+            SelectionOp::Shrink => {
+                let before = chosen.clone();
+                for (edge, chosen) in chosen.iter_mut().enumerate() {
+                    *chosen &= self.edge_neighbours(edge).iter().all(|neighbour| before[*neighbour]);
+                }
+            }
+            SelectionOp::Connected => chosen = flood(chosen, |edge| self.edge_neighbours(edge)),
+            SelectionOp::EdgeLoop | SelectionOp::EdgeRing => {
+                let seeds: Vec<usize> = (0..self.edges.len()).filter(|edge| chosen[*edge]).collect();
+                for seed in seeds {
+                    let found = if op == SelectionOp::EdgeLoop { self.edge_loop(seed) } else { self.edge_ring(seed) };
+                    for edge in found {
+                        chosen[edge] = true;
+                    }
+                }
+            }
+            SelectionOp::Coplanar | SelectionOp::SameNormal => return None,
+        }
+        Some(pick(&self.edges, &chosen))
+    }
+
+    fn face_neighbours(&self, face: usize) -> Vec<usize> {
+        let mut neighbours: Vec<usize> = self.face_edges[face].iter()
+            .flat_map(|edge| self.edge_faces[*edge].iter().copied())
+            .filter(|other| *other != face)
+            .collect();
+        neighbours.dedup();
+        neighbours
+    }
+
+    fn edge_neighbours(&self, edge: usize) -> Vec<usize> {
+        self.edge_vertices[edge].iter()
+            .flat_map(|vertex| self.vertex_edges[*vertex].iter().copied())
+            .filter(|other| *other != edge)
+            .collect()
+    }
+
+    fn share_face(&self, a: usize, b: usize) -> bool {
+        self.edge_faces[a].iter().any(|face| self.edge_faces[b].contains(face))
+    }
+
+    /// Edges continuing straight through vertices with four edges, as on a grid of quads
+    fn edge_loop(&self, start: usize) -> Vec<usize> {
+        let mut found = vec![start];
+        for mut vertex in self.edge_vertices[start] {
+            let mut current = start;
+            loop {
+                let around = &self.vertex_edges[vertex];
+                if around.len() != 4 {
+                    break;
+                }
+                // Across the vertex is the edge sharing no face with this one
+                let Some(&next) = around.iter().find(|edge| **edge != current && !self.share_face(current, **edge)) else {
+                    break;
+                };
+                if found.contains(&next) {
+                    break;
+                }
+                found.push(next);
+                let [a, b] = self.edge_vertices[next];
+                vertex = if a == vertex { b } else { a };
+                current = next;
+            }
+        }
+        found
+    }
+
+    /// Edges opposite each other across a strip of quads
+    fn edge_ring(&self, start: usize) -> Vec<usize> {
+        let mut found = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(edge) = queue.pop_front() {
+            for face in &self.edge_faces[edge] {
+                let edges = &self.face_edges[*face];
+                if edges.len() != 4 {
+                    continue;
+                }
+                // The edge of the quad touching neither end of this one
+                let ends = self.edge_vertices[edge];
+                let opposite = edges.iter().copied().find(|other| {
+                    *other != edge && self.edge_vertices[*other].iter().all(|vertex| !ends.contains(vertex))
+                });
+                if let Some(opposite) = opposite.filter(|opposite| !found.contains(opposite)) {
+                    found.push(opposite);
+                    queue.push_back(opposite);
+                }
+            }
+        }
+        found
+    }
+}
+
+fn flood(mut chosen: Vec<bool>, neighbours: impl Fn(usize) -> Vec<usize>) -> Vec<bool> {
+    let mut queue: VecDeque<usize> = (0..chosen.len()).filter(|item| chosen[*item]).collect();
+    while let Some(item) = queue.pop_front() {
+        for neighbour in neighbours(item) {
+            if !chosen[neighbour] {
+                chosen[neighbour] = true;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    chosen
+}
+
+fn pick<T: Clone>(items: &[T], chosen: &[bool]) -> Vec<T> {
+    items.iter().zip(chosen).filter(|(_, chosen)| **chosen).map(|(item, _)| item.clone()).collect()
+}
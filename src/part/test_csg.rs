@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+    use super::super::csg::BooleanOp;
+    use super::super::mass_properties::{compute_mass_properties, polygon_area};
+    use super::super::naming::FeatureId;
+    use super::super::part_edit_systems::part_from_polygons;
+    use super::super::primitives::CubePoints;
+
+    // Unit cube faces, wound counter-clockwise seen from outside
+    const FACES: [[usize; 4]; 6] = [[3, 2, 1, 0], [4, 5, 6, 7], [1, 2, 6, 5], [4, 7, 3, 0], [7, 6, 2, 3], [0, 1, 5, 4]];
+
+    fn cube(origin: Vec3) -> Vec<Vec<Vec3>> {
+        let points = CubePoints::get_points();
+        FACES.iter().map(|face| face.iter().map(|&i| points[i] + origin).collect()).collect()
+    }
+
+    fn volume(polygons: &[Vec<Vec3>]) -> f32 {
+        // Fails unless the result is a closed shell
+        compute_mass_properties(&part_from_polygons(polygons, FeatureId(1)), 1.0).unwrap().volume
+    }
+
+    #[test]
+    fn test_overlapping_cubes() {
+        let (a, b) = (cube(Vec3::ZERO), cube(Vec3::splat(0.5)));
+        assert!((volume(&BooleanOp::Union.apply(&a, &b)) - 1.875).abs() < 1e-4);
+        assert!((volume(&BooleanOp::Difference.apply(&a, &b)) - 0.875).abs() < 1e-4);
+        assert!((volume(&BooleanOp::Intersection.apply(&a, &b)) - 0.125).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_separate_cubes() {
+        let (a, b) = (cube(Vec3::ZERO), cube(Vec3::new(3.0, 0.0, 0.0)));
+        assert_eq!(BooleanOp::Union.apply(&a, &b).len(), 12);
+        assert!((volume(&BooleanOp::Difference.apply(&a, &b)) - 1.0).abs() < 1e-4);
+        assert!(BooleanOp::Intersection.apply(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn test_touching_faces_are_welded() {
+        // Side by side sharing a face, the union has no faces inside
+        let (a, b) = (cube(Vec3::ZERO), cube(Vec3::new(1.0, 0.5, 0.0)));
+        let union = part_from_polygons(&BooleanOp::Union.apply(&a, &b), FeatureId(1));
+        assert!((compute_mass_properties(&union, 1.0).unwrap().volume - 2.0).abs() < 1e-4);
+        let area: f32 = union.faces.iter()
+            .map(|face| polygon_area(&face.vertices.iter().map(|v| v.coordinates).collect::<Vec<_>>()))
+            .sum();
+        assert!((area - 11.0).abs() < 1e-4);
+    }
+}
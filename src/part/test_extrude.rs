@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use super::super::components::{ExtrusionParams, Face, Part};
+    use super::super::csg::BooleanOp;
+    use super::super::mass_properties::{compute_mass_properties, polygon_area};
+    use super::super::part_edit_systems::{create_3d_object_system, create_part_from_polygons, extrude_faces};
+    use super::super::primitives::CubePoints;
+
+    // Unit cube faces, wound counter-clockwise seen from outside
+    const FACES: [[usize; 4]; 6] = [[3, 2, 1, 0], [4, 5, 6, 7], [1, 2, 6, 5], [4, 7, 3, 0], [7, 6, 2, 3], [0, 1, 5, 4]];
+
+    fn cube(origin: Vec3) -> Vec<Vec<Vec3>> {
+        let points = CubePoints::get_points();
+        FACES.iter().map(|face| face.iter().map(|&i| points[i] + origin).collect()).collect()
+    }
+
+    fn world_with_cube() -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.run_system_once(|mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>| {
+            create_3d_object_system(&mut commands, &mut meshes, &mut materials, CubePoints::get_points());
+        }).unwrap();
+        let entity = world.query_filtered::<Entity, With<Part>>().single(&world);
+        (world, entity)
+    }
+
+    fn extrude(world: &mut World, entity: Entity, distance: f32) {
+        world.run_system_once(move |mut commands: Commands, mut parts: Query<&mut Part>, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>| {
+            let mut part = parts.get_mut(entity).unwrap();
+            let params = ExtrusionParams { direction: Vec3::Y, distance };
+            extrude_faces(&mut part, &params, &mut commands, &mut meshes, &mut materials, entity);
+        }).unwrap();
+    }
+
+    #[test]
+    fn test_extruded_part_stays_closed() {
+        let (mut world, entity) = world_with_cube();
+        let mut part = world.get_mut::<Part>(entity).unwrap();
+        let top = part.faces.iter().find(|face| face.normal.y > 0.5).cloned().unwrap();
+        part.selected_faces = vec![top.clone()];
+
+        extrude(&mut world, entity, 2.0);
+
+        let part = world.get::<Part>(entity).unwrap();
+        // The top is replaced by a cap and four sides
+        assert_eq!(part.faces.len(), 6 - 1 + 5);
+        assert!(!part.faces.contains(&top));
+        // The extruded face no longer exists, so it is no longer selected
+        assert!(part.selected_faces.is_empty());
+        let properties = compute_mass_properties(part, 1.0).unwrap();
+        assert!((properties.volume - 3.0).abs() < 1e-4);
+        assert!((properties.centroid - Vec3::new(0.5, 1.5, 0.5)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_extrude_boolean_result() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<StandardMaterial>>();
+        let polygons = BooleanOp::Union.apply(&cube(Vec3::ZERO), &cube(Vec3::new(0.5, 0.0, 0.0)));
+        world.run_system_once(move |mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>| {
+            create_part_from_polygons(&mut commands, &mut meshes, &mut materials, &polygons);
+        }).unwrap();
+        let entity = world.query_filtered::<Entity, With<Part>>().single(&world);
+        let mut part = world.get_mut::<Part>(entity).unwrap();
+        let top = part.faces.iter().find(|face| face.normal.y > 0.5).cloned().unwrap();
+        let top_area = polygon_area(&top.vertices.iter().map(|v| v.coordinates).collect::<Vec<_>>());
+        part.selected_faces = vec![top];
+
+        extrude(&mut world, entity, 1.0);
+
+        let part = world.get::<Part>(entity).unwrap();
+        let properties = compute_mass_properties(part, 1.0).unwrap();
+        assert!((properties.volume - (1.5 + top_area)).abs() < 1e-4);
+        let face_count = part.faces.len();
+        // The new face meshes sit where the faces of the boolean result are
+        let mut faces = world.query::<(&Face, &Transform)>();
+        assert_eq!(faces.iter(&world).count(), face_count + 1);
+        assert!(faces.iter(&world).all(|(_, transform)| *transform == Transform::IDENTITY));
+    }
+}
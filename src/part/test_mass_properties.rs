@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use bevy::math::{Mat3, Vec3};
+    use super::super::components::{Face, Vertex};
+    use super::super::mass_properties::{compute_faces_mass_properties, MassPropertiesError};
+    use super::super::naming::{FeatureId, TopoKind, TopoName};
+
+    fn face(points: Vec<Vec3>, index: usize) -> Face {
+        let normal = (points[1] - points[0]).cross(points[2] - points[0]).normalize();
+        Face {
+            vertices: points.into_iter().map(Vertex::point_to_vertex).collect(),
+            edges: Vec::new(),
+            normal,
+            name: TopoName::primitive(FeatureId(1), TopoKind::Face, index),
+        }
+    }
+
+    fn box_faces(min: Vec3, max: Vec3) -> Vec<Face> {
+        let p = |x: bool, y: bool, z: bool| Vec3::new(
+            if x { max.x } else { min.x },
+            if y { max.y } else { min.y },
+            if z { max.z } else { min.z },
+        );
+        let corners = [
+            p(false, false, false), p(true, false, false), p(true, true, false), p(false, true, false),
+            p(false, false, true), p(true, false, true), p(true, true, true), p(false, true, true),
+        ];
+        // Same outward winding as the cube primitive
+        [[3, 2, 1, 0], [4, 5, 6, 7], [1, 2, 6, 5], [4, 7, 3, 0], [7, 6, 2, 3], [0, 1, 5, 4]]
+            .iter()
+            .enumerate()
+            .map(|(i, indices)| face(indices.iter().map(|&j| corners[j]).collect(), i))
+            .collect()
+    }
+
+    // Faceted cylinder standing on the XY plane with its axis along Z
+    fn cylinder_faces(radius: f32, height: f32, segments: usize) -> Vec<Face> {
+        let ring = |z: f32| -> Vec<Vec3> {
+            (0..segments)
+                .map(|i| {
+                    let angle = 2.0 * PI * i as f32 / segments as f32;
+                    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+                })
+                .collect()
+        };
+        let bottom = ring(0.0);
+        let top = ring(height);
+
+        let mut faces = vec![
+            face(bottom.iter().rev().copied().collect(), 0),
+            face(top.clone(), 1),
+        ];
+        for i in 0..segments {
+            let next = (i + 1) % segments;
+            faces.push(face(vec![bottom[i], bottom[next], top[next], top[i]], i + 2));
+        }
+        faces
+    }
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance * expected.abs().max(1.0),
+            "expected {} but got {}", expected, actual
+        );
+    }
+
+    // Tolerance is relative to the largest entry, off-diagonal zeros carry the same rounding error
+    fn assert_mat_close(actual: Mat3, expected: Mat3, tolerance: f32) {
+        let scale = expected.to_cols_array().iter().fold(1.0f32, |max, v| max.max(v.abs()));
+        for (a, e) in actual.to_cols_array().iter().zip(expected.to_cols_array()) {
+            assert!((a - e).abs() <= tolerance * scale, "expected {} but got {}", expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_box_mass_properties() {
+        let (w, h, d) = (2.0, 3.0, 4.0);
+        let min = Vec3::new(1.0, 2.0, 3.0);
+        let density = 2.5;
+        let props = compute_faces_mass_properties(&box_faces(min, min + Vec3::new(w, h, d)), density).unwrap();
+
+        let mass = w * h * d * density;
+        assert_close(props.volume, w * h * d, 1e-5);
+        assert_close(props.mass, mass, 1e-5);
+        assert_close(props.surface_area, 2.0 * (w * h + h * d + w * d), 1e-5);
+        assert_eq!(props.face_areas.len(), 6);
+        assert!((props.centroid - (min + Vec3::new(w, h, d) / 2.0)).length() < 1e-4);
+
+        let inertia_centroid = Mat3::from_diagonal(Vec3::new(
+            mass * (h * h + d * d) / 12.0,
+            mass * (w * w + d * d) / 12.0,
+            mass * (w * w + h * h) / 12.0,
+        ));
+        assert_mat_close(props.inertia_centroid, inertia_centroid, 1e-4);
+
+        // Parallel axis theorem
+        let c = props.centroid;
+        let shift = Mat3::IDENTITY * c.length_squared() - Mat3::from_cols(c * c.x, c * c.y, c * c.z);
+        assert_mat_close(props.inertia_origin, inertia_centroid + shift * mass, 1e-4);
+    }
+
+    #[test]
+    fn test_cylinder_mass_properties() {
+        let (r, h) = (1.5, 4.0);
+        let props = compute_faces_mass_properties(&cylinder_faces(r, h, 512), 1.0).unwrap();
+
+        let volume = PI * r * r * h;
+        assert_close(props.volume, volume, 1e-3);
+        assert_close(props.surface_area, 2.0 * PI * r * r + 2.0 * PI * r * h, 1e-3);
+        assert!((props.centroid - Vec3::new(0.0, 0.0, h / 2.0)).length() < 1e-3);
+
+        let radial = volume * (3.0 * r * r + h * h) / 12.0;
+        let axial = volume * r * r / 2.0;
+        assert_mat_close(props.inertia_centroid, Mat3::from_diagonal(Vec3::new(radial, radial, axial)), 1e-3);
+    }
+
+    #[test]
+    fn test_open_shell_is_rejected() {
+        let mut faces = box_faces(Vec3::ZERO, Vec3::ONE);
+        faces.pop();
+        assert_eq!(compute_faces_mass_properties(&faces, 1.0).unwrap_err(), MassPropertiesError::NotClosed);
+        assert_eq!(compute_faces_mass_properties(&[], 1.0).unwrap_err(), MassPropertiesError::NoFaces);
+    }
+}
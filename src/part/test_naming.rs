@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+    use super::super::components::{Face, Vertex};
+    use super::super::naming::{FeatureId, TopoKind, TopoName, TopoRole};
+
+    fn quad(name: TopoName, offset: Vec3) -> Face {
+        let vertices = [Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y]
+            .iter()
+            .map(|p| Vertex::point_to_vertex(*p + offset))
+            .collect();
+        Face {
+            vertices,
+            edges: Vec::new(),
+            normal: Vec3::Z,
+            name,
+        }
+    }
+
+    #[test]
+    fn test_generated_names_are_deterministic() {
+        let feature = FeatureId(7);
+        let base = TopoName::primitive(FeatureId(3), TopoKind::Face, 4);
+
+        let a = TopoName::generated(feature, TopoKind::Face, TopoRole::Cap, &[base]);
+        let b = TopoName::generated(feature, TopoKind::Face, TopoRole::Cap, &[base]);
+        assert_eq!(a, b);
+
+        // A different role, kind or feature must give a different name
+        assert_ne!(a, TopoName::generated(feature, TopoKind::Face, TopoRole::Side, &[base]));
+        assert_ne!(a, TopoName::generated(feature, TopoKind::Edge, TopoRole::Cap, &[base]));
+        assert_ne!(a, TopoName::generated(FeatureId(8), TopoKind::Face, TopoRole::Cap, &[base]));
+    }
+
+    #[test]
+    fn test_face_selection_survives_moving_geometry() {
+        let name = TopoName::primitive(FeatureId(1), TopoKind::Face, 0);
+        let selected = quad(name, Vec3::ZERO);
+        let moved = quad(name, Vec3::new(0.0, 0.0, 2.5));
+        assert_eq!(selected, moved);
+    }
+
+    #[test]
+    fn test_coincident_faces_from_different_features_differ() {
+        let a = quad(TopoName::primitive(FeatureId(1), TopoKind::Face, 0), Vec3::ZERO);
+        let b = quad(TopoName::primitive(FeatureId(2), TopoKind::Face, 0), Vec3::ZERO);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_copy_gets_names_of_its_own() {
+        let original = quad(TopoName::primitive(FeatureId(1), TopoKind::Face, 0), Vec3::ZERO);
+        let copy = original.copied(FeatureId(5));
+        assert_ne!(copy, original);
+        assert_eq!(copy.name.feature, FeatureId(5));
+        // Every copy is a feature of its own
+        assert_ne!(copy, original.copied(FeatureId(6)));
+        // Unnamed geometry stays unnamed
+        assert!(!Vertex::new(1.0, 0.0, 0.0).copied(FeatureId(5)).name.is_named());
+    }
+
+    #[test]
+    fn test_name_round_trips_through_string() {
+        let name = TopoName::generated(FeatureId(12), TopoKind::Edge, TopoRole::Lateral, &[]);
+        let parsed: TopoName = name.to_string().parse().unwrap();
+        assert_eq!(parsed, name);
+        assert!("F12:x00".parse::<TopoName>().is_err());
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+    use super::super::components::{Edge, Face, Vertex};
+    use super::super::selection::{SelectionOp, Topology};
+
+    fn face(points: &[Vec3]) -> Face {
+        let normal = (points[1] - points[0]).cross(points[2] - points[0]).normalize();
+        Face {
+            vertices: points.iter().copied().map(Vertex::point_to_vertex).collect(),
+            edges: Vec::new(),
+            normal,
+            name: Default::default(),
+        }
+    }
+
+    fn box_faces(min: Vec3, max: Vec3) -> Vec<Face> {
+        let p = |x: bool, y: bool, z: bool| Vec3::new(
+            if x { max.x } else { min.x },
+            if y { max.y } else { min.y },
+            if z { max.z } else { min.z },
+        );
+        let corners = [
+            p(false, false, false), p(true, false, false), p(true, true, false), p(false, true, false),
+            p(false, false, true), p(true, false, true), p(true, true, true), p(false, true, true),
+        ];
+        [[3, 2, 1, 0], [4, 5, 6, 7], [1, 2, 6, 5], [4, 7, 3, 0], [7, 6, 2, 3], [0, 1, 5, 4]]
+            .iter()
+            .map(|indices| face(&indices.map(|i| corners[i])))
+            .collect()
+    }
+
+    // Flat grid of unit quads in the XY plane
+    fn grid(columns: usize, rows: usize) -> Vec<Face> {
+        let p = |x: usize, y: usize| Vec3::new(x as f32, y as f32, 0.0);
+        (0..rows)
+            .flat_map(|y| (0..columns).map(move |x| face(&[p(x, y), p(x + 1, y), p(x + 1, y + 1), p(x, y + 1)])))
+            .collect()
+    }
+
+    fn edge(a: (f32, f32), b: (f32, f32)) -> Edge {
+        Edge::new(Vertex::new(a.0, a.1, 0.0), Vertex::new(b.0, b.1, 0.0))
+    }
+
+    #[test]
+    fn test_grow_and_shrink_faces() {
+        let faces = box_faces(Vec3::ZERO, Vec3::ONE);
+        let topology = Topology::new(&faces);
+        let grown = topology.select_faces(SelectionOp::Grow, &faces[..1]).unwrap();
+        // The bottom and the four sides around it
+        assert_eq!(grown.len(), 5);
+        assert!(!grown.contains(&faces[1]));
+        assert_eq!(topology.select_faces(SelectionOp::Shrink, &grown).unwrap(), vec![faces[0].clone()]);
+        // A closed selection has no border to shrink
+        assert_eq!(topology.select_faces(SelectionOp::Shrink, &faces).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_connected_stays_on_one_solid() {
+        let mut faces = box_faces(Vec3::ZERO, Vec3::ONE);
+        faces.extend(box_faces(Vec3::splat(3.0), Vec3::splat(4.0)));
+        let topology = Topology::new(&faces);
+        let connected = topology.select_faces(SelectionOp::Connected, &faces[7..8]).unwrap();
+        assert_eq!(connected, faces[6..].to_vec());
+    }
+
+    #[test]
+    fn test_coplanar_and_same_normal() {
+        let mut faces = grid(2, 1);
+        let raised = Vec3::Z;
+        faces.push(face(&[Vec3::new(5.0, 0.0, 0.0) + raised, Vec3::new(6.0, 0.0, 0.0) + raised, Vec3::new(6.0, 1.0, 0.0) + raised]));
+        let topology = Topology::new(&faces);
+        assert_eq!(topology.select_faces(SelectionOp::Coplanar, &faces[..1]).unwrap().len(), 2);
+        assert_eq!(topology.select_faces(SelectionOp::SameNormal, &faces[..1]).unwrap().len(), 3);
+        assert!(topology.select_faces(SelectionOp::EdgeLoop, &faces[..1]).is_none());
+    }
+
+    #[test]
+    fn test_invert_faces_and_edges() {
+        let faces = box_faces(Vec3::ZERO, Vec3::ONE);
+        let topology = Topology::new(&faces);
+        assert_eq!(topology.edges.len(), 12);
+        assert_eq!(topology.select_faces(SelectionOp::Invert, &faces[..2]).unwrap(), faces[2..].to_vec());
+        let edges = topology.select_edges(SelectionOp::Invert, &topology.edges[..3]).unwrap();
+        assert_eq!(edges, topology.edges[3..].to_vec());
+    }
+
+    #[test]
+    fn test_edge_loop_stops_at_the_border() {
+        let topology = Topology::new(&grid(3, 3));
+        let edges = topology.select_edges(SelectionOp::EdgeLoop, &[edge((1.0, 1.0), (2.0, 1.0))]).unwrap();
+        assert_eq!(edges.len(), 3);
+        for x in 0..3 {
+            assert!(edges.contains(&edge((x as f32, 1.0), (x as f32 + 1.0, 1.0))));
+        }
+    }
+
+    #[test]
+    fn test_edge_ring_crosses_the_quads() {
+        let topology = Topology::new(&grid(3, 3));
+        let edges = topology.select_edges(SelectionOp::EdgeRing, &[edge((1.0, 0.0), (1.0, 1.0))]).unwrap();
+        assert_eq!(edges.len(), 4);
+        for x in 0..4 {
+            assert!(edges.contains(&edge((x as f32, 0.0), (x as f32, 1.0))));
+        }
+        // Around a box the ring closes on itself
+        let topology = Topology::new(&box_faces(Vec3::ZERO, Vec3::ONE));
+        let ring = topology.select_edges(SelectionOp::EdgeRing, &topology.edges[..1]).unwrap();
+        assert_eq!(ring.len(), 4);
+    }
+
+    #[test]
+    fn test_op_names() {
+        for op in SelectionOp::ALL {
+            assert_eq!(SelectionOp::from_name(op.name()), Some(op));
+        }
+        assert_eq!(SelectionOp::from_name("LOOP"), Some(SelectionOp::EdgeLoop));
+        assert_eq!(SelectionOp::from_name("box"), None);
+    }
+}
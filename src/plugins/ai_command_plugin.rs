@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+
+use crate::ai::commands::{parse_color, target_matches, AiCommand, AiCommandQueue, PartRef, QueuedCommand};
+use crate::ai::conversation::{Conversation, Message};
+use crate::part::components::{ExtrusionParams, Face, FaceMaterial, Part, PartColor, PartGroup, PartId, PartSelection};
+use crate::part::primitives::CubePoints;
+use crate::part::{create_3d_object_system, create_part_from_polygons, extrude_faces, PartAssets};
+use crate::ui::output_console::OutputConsole;
+
+/// Runs the commands of AI answers against the document, one per frame
+pub struct AiCommandPlugin;
+
+impl Plugin for AiCommandPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AiCommandQueue>()
+            .add_systems(Update, (assign_part_ids, run_ai_commands).chain());
+    }
+}
+
+/// Numbers new parts, the numbers are never reused so the AI's references stay valid
+fn assign_part_ids(
+    mut next_id: Local<u32>,
+    mut commands: Commands,
+    parts: Query<Entity, (With<Part>, Without<PartId>)>,
+) {
+    for entity in &parts {
+        *next_id += 1;
+        commands.entity(entity).insert(PartId(*next_id));
+    }
+}
+
+type TargetParts<'a> = (
+    Entity,
+    &'a mut Part,
+    &'a mut Transform,
+    &'a Children,
+    Option<&'a Name>,
+    Option<&'a PartGroup>,
+    Option<&'a PartId>,
+    Has<PartSelection>,
+    Option<&'a PartColor>,
+);
+
+fn run_ai_commands(
+    mut queue: ResMut<AiCommandQueue>,
+    mut console: ResMut<OutputConsole>,
+    mut conversation: ResMut<Conversation>,
+    mut commands: Commands,
+    mut assets: PartAssets,
+    mut parts: Query<TargetParts>,
+    mut faces: Query<(Entity, &Face, &GlobalTransform, Option<&mut FaceMaterial>)>,
+) {
+    let Some(QueuedCommand { command, unit, call }) = queue.pop() else {
+        return;
+    };
+    let to_model = |v: [f32; 3]| Vec3::from(v.map(|c| unit.unit_to_model(c)));
+    let last = queue.last_created;
+    let resolve = |target: &str, parts: &Query<TargetParts>| -> Vec<Entity> {
+        parts.iter()
+            .filter(|(entity, _, _, _, name, group, id, selected, _)| {
+                let part = PartRef {
+                    id: id.map(|id| id.0),
+                    name: name.map(Name::as_str),
+                    group: group.map(|group| group.0.as_str()),
+                    selected: *selected,
+                    last: last == Some(*entity),
+                };
+                target_matches(target, &part)
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    };
+
+    let report = match command {
+        AiCommand::CreatePrimitive { position, size, name, relative_to, .. } => 'create: {
+            let mut origin = to_model(position);
+            if let Some(reference) = &relative_to {
+                let Some((.., children, _, _, _, _, _)) = resolve(reference, &parts).first().and_then(|entity| parts.get(*entity).ok()) else {
+                    break 'create no_match(reference);
+                };
+                let min = faces.iter_many(children)
+                    .flat_map(|(_, face, global, _)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)).collect::<Vec<_>>())
+                    .reduce(Vec3::min);
+                if let Some(min) = min {
+                    origin += min;
+                }
+            }
+            let size = to_model(size);
+            let points = CubePoints::get_points().into_iter().map(|point| point * size + origin).collect();
+            let entity = create_3d_object_system(&mut commands, &mut assets.meshes, &mut assets.materials, points);
+            if let Some(name) = &name {
+                commands.entity(entity).insert(Name::new(name.clone()));
+            }
+            queue.last_created = Some(entity);
+            format!("created {}", name.as_deref().unwrap_or("a box"))
+        }
+        AiCommand::Transform { target, translate, rotate } => {
+            let targets = resolve(&target, &parts);
+            for entity in &targets {
+                let Ok((_, _, mut transform, children, ..)) = parts.get_mut(*entity) else {
+                    continue;
+                };
+                if let Some(degrees) = rotate {
+                    // About the centre of the part rather than its origin
+                    let points: Vec<Vec3> = faces.iter_many(children)
+                        .flat_map(|(_, face, global, _)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)).collect::<Vec<_>>())
+                        .collect();
+                    let centre = points.iter().sum::<Vec3>() / points.len().max(1) as f32;
+                    let [x, y, z] = degrees.map(f32::to_radians);
+                    transform.rotate_around(centre, Quat::from_euler(EulerRot::XYZ, x, y, z));
+                }
+                if let Some(distance) = translate {
+                    transform.translation += to_model(distance);
+                }
+            }
+            targets_report("transformed", &target, targets.len())
+        }
+        AiCommand::ExtrudeFace { target, face: direction, distance } => {
+            let targets = resolve(&target, &parts);
+            for entity in &targets {
+                let Ok((_, mut part, transform, children, ..)) = parts.get_mut(*entity) else {
+                    continue;
+                };
+                // The face pointing most in the asked direction
+                let Some(face) = part.faces.iter()
+                    .max_by(|a, b| {
+                        let facing = |face: &Face| (transform.rotation * face.normal).dot(direction.vector());
+                        facing(a).total_cmp(&facing(b))
+                    })
+                    .cloned()
+                else {
+                    continue;
+                };
+                // The extruded face ends up inside the solid, like the toolbar extrusion
+                for (face_entity, ..) in faces.iter_many(children).filter(|(_, other, ..)| **other == face) {
+                    commands.entity(face_entity).despawn_recursive();
+                }
+                let selection = std::mem::replace(&mut part.selected_faces, vec![face.clone()]);
+                let params = ExtrusionParams { direction: face.normal, distance: unit.unit_to_model(distance) };
+                extrude_faces(&mut part, &params, &mut commands, &mut assets.meshes, &mut assets.materials, *entity);
+                part.selected_faces = selection.into_iter().filter(|selected| *selected != face).collect();
+            }
+            targets_report("extruded", &target, targets.len())
+        }
+        AiCommand::Boolean { operation, target, tool } => 'boolean: {
+            let targets = resolve(&target, &parts);
+            let tools: Vec<Entity> = resolve(&tool, &parts).into_iter().filter(|entity| !targets.contains(entity)).collect();
+            if targets.is_empty() {
+                break 'boolean no_match(&target);
+            }
+            if tools.is_empty() {
+                break 'boolean no_match(&tool);
+            }
+            let polygons = |entity: &Entity| -> Vec<Vec<Vec3>> {
+                let Ok((.., children, _, _, _, _, _)) = parts.get(*entity) else {
+                    return Vec::new();
+                };
+                faces.iter_many(children)
+                    .map(|(_, face, global, _)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)).collect())
+                    .collect()
+            };
+            let tool_polygons: Vec<_> = tools.iter().map(polygons).collect();
+            let mut combined = 0;
+            for entity in &targets {
+                let result = tool_polygons.iter().fold(polygons(entity), |result, tool| operation.apply(&result, tool));
+                if result.is_empty() {
+                    continue;
+                }
+                // The result takes the place of the target, the AI keeps referring to it by the same ID
+                let replacement = create_part_from_polygons(&mut commands, &mut assets.meshes, &mut assets.materials, &result);
+                if let Ok((_, _, _, _, name, group, id, selected, color)) = parts.get(*entity) {
+                    let mut replacement = commands.entity(replacement);
+                    if let Some(name) = name {
+                        replacement.insert(name.clone());
+                    }
+                    if let Some(group) = group {
+                        replacement.insert(group.clone());
+                    }
+                    if let Some(id) = id {
+                        replacement.insert(*id);
+                    }
+                    if let Some(color) = color {
+                        replacement.insert(*color);
+                    }
+                    if selected {
+                        replacement.insert(PartSelection);
+                    }
+                }
+                if queue.last_created == Some(*entity) {
+                    queue.last_created = Some(replacement);
+                }
+                commands.entity(*entity).despawn_recursive();
+                combined += 1;
+            }
+            if combined == 0 {
+                break 'boolean format!("the {} of {} and {} is empty, the parts were left unchanged", operation.name(), target, tool);
+            }
+            for entity in &tools {
+                commands.entity(*entity).despawn_recursive();
+                if queue.last_created == Some(*entity) {
+                    queue.last_created = None;
+                }
+            }
+            format!("made the {} of {} parts and {} tool parts, the tool parts were removed", operation.name(), combined, tools.len())
+        }
+        AiCommand::Delete { target } => {
+            let targets = resolve(&target, &parts);
+            for entity in &targets {
+                commands.entity(*entity).despawn_recursive();
+                if queue.last_created == Some(*entity) {
+                    queue.last_created = None;
+                }
+            }
+            targets_report("deleted", &target, targets.len())
+        }
+        AiCommand::SetColor { target, color } => 'color: {
+            let Some(color) = parse_color(&color) else {
+                break 'color format!("invalid colour '{}'", color);
+            };
+            let targets = resolve(&target, &parts);
+            let material = assets.materials.add(color);
+            for entity in &targets {
+                commands.entity(*entity).insert(PartColor(color));
+                let Ok((.., children, _, _, _, _, _)) = parts.get(*entity) else {
+                    continue;
+                };
+                // Shows right away, also in modes that leave the face materials alone
+                let mut children_faces = faces.iter_many_mut(children);
+                while let Some((.., face_material)) = children_faces.fetch_next() {
+                    if let Some(mut face_material) = face_material {
+                        face_material.0 = material.clone();
+                    }
+                }
+            }
+            targets_report("coloured", &target, targets.len())
+        }
+        AiCommand::Group { name, targets } => {
+            let mut count = 0;
+            for target in &targets {
+                for entity in resolve(target, &parts) {
+                    commands.entity(entity).insert(PartGroup(name.clone()));
+                    count += 1;
+                }
+            }
+            format!("grouped {} parts as {}", count, name)
+        }
+    };
+    console.add_log(format!("AI: {}", report));
+    match call {
+        Some(call) => conversation.push(Message::tool_result(call, report)),
+        None => conversation.record_action(report),
+    }
+}
+
+fn targets_report(done: &str, target: &str, count: usize) -> String {
+    if count == 0 {
+        no_match(target)
+    } else {
+        format!("{} {} parts", done, count)
+    }
+}
+
+fn no_match(target: &str) -> String {
+    format!("no part matches '{}'", target)
+}
@@ -0,0 +1,150 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::ai::commands::AiCommandQueue;
+use crate::ai::conversation::{Conversation, Message, Role};
+use crate::ai::preview::{ghost_boxes, preview_summary};
+use crate::ai::requests::{AiPrompt, AiRequests};
+use crate::ai::scene::SceneParts;
+use crate::tools::units::DocumentUnits;
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+
+/// Shows the parts an AI answer would create as translucent ghosts, and lets the user accept,
+/// reject or regenerate the answer before it changes the document
+pub struct AiPreviewPlugin;
+
+impl Plugin for AiPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (ai_preview_console_system, update_ghosts, ai_preview_window).chain());
+    }
+}
+
+/// Translucent stand-in for a part an answer would create
+#[derive(Component)]
+struct AiGhost;
+
+const GHOST_COLOR: Color = Color::srgba(0.3, 0.7, 1.0, 0.35);
+
+fn ai_preview_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut queue: ResMut<AiCommandQueue>,
+    mut requests: ResMut<AiRequests>,
+    mut conversation: ResMut<Conversation>,
+) {
+    for command in events.read().filter(|command| command.name == "ai") {
+        let args: Vec<&str> = command.args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["accept"] => match queue.accept() {
+                0 => console.add_log("No AI answer to accept"),
+                count => console.add_log(format!("Accepted {} AI commands", count)),
+            },
+            [op @ ("reject" | "regenerate")] => {
+                let rejected = queue.reject();
+                let Some(unit) = rejected.first().map(|queued| queued.unit) else {
+                    console.add_log(format!("No AI answer to {}", op));
+                    continue;
+                };
+                // The model hears why nothing happened and does not carry on with it
+                for queued in rejected {
+                    match queued.call {
+                        Some(call) => conversation.push(Message::tool_result(call, "rejected by the user, not run")),
+                        None => conversation.record_action("rejected by the user, not run"),
+                    }
+                }
+                requests.drop_follow_ups();
+                console.add_log("Rejected the AI answer");
+                if *op == "regenerate" {
+                    let Some(input) = conversation.messages().iter().rev().find(|message| message.role == Role::User).map(|message| message.text.clone()) else {
+                        continue;
+                    };
+                    console.add_log(format!("Asking again: {}", input));
+                    requests.push_front(AiPrompt { input, unit, follow_up: false });
+                }
+            }
+            ["preview", setting @ ("on" | "off")] => {
+                queue.review = *setting == "on";
+                if !queue.review && queue.accept() > 0 {
+                    console.add_log("Accepted the previewed AI answer");
+                }
+                console.add_log(format!("AI previews {}", setting));
+            }
+            ["preview"] => console.add_log(format!("AI previews {}", if queue.review { "on" } else { "off" })),
+            _ => {}
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct GhostAssets<'w, 's> {
+    commands: Commands<'w, 's>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+}
+
+/// Spawns the ghosts of an answer when it arrives and removes them once it is accepted or
+/// rejected
+fn update_ghosts(
+    mut shown: Local<bool>,
+    queue: Res<AiCommandQueue>,
+    mut console: ResMut<OutputConsole>,
+    mut assets: GhostAssets,
+    ghosts: Query<Entity, With<AiGhost>>,
+    scene: SceneParts,
+    units: Res<DocumentUnits>,
+) {
+    let pending = !queue.pending().is_empty();
+    if pending == *shown {
+        return;
+    }
+    *shown = pending;
+    for entity in &ghosts {
+        assets.commands.entity(entity).despawn_recursive();
+    }
+    if !pending {
+        return;
+    }
+
+    let material = assets.materials.add(StandardMaterial {
+        base_color: GHOST_COLOR,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    for ghost in ghost_boxes(queue.pending(), |target| scene.min_corner(target, queue.last_created)) {
+        assets.commands.spawn((
+            Mesh3d(assets.meshes.add(Cuboid::from_size(ghost.size))),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(ghost.min + ghost.size / 2.0),
+            PickingBehavior::IGNORE,
+            AiGhost,
+        ));
+    }
+    console.add_log(format!("AI preview: {}. Accept, reject or regenerate it.", preview_summary(queue.pending(), &units)));
+}
+
+fn ai_preview_window(
+    queue: Res<AiCommandQueue>,
+    units: Res<DocumentUnits>,
+    mut console_commands: EventWriter<ConsoleCommand>,
+    mut egui_contexts: EguiContexts,
+) {
+    if queue.pending().is_empty() {
+        return;
+    }
+    egui::Window::new("AI preview")
+        .resizable(false)
+        .collapsible(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(preview_summary(queue.pending(), &units));
+            ui.horizontal(|ui| {
+                for (label, command) in [("Accept", "/ai accept"), ("Reject", "/ai reject"), ("Regenerate", "/ai regenerate")] {
+                    if ui.button(label).clicked() {
+                        console_commands.send_batch(ConsoleCommand::parse(command));
+                    }
+                }
+            });
+        });
+}
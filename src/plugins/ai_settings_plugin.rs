@@ -0,0 +1,316 @@
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::ai::ai_client::AiClient;
+use crate::ai::conversation::{Conversation, Role};
+use crate::ai::credentials::{process_env, Credentials};
+use crate::ai::error::RetryPolicy;
+use crate::ai::llm_provider::{LlmConfig, ProviderKind};
+use crate::ai::requests::AiRequests;
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+
+/// Chooses the LLM backend the console sends its prompts to, with its model, base URL,
+/// temperature and timeout, from the Window menu, the AI settings dialog or the `/ai`
+/// command. API keys come from the settings dialog, the environment or the credentials file.
+/// Also starts, saves and loads the conversation the prompts are part of.
+pub struct AiSettingsPlugin;
+
+const DEFAULT_CONVERSATION_FILE: &str = "conversation.json";
+const DEFAULT_FIXTURE_FILE: &str = "ai_fixture.json";
+
+impl Plugin for AiSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        for provider in ProviderKind::ALL {
+            app.add_menu_item(Menu::Window, &format!("AI provider: {}", provider.label()), None, &format!("/ai provider {}", provider.name()));
+        }
+        app.add_menu_item(Menu::Window, "AI settings...", None, "/ai settings");
+        app.add_menu_item(Menu::Window, "AI: New conversation", None, "/ai new");
+        app.insert_resource(Credentials::load())
+            .init_resource::<AiSettingsDialog>()
+            .add_systems(Update, (
+                ai_console_system,
+                ai_settings_dialog,
+                sync_api_key,
+            ).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+struct AiSettingsDialog {
+    open: bool,
+    /// Key being typed, it only goes to the credentials when set
+    key: String,
+    remember: bool,
+}
+
+fn ai_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut client: ResMut<AiClient>,
+    mut dialog: ResMut<AiSettingsDialog>,
+    mut requests: ResMut<AiRequests>,
+    mut conversation: ResMut<Conversation>,
+    credentials: Res<Credentials>,
+) {
+    for command in events.read().filter(|command| command.name == "ai") {
+        let mut config = client.config().clone();
+        let args: Vec<&str> = command.args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            [] => {}
+            ["settings"] => {
+                dialog.open = true;
+                continue;
+            }
+            ["cancel"] => {
+                let dropped = requests.clear_queue();
+                match requests.cancel() {
+                    Some(prompt) => {
+                        conversation.discard_unanswered();
+                        console.add_log(format!("Cancelled '{}'", prompt.input));
+                    }
+                    None if dropped == 0 => console.add_log("No AI request in progress"),
+                    None => {}
+                }
+                if dropped > 0 {
+                    console.add_log(format!("Dropped {} queued prompts", dropped));
+                }
+                continue;
+            }
+            // Handled by the preview plugin
+            ["accept" | "reject" | "regenerate"] | ["preview", ..] => continue,
+            ["new"] => {
+                conversation.clear();
+                console.add_log("Started a new AI conversation");
+                continue;
+            }
+            ["history"] => {
+                if conversation.is_empty() {
+                    console.add_log("The AI conversation is empty");
+                }
+                for message in conversation.messages() {
+                    let speaker = match message.role {
+                        Role::User => "You",
+                        Role::Assistant => "AI",
+                        Role::Tool => "Result",
+                    };
+                    let mut text = message.content();
+                    if !message.tool_calls.is_empty() {
+                        let names: Vec<&str> = message.tool_calls.iter().map(|call| call.name.as_str()).collect();
+                        text = format!("{} (called {})", text, names.join(", ")).trim_start().to_string();
+                    }
+                    console.add_log(format!("{}: {}", speaker, text));
+                }
+                continue;
+            }
+            ["save", file @ ..] if file.len() <= 1 => {
+                let path = file.first().copied().unwrap_or(DEFAULT_CONVERSATION_FILE);
+                match conversation.save(Path::new(path)) {
+                    Ok(()) => console.add_log(format!("Saved the AI conversation to {}", path)),
+                    Err(error) => console.add_log(format!("Could not write {}: {}", path, error)),
+                }
+                continue;
+            }
+            ["load", file @ ..] if file.len() <= 1 => {
+                let path = file.first().copied().unwrap_or(DEFAULT_CONVERSATION_FILE);
+                match Conversation::load(Path::new(path)) {
+                    Ok((loaded, forgot)) => {
+                        *conversation = loaded;
+                        console.add_log(format!("Loaded {} AI messages from {}", conversation.messages().len(), path));
+                        if forgot {
+                            console.add_log("Its part IDs were numbered in an earlier session and were taken out");
+                        }
+                    }
+                    Err(error) => console.add_log(format!("Could not read {}: {}", path, error)),
+                }
+                continue;
+            }
+            ["record", "off"] => {
+                match client.recording() {
+                    Some(path) => console.add_log(format!("Stopped recording AI requests to {}", path.display())),
+                    None => console.add_log("AI requests are not being recorded"),
+                }
+                client.set_recording(None);
+                continue;
+            }
+            ["record", file @ ..] if file.len() <= 1 => {
+                let path = file.first().copied().unwrap_or(DEFAULT_FIXTURE_FILE);
+                client.set_recording(Some(path.into()));
+                console.add_log(format!("Recording AI requests and responses to {}, API keys are left out", path));
+                continue;
+            }
+            ["retries"] => {
+                console.add_log(format!("AI requests are retried up to {} times", client.retry_policy().max_retries));
+                continue;
+            }
+            ["retries", value] => {
+                match value.parse::<u32>() {
+                    Ok(max_retries) => {
+                        let retry = RetryPolicy { max_retries, ..client.retry_policy().clone() };
+                        client.set_retry_policy(retry);
+                        console.add_log(format!("AI requests are retried up to {} times", max_retries));
+                    }
+                    Err(_) => console.add_log(format!("Invalid retry count '{}', expected a whole number", value)),
+                }
+                continue;
+            }
+            ["provider", name] => match ProviderKind::from_name(name) {
+                Some(provider) => config = config.with_provider(provider),
+                None => {
+                    console.add_log(format!("Unknown provider '{}', use gemini, openai or ollama", name));
+                    continue;
+                }
+            },
+            ["tools", setting @ ("on" | "off")] => config.use_tools = *setting == "on",
+            ["model", model] => config.model = model.to_string(),
+            ["url", url] => config.base_url = url.to_string(),
+            ["temperature", value] => match value.parse::<f32>() {
+                Ok(temperature) if (0.0..=2.0).contains(&temperature) => config.temperature = temperature,
+                _ => {
+                    console.add_log(format!("Invalid temperature '{}', expected a number from 0 to 2", value));
+                    continue;
+                }
+            },
+            ["timeout", value] => match value.parse::<f32>() {
+                Ok(seconds) if seconds > 0.0 => config.timeout = Duration::from_secs_f32(seconds),
+                _ => {
+                    console.add_log(format!("Invalid timeout '{}', expected seconds", value));
+                    continue;
+                }
+            },
+            _ => {
+                console.add_log("Usage: /ai [settings|cancel|new|history|save [file]|load [file]|record [file|off]|retries [n]|provider <gemini|openai|ollama>|model <name>|url <base url>|temperature <t>|timeout <seconds>|tools <on|off>]");
+                continue;
+            }
+        }
+        console.add_log(format!(
+            "AI: {} model {} at {}, temperature {}, timeout {}s, tools {}, {}",
+            config.provider.label(),
+            config.model,
+            config.base_url,
+            config.temperature,
+            config.timeout.as_secs_f32(),
+            if config.use_tools { "on" } else { "off" },
+            key_status(&credentials, &config),
+        ));
+        if config != *client.config() {
+            client.set_config(config);
+        }
+    }
+}
+
+// Where the key comes from, never the key itself
+fn key_status(credentials: &Credentials, config: &LlmConfig) -> String {
+    match credentials.resolve(config.provider, process_env) {
+        Some((_, source)) => format!("key from {}", source),
+        None if config.requires_key() => "no API key".to_string(),
+        None => "no key needed".to_string(),
+    }
+}
+
+fn ai_settings_dialog(
+    mut dialog: ResMut<AiSettingsDialog>,
+    mut client: ResMut<AiClient>,
+    mut credentials: ResMut<Credentials>,
+    mut console: ResMut<OutputConsole>,
+    mut egui_contexts: EguiContexts,
+) {
+    if !dialog.open {
+        return;
+    }
+    let dialog = &mut *dialog;
+    let mut config = client.config().clone();
+    let status = key_status(&credentials, &config);
+    let mut open = true;
+    let mut set_key = false;
+
+    egui::Window::new("AI settings")
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("ai_settings_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Provider");
+                egui::ComboBox::from_id_salt("ai_provider")
+                    .selected_text(config.provider.label())
+                    .show_ui(ui, |ui| {
+                        for provider in ProviderKind::ALL {
+                            if ui.selectable_label(config.provider == provider, provider.label()).clicked() && config.provider != provider {
+                                config = config.with_provider(provider);
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Model");
+                ui.text_edit_singleline(&mut config.model);
+                ui.end_row();
+
+                ui.label("Base URL");
+                ui.text_edit_singleline(&mut config.base_url);
+                ui.end_row();
+
+                ui.label("Temperature");
+                ui.add(egui::Slider::new(&mut config.temperature, 0.0..=2.0));
+                ui.end_row();
+
+                ui.label("Timeout");
+                let mut seconds = config.timeout.as_secs_f32();
+                if ui.add(egui::DragValue::new(&mut seconds).range(1.0..=600.0).suffix(" s")).changed() {
+                    config.timeout = Duration::from_secs_f32(seconds);
+                }
+                ui.end_row();
+
+                ui.label("Function calling");
+                ui.checkbox(&mut config.use_tools, "").on_hover_text("Off for models without tool support, they answer in JSON text");
+                ui.end_row();
+
+                ui.label("API key");
+                ui.add(egui::TextEdit::singleline(&mut dialog.key).password(true).hint_text("leave empty to remove"));
+                ui.end_row();
+            });
+            ui.label(format!("Using: {}", status));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut dialog.remember, "Remember in the credentials file");
+                set_key = ui.button("Set key").clicked();
+            });
+        });
+
+    if set_key {
+        let removed = dialog.key.trim().is_empty();
+        credentials.set_key(config.provider, &dialog.key, dialog.remember);
+        dialog.key.clear();
+        if removed {
+            console.add_log(format!("API key for {} removed", config.provider.label()));
+        } else {
+            console.add_log(format!("API key for {} set", config.provider.label()));
+        }
+        if dialog.remember || removed {
+            if let Err(err) = credentials.save() {
+                console.add_log(format!("Could not save the credentials: {}", err));
+            }
+        }
+    }
+    if config != *client.config() {
+        client.set_config(config);
+    }
+    dialog.open = open;
+}
+
+// The client holds the key of the current provider only
+fn sync_api_key(
+    credentials: Res<Credentials>,
+    mut client: ResMut<AiClient>,
+    mut provider: Local<Option<ProviderKind>>,
+) {
+    let current = client.config().provider;
+    if !credentials.is_changed() && *provider == Some(current) {
+        return;
+    }
+    *provider = Some(current);
+    let key = credentials.resolve(current, process_env).map(|(key, _)| key).unwrap_or_default();
+    client.set_api_key(key);
+}
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::{mesh::PrimitiveTopology, view::RenderLayers},
+};
+
+use crate::part::components::{Face, FaceMaterial};
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::view::display_mode::{
+    visible_edges, DisplayMode, EdgeFace, Eye, ViewportIndex, EDGE_LAYER_START, HIDDEN_LINE_LAYER, SHADED_LAYER, XRAY_LAYER,
+};
+use crate::view::section::{section_material, SectionMaterial, SectionPlanes};
+use crate::view::viewports::ActiveViewport;
+
+/// Display modes per viewport: shaded, shaded with edges, wireframe, hidden line and X-ray.
+/// Each face gets a child entity per look on its own render layer and every viewport camera
+/// renders the layers of its mode, the face entity itself stays invisible for picking. Edge
+/// lines come from the faces' edge data and are built per viewport so silhouettes match its view.
+/// Everything drawn uses the section material so section planes clip it.
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        for mode in DisplayMode::ALL {
+            app.add_menu_item(Menu::View, &format!("Display: {}", mode.label()), None, &format!("/display {}", mode.name()));
+        }
+        app.add_plugins(MaterialPlugin::<SectionMaterial>::default())
+            .init_resource::<SectionPlanes>()
+            .init_resource::<DisplayMaterials>()
+            .add_systems(Update, (
+                display_console_system,
+                apply_display_mode,
+                attach_display_proxies,
+                sync_display_proxies,
+                update_viewport_edges,
+            ).chain());
+    }
+}
+
+#[derive(Resource)]
+struct DisplayMaterials {
+    /// Fully transparent, keeps face entities pickable without drawing them
+    pick: Handle<StandardMaterial>,
+    hidden_line: Handle<SectionMaterial>,
+    xray: Handle<SectionMaterial>,
+    edge: Handle<SectionMaterial>,
+}
+
+impl FromWorld for DisplayMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let background = world.resource::<ClearColor>().0;
+        let planes = world.resource::<SectionPlanes>().clone();
+        let pick = world.resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color: Color::NONE,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        let mut materials = world.resource_mut::<Assets<SectionMaterial>>();
+        let mut add = |base| materials.add(section_material(base, &planes));
+        DisplayMaterials {
+            pick,
+            hidden_line: add(StandardMaterial {
+                base_color: background,
+                unlit: true,
+                ..default()
+            }),
+            xray: add(StandardMaterial {
+                base_color: Color::srgba(0.6, 0.75, 0.9, 0.25),
+                alpha_mode: AlphaMode::Blend,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            }),
+            edge: add(StandardMaterial {
+                base_color: Color::srgb(0.08, 0.08, 0.1),
+                unlit: true,
+                // Lines lie on the faces, pull them forward so they win the depth test
+                depth_bias: 100.0,
+                ..default()
+            }),
+        }
+    }
+}
+
+/// Child of a face entity drawing it in one of the display modes
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum FaceProxy {
+    Shaded,
+    HiddenLine,
+    XRay,
+}
+
+impl FaceProxy {
+    const ALL: [FaceProxy; 3] = [FaceProxy::Shaded, FaceProxy::HiddenLine, FaceProxy::XRay];
+
+    fn layer(&self) -> usize {
+        match self {
+            FaceProxy::Shaded => SHADED_LAYER,
+            FaceProxy::HiddenLine => HIDDEN_LINE_LAYER,
+            FaceProxy::XRay => XRAY_LAYER,
+        }
+    }
+}
+
+/// Edge lines of the parts as seen by one viewport camera
+#[derive(Component)]
+struct ViewportEdges {
+    camera: Entity,
+    lines: Vec<[Vec3; 2]>,
+}
+
+fn display_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut cameras: Query<&mut DisplayMode, With<ActiveViewport>>,
+) {
+    for command in events.read().filter(|command| command.name == "display") {
+        match command.args.first() {
+            Some(name) => match DisplayMode::from_name(name) {
+                Some(mode) => {
+                    for mut display_mode in cameras.iter_mut() {
+                        *display_mode = mode;
+                    }
+                    console.add_log(format!("Display: {}", mode.label()));
+                }
+                None => console.add_log(format!("Unknown display mode '{}'", name)),
+            },
+            None => {
+                for mode in cameras.iter() {
+                    console.add_log(format!("Display: {}", mode.label()));
+                }
+            }
+        }
+    }
+}
+
+fn apply_display_mode(
+    mut commands: Commands,
+    cameras: Query<(Entity, &DisplayMode, Option<&ViewportIndex>), Changed<DisplayMode>>,
+) {
+    for (entity, mode, viewport) in cameras.iter() {
+        commands.entity(entity).insert(mode.render_layers(viewport.map_or(0, |viewport| viewport.0)));
+    }
+}
+
+// New faces hand their material over to the proxies and become invisible
+fn attach_display_proxies(
+    mut commands: Commands,
+    faces: Query<(Entity, &Mesh3d, &MeshMaterial3d<StandardMaterial>), Added<Face>>,
+    materials: Res<DisplayMaterials>,
+) {
+    for (entity, mesh, material) in faces.iter() {
+        commands.entity(entity)
+            .insert((FaceMaterial(material.0.clone()), MeshMaterial3d(materials.pick.clone())))
+            .with_children(|children| {
+                for proxy in FaceProxy::ALL {
+                    // The shaded look follows the face material, set by `sync_display_proxies`
+                    let material = match proxy {
+                        FaceProxy::Shaded | FaceProxy::HiddenLine => materials.hidden_line.clone(),
+                        FaceProxy::XRay => materials.xray.clone(),
+                    };
+                    children.spawn((
+                        Mesh3d(mesh.0.clone()),
+                        MeshMaterial3d(material),
+                        RenderLayers::layer(proxy.layer()),
+                        PickingBehavior::IGNORE,
+                        proxy,
+                    ));
+                }
+            });
+    }
+}
+
+type ChangedFaces = (With<Face>, Or<(Changed<FaceMaterial>, Changed<Mesh3d>)>);
+
+fn sync_display_proxies(
+    faces: Query<(&FaceMaterial, &Mesh3d, &Children), ChangedFaces>,
+    mut proxies: Query<(&FaceProxy, &mut Mesh3d, &mut MeshMaterial3d<SectionMaterial>), Without<Face>>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut section_materials: ResMut<Assets<SectionMaterial>>,
+    planes: Res<SectionPlanes>,
+    // Clipping version of each face material
+    mut clipped: Local<HashMap<AssetId<StandardMaterial>, Handle<SectionMaterial>>>,
+) {
+    for (face_material, face_mesh, children) in faces.iter() {
+        let Some(shaded) = clipped.get(&face_material.0.id()).cloned().or_else(|| {
+            let base = standard_materials.get(&face_material.0)?.clone();
+            let handle = section_materials.add(section_material(base, &planes));
+            clipped.insert(face_material.0.id(), handle.clone());
+            Some(handle)
+        }) else {
+            continue;
+        };
+        let mut iter = proxies.iter_many_mut(children);
+        while let Some((proxy, mut mesh, mut material)) = iter.fetch_next() {
+            if mesh.0 != face_mesh.0 {
+                mesh.0 = face_mesh.0.clone();
+            }
+            if *proxy == FaceProxy::Shaded && material.0 != shaded {
+                material.0 = shaded.clone();
+            }
+        }
+    }
+}
+
+fn update_viewport_edges(
+    mut commands: Commands,
+    cameras: Query<(Entity, &DisplayMode, Option<&ViewportIndex>, &GlobalTransform, &Projection)>,
+    faces: Query<(&Face, &GlobalTransform)>,
+    mut edges_q: Query<(Entity, &mut ViewportEdges, &Mesh3d, &mut Visibility, &mut RenderLayers)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<DisplayMaterials>,
+) {
+    for (entity, edges, _, _, _) in edges_q.iter() {
+        if !cameras.contains(edges.camera) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let edge_faces: Vec<EdgeFace> = faces.iter().map(|(face, transform)| world_edge_face(face, transform)).collect();
+
+    for (camera, mode, viewport, transform, projection) in cameras.iter() {
+        let lines = if mode.shows_edges() {
+            let eye = match projection {
+                Projection::Orthographic(_) => Eye::Direction(transform.forward().into()),
+                _ => Eye::Position(transform.translation()),
+            };
+            visible_edges(&edge_faces, eye)
+        } else {
+            Vec::new()
+        };
+        let layers = RenderLayers::layer(EDGE_LAYER_START + viewport.map_or(0, |viewport| viewport.0));
+
+        match edges_q.iter_mut().find(|(_, edges, _, _, _)| edges.camera == camera) {
+            Some((_, mut edges, mesh, mut visibility, mut render_layers)) => {
+                if *render_layers != layers {
+                    *render_layers = layers;
+                }
+                if edges.lines == lines {
+                    continue;
+                }
+                *visibility = if lines.is_empty() { Visibility::Hidden } else { Visibility::Inherited };
+                if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                    *mesh = line_mesh(&lines);
+                }
+                edges.lines = lines;
+            }
+            None => {
+                commands.spawn((
+                    Mesh3d(meshes.add(line_mesh(&lines))),
+                    MeshMaterial3d(materials.edge.clone()),
+                    if lines.is_empty() { Visibility::Hidden } else { Visibility::Inherited },
+                    layers,
+                    PickingBehavior::IGNORE,
+                    ViewportEdges { camera, lines },
+                ));
+            }
+        }
+    }
+}
+
+fn world_edge_face(face: &Face, transform: &GlobalTransform) -> EdgeFace {
+    let points: Vec<Vec3> = face.vertices.iter().map(|v| transform.transform_point(v.coordinates)).collect();
+    let edges = if face.edges.is_empty() {
+        // No edge data, walk the outline
+        (0..points.len()).map(|i| [points[i], points[(i + 1) % points.len()]]).collect()
+    } else {
+        face.edges.iter()
+            .map(|edge| [transform.transform_point(edge.start.coordinates), transform.transform_point(edge.end.coordinates)])
+            .collect()
+    };
+    EdgeFace {
+        edges,
+        normal: transform.affine().transform_vector3(face.normal),
+        center: points.iter().sum::<Vec3>() / points.len().max(1) as f32,
+    }
+}
+
+fn line_mesh(lines: &[[Vec3; 2]]) -> Mesh {
+    // An empty vertex buffer cannot be drawn, hidden meshes keep a degenerate line instead
+    let positions: Vec<Vec3> = if lines.is_empty() {
+        vec![Vec3::ZERO, Vec3::ZERO]
+    } else {
+        lines.iter().flatten().copied().collect()
+    };
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+}
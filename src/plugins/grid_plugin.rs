@@ -0,0 +1,247 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::part::components::{Face, Part};
+use crate::tools::grid::{GridLevel, GridPlane, GridSettings, WorkPlane};
+use crate::tools::units::DocumentUnits;
+use crate::ui::document_settings::length_input;
+use crate::view::viewports::{viewport_origin, ActiveViewport};
+use crate::view::PanOrbitCamera;
+
+/// Viewport grid on a work plane. The spacing follows the zoom level with major and minor
+/// lines, minor lines fade out before the next level takes over and lines fade towards the
+/// edge. Origin lines are coloured by world axis and major lines are labelled in document units.
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridSettings>()
+            .init_resource::<GridState>()
+            .add_systems(Update, (
+                update_grid_state,
+                draw_grid,
+                grid_labels_ui,
+                grid_settings_ui,
+            ).chain());
+    }
+}
+
+/// Work plane and spacing of the current frame, also used for snapping
+#[derive(Resource)]
+pub struct GridState {
+    pub plane: WorkPlane,
+    pub level: GridLevel,
+    /// Grid centre in plane coordinates, follows the camera focus in major steps
+    pub center: Vec2,
+}
+
+impl Default for GridState {
+    fn default() -> Self {
+        let settings = GridSettings::default();
+        GridState {
+            plane: WorkPlane::XZ,
+            level: settings.level(0.0),
+            center: Vec2::ZERO,
+        }
+    }
+}
+
+pub fn update_grid_state(
+    settings: Res<GridSettings>,
+    camera_q: Query<(&PanOrbitCamera, &GlobalTransform), With<ActiveViewport>>,
+    faces: Query<(&Face, &GlobalTransform, &Parent)>,
+    parts: Query<&Part>,
+    mut state: ResMut<GridState>,
+) {
+    state.plane = match settings.plane {
+        GridPlane::XZ => WorkPlane::XZ,
+        GridPlane::XY => WorkPlane::XY,
+        GridPlane::YZ => WorkPlane::YZ,
+        GridPlane::SelectedFace => selected_face_plane(&faces, &parts).unwrap_or(WorkPlane::XZ),
+    };
+
+    let Ok((pan_orbit, camera)) = camera_q.get_single() else {
+        return;
+    };
+    // Distance to the plane rather than to the focus, so looking along the plane does not zoom the grid
+    let distance = (camera.translation() - state.plane.origin).dot(state.plane.normal).abs()
+        .max(pan_orbit.radius * 0.25);
+    state.level = settings.level(distance);
+    state.center = (state.plane.to_local(pan_orbit.focus) / state.level.major).round() * state.level.major;
+}
+
+// Plane of the first selected face with the u axis along its first edge
+fn selected_face_plane(
+    faces: &Query<(&Face, &GlobalTransform, &Parent)>,
+    parts: &Query<&Part>,
+) -> Option<WorkPlane> {
+    let (face, transform, _) = faces.iter().find(|(face, _, parent)| {
+        parts.get(parent.get()).is_ok_and(|part| part.selected_faces.first() == Some(*face))
+    })?;
+    let points: Vec<Vec3> = face.vertices.iter().map(|v| transform.transform_point(v.coordinates)).collect();
+    let center = points.iter().sum::<Vec3>() / points.len().max(1) as f32;
+    let direction = points.get(1).zip(points.first()).map_or(Vec3::X, |(b, a)| *b - *a);
+    Some(WorkPlane::new(center, transform.rotation() * face.normal, direction))
+}
+
+fn draw_grid(settings: Res<GridSettings>, state: Res<GridState>, mut gizmos: Gizmos) {
+    if !settings.visible {
+        return;
+    }
+    let GridState { plane, level, center } = *state;
+    let half = level.major * settings.extent as f32;
+    let steps = (half / level.minor).round() as i32;
+    let major_every = settings.major_every.max(2) as i32;
+
+    let minor = settings.minor_color.with_alpha(settings.minor_color.alpha() * level.minor_alpha);
+    for i in -steps..=steps {
+        let offset = i as f32 * level.minor;
+        // The centre sits on a major line in both directions
+        let is_major = i.rem_euclid(major_every) == 0;
+        let color = if is_major { settings.major_color } else { minor };
+        if !is_major && level.minor_alpha < 0.01 {
+            continue;
+        }
+        // Lines along v at a fixed u, and along u at a fixed v
+        faded_line(&mut gizmos, plane, center + Vec2::new(offset, -half), center + Vec2::new(offset, half), color);
+        faded_line(&mut gizmos, plane, center + Vec2::new(-half, offset), center + Vec2::new(half, offset), color);
+    }
+
+    if settings.show_axes {
+        // Origin lines are drawn across the whole grid when they pass through it
+        if center.x.abs() <= half {
+            gizmos.line(plane.to_world(Vec2::new(0.0, center.y - half)), plane.to_world(Vec2::new(0.0, center.y + half)), axis_color(plane.v));
+        }
+        if center.y.abs() <= half {
+            gizmos.line(plane.to_world(Vec2::new(center.x - half, 0.0)), plane.to_world(Vec2::new(center.x + half, 0.0)), axis_color(plane.u));
+        }
+    }
+}
+
+// Line that fades from full colour in its middle to transparent at both ends
+fn faded_line(gizmos: &mut Gizmos, plane: WorkPlane, start: Vec2, end: Vec2, color: Color) {
+    let middle = plane.to_world(start.lerp(end, 0.5));
+    let transparent = color.with_alpha(0.0);
+    gizmos.line_gradient(middle, plane.to_world(start), color, transparent);
+    gizmos.line_gradient(middle, plane.to_world(end), color, transparent);
+}
+
+// World axis colour of the axis a direction is closest to
+fn axis_color(direction: Vec3) -> Color {
+    let d = direction.abs();
+    if d.x >= d.y && d.x >= d.z {
+        RED_500.into()
+    } else if d.y >= d.z {
+        GREEN_500.into()
+    } else {
+        BLUE_500.into()
+    }
+}
+
+// Distances along the origin lines at every major line
+fn grid_labels_ui(
+    settings: Res<GridSettings>,
+    state: Res<GridState>,
+    units: Res<DocumentUnits>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<ActiveViewport>>,
+    mut egui_contexts: EguiContexts,
+) {
+    if !settings.visible || !settings.show_labels {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_q.get_single() else {
+        return;
+    };
+    let GridState { plane, level, center } = *state;
+    let mut painter = egui_contexts.ctx_mut().layer_painter(egui::LayerId::background());
+    // Labels stay inside the active viewport
+    if let Some(rect) = camera.logical_viewport_rect() {
+        painter.set_clip_rect(egui::Rect::from_min_max(egui::pos2(rect.min.x, rect.min.y), egui::pos2(rect.max.x, rect.max.y)));
+    }
+    let origin = viewport_origin(camera);
+    let count = settings.extent as i32;
+
+    for axis in [Vec2::X, Vec2::Y] {
+        let start = (center.dot(axis) / level.major).round() as i32;
+        for i in (start - count)..=(start + count) {
+            if i == 0 {
+                continue;
+            }
+            let distance = i as f32 * level.major;
+            let Ok(position) = camera.world_to_viewport(camera_transform, plane.to_world(axis * distance)) else {
+                continue;
+            };
+            let position = position + origin;
+            painter.text(
+                egui::pos2(position.x + 3.0, position.y - 3.0),
+                egui::Align2::LEFT_BOTTOM,
+                units.format_length(distance),
+                egui::FontId::proportional(11.0),
+                egui::Color32::from_gray(170),
+            );
+        }
+    }
+}
+
+fn grid_settings_ui(
+    mut egui_contexts: EguiContexts,
+    mut settings: ResMut<GridSettings>,
+    units: Res<DocumentUnits>,
+) {
+    egui::Window::new("Grid")
+        .resizable(false)
+        .collapsible(true)
+        .default_open(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            // Copy so the resource is only marked changed on an actual edit
+            let mut edited = settings.clone();
+            ui.checkbox(&mut edited.visible, "Show grid");
+            egui::Grid::new("grid_settings_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Plane");
+                egui::ComboBox::from_id_salt("grid_plane")
+                    .selected_text(edited.plane.label())
+                    .show_ui(ui, |ui| {
+                        for plane in GridPlane::ALL {
+                            ui.selectable_value(&mut edited.plane, plane, plane.label());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Spacing");
+                length_input(ui, "grid_spacing", &mut edited.spacing, &units);
+                ui.end_row();
+
+                ui.label("Major every");
+                ui.add(egui::Slider::new(&mut edited.major_every, 2..=10).suffix(" lines"));
+                ui.end_row();
+
+                ui.label("Extent");
+                ui.add(egui::Slider::new(&mut edited.extent, 1..=20).suffix(" major cells"));
+                ui.end_row();
+
+                ui.label("Minor colour");
+                color_edit(ui, &mut edited.minor_color);
+                ui.end_row();
+
+                ui.label("Major colour");
+                color_edit(ui, &mut edited.major_color);
+                ui.end_row();
+            });
+            ui.checkbox(&mut edited.adaptive, "Adapt spacing to zoom");
+            ui.checkbox(&mut edited.show_axes, "Origin axes");
+            ui.checkbox(&mut edited.show_labels, "Labels");
+
+            if edited.spacing > 0.0 && edited != *settings {
+                *settings = edited;
+            }
+        });
+}
+
+fn color_edit(ui: &mut egui::Ui, color: &mut Color) {
+    let [r, g, b, a] = color.to_srgba().to_u8_array();
+    let mut edited = egui::Color32::from_rgba_unmultiplied(r, g, b, a);
+    if ui.color_edit_button_srgba(&mut edited).changed() {
+        let [r, g, b, a] = edited.to_srgba_unmultiplied();
+        *color = Color::srgba_u8(r, g, b, a);
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::{color::palettes::tailwind::*, input::common_conditions::input_just_pressed, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::part::components::Face;
+use crate::tools::measure::{measure_pair, measure_radius, measure_single, MeasurePick, Measurement};
+use crate::tools::snapping::SnapKind;
+use crate::tools::units::DocumentUnits;
+use crate::ui::{output_console::OutputConsole, EditorMode};
+use crate::view::viewports::{viewport_origin, ActiveViewport};
+use super::snap_plugin::{update_snap, SnapState};
+
+// Pick radius for vertices and edges as a fraction of the camera distance
+const PICK_TOLERANCE: f32 = 0.02;
+
+/// Measure mode: click two vertices, edges, faces or a mix of them (or a single edge)
+/// to get distances, angles and lengths, or three vertices for the radius of the circle
+/// through them. Results are drawn as dimensions and logged to the console.
+pub struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeasureState>()
+            .add_systems(Update, (
+                clear_measurement.run_if(resource_changed::<EditorMode>.or(input_just_pressed(KeyCode::Escape))),
+                handle_measure_clicks.after(update_snap).run_if(resource_equals(EditorMode::Measure)),
+                draw_measure_gizmos,
+                measure_label_ui,
+            ).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MeasureState {
+    pub picks: Vec<MeasurePick>,
+    pub result: Option<Measurement>,
+}
+
+fn clear_measurement(mut state: ResMut<MeasureState>, mut snap: ResMut<SnapState>) {
+    *state = MeasureState::default();
+    snap.reference = None;
+}
+
+fn handle_measure_clicks(
+    mouse: Res<ButtonInput<MouseButton>>,
+    units: Res<DocumentUnits>,
+    faces: Query<(&Face, &GlobalTransform)>,
+    camera_q: Query<&GlobalTransform, With<ActiveViewport>>,
+    mut snap: ResMut<SnapState>,
+    mut state: ResMut<MeasureState>,
+    mut console: ResMut<OutputConsole>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = snap.cursor else {
+        return;
+    };
+
+    // Snapped points are measured as points, otherwise the hovered edge or face is measured
+    let hovered = snap.hovered_face.and_then(|entity| faces.get(entity).ok());
+    let pick = match (cursor.kind, hovered) {
+        (Some(SnapKind::Vertex | SnapKind::Midpoint), _) | (_, None) => MeasurePick::Vertex(cursor.point),
+        (_, Some((face, transform))) => {
+            let camera_distance = camera_q.get_single().map(|camera| camera.translation().distance(cursor.point)).unwrap_or(1.0);
+            classify_pick(face, transform, cursor.point, camera_distance * PICK_TOLERANCE)
+        }
+    };
+
+    // A third vertex after two measures the radius through them, any other click after a
+    // finished measurement starts a new one
+    let is_vertex = |pick: &MeasurePick| matches!(pick, MeasurePick::Vertex(_));
+    let radius_pick = state.picks.len() == 2 && is_vertex(&pick) && state.picks.iter().all(is_vertex);
+    if state.picks.len() >= 2 && !radius_pick {
+        state.picks.clear();
+        state.result = None;
+    }
+    state.picks.push(pick);
+    snap.reference = (state.picks.len() == 1).then_some(cursor.point);
+
+    let result = match state.picks.as_slice() {
+        [single] => measure_single(single),
+        [a, b] => Some(measure_pair(a, b)),
+        [a, b, c] => measure_radius(a, b, c),
+        _ => None,
+    };
+    let picked: Vec<&str> = state.picks.iter().map(|pick| pick.label()).collect();
+    match &result {
+        Some(measurement) => console.add_log(format!("Measure {}: {}", picked.join(" - "), describe(measurement, &units))),
+        None if state.picks.len() == 3 => console.add_log("Measure: the three vertices are in a line, no radius"),
+        None => {}
+    }
+    state.result = result;
+}
+
+// Snaps the clicked point to a vertex or edge of the face when it is close enough
+fn classify_pick(face: &Face, transform: &GlobalTransform, point: Vec3, tolerance: f32) -> MeasurePick {
+    let vertices: Vec<Vec3> = face.vertices.iter().map(|v| transform.transform_point(v.coordinates)).collect();
+
+    if let Some(vertex) = vertices.iter().find(|v| v.distance(point) < tolerance) {
+        return MeasurePick::Vertex(*vertex);
+    }
+    for i in 0..vertices.len() {
+        let (start, end) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+        if crate::tools::measure::closest_on_segment(point, start, end).distance(point) < tolerance {
+            return MeasurePick::Edge { start, end };
+        }
+    }
+    MeasurePick::Face {
+        point,
+        normal: transform.rotation() * face.normal,
+        vertices,
+    }
+}
+
+fn describe(measurement: &Measurement, units: &DocumentUnits) -> String {
+    if let Some(radius) = measurement.radius {
+        return format!("radius {} (diameter {})", units.format_length(radius), units.format_length(2.0 * radius));
+    }
+    if let Some(length) = measurement.length {
+        return format!("length {}", units.format_length(length));
+    }
+    let delta = measurement.delta();
+    let mut text = format!(
+        "distance {} (dx {}, dy {}, dz {})",
+        units.format_length(measurement.distance),
+        units.format_length(delta.x.abs()),
+        units.format_length(delta.y.abs()),
+        units.format_length(delta.z.abs())
+    );
+    if let Some(angle) = measurement.angle {
+        text.push_str(&format!(", angle {:.2}°", angle.to_degrees()));
+    }
+    text
+}
+
+fn draw_measure_gizmos(state: Res<MeasureState>, mut gizmos: Gizmos) {
+    for pick in &state.picks {
+        match pick {
+            MeasurePick::Vertex(point) => {
+                gizmos.sphere(*point, 0.04, AMBER_400);
+            }
+            MeasurePick::Edge { start, end } => {
+                gizmos.line(*start, *end, AMBER_400);
+            }
+            MeasurePick::Face { point, vertices, .. } => {
+                let mut outline = vertices.clone();
+                outline.extend(vertices.first());
+                gizmos.linestrip(outline, AMBER_400);
+                gizmos.sphere(*point, 0.02, AMBER_400);
+            }
+        }
+    }
+
+    let Some(measurement) = &state.result else {
+        return;
+    };
+    if measurement.distance < f32::EPSILON {
+        return;
+    }
+
+    // The circle and a radius line from its centre
+    if let (Some(radius), [MeasurePick::Vertex(a), MeasurePick::Vertex(b), MeasurePick::Vertex(c)]) = (measurement.radius, state.picks.as_slice()) {
+        let normal = (*b - *a).cross(*c - *a).normalize();
+        gizmos.circle(Isometry3d::new(measurement.from, Quat::from_rotation_arc(Vec3::Z, normal)), radius, EMERALD_400);
+        gizmos.sphere(measurement.from, 0.02, EMERALD_400);
+        gizmos.arrow(measurement.from, measurement.to, EMERALD_400);
+        return;
+    }
+
+    // Dimension line with arrow heads at both ends
+    let middle = measurement.from.lerp(measurement.to, 0.5);
+    gizmos.arrow(middle, measurement.from, EMERALD_400);
+    gizmos.arrow(middle, measurement.to, EMERALD_400);
+
+    // Distance along the axes as a path of axis coloured legs
+    if measurement.length.is_none() {
+        let delta = measurement.delta();
+        let x = measurement.from + Vec3::new(delta.x, 0.0, 0.0);
+        let y = x + Vec3::new(0.0, delta.y, 0.0);
+        gizmos.line(measurement.from, x, RED_500.with_alpha(0.6));
+        gizmos.line(x, y, GREEN_500.with_alpha(0.6));
+        gizmos.line(y, measurement.to, BLUE_500.with_alpha(0.6));
+    }
+}
+
+// Value of the current measurement next to the dimension line
+fn measure_label_ui(
+    state: Res<MeasureState>,
+    units: Res<DocumentUnits>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<ActiveViewport>>,
+    mut egui_contexts: EguiContexts,
+) {
+    let Some(measurement) = &state.result else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_q.get_single() else {
+        return;
+    };
+    let middle = measurement.from.lerp(measurement.to, 0.5);
+    let Ok(position) = camera.world_to_viewport(camera_transform, middle) else {
+        return;
+    };
+    let position = position + viewport_origin(camera);
+
+    let text = match (measurement.length, measurement.angle) {
+        _ if measurement.radius.is_some() => format!("R {}", units.format_length(measurement.distance)),
+        (Some(length), _) => units.format_length(length),
+        (None, Some(angle)) => format!("{}  ∠ {:.2}°", units.format_length(measurement.distance), angle.to_degrees()),
+        (None, None) => units.format_length(measurement.distance),
+    };
+    egui::Area::new(egui::Id::new("measure_label"))
+        .fixed_pos(egui::pos2(position.x + 8.0, position.y - 8.0))
+        .interactable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(text);
+            });
+        });
+}
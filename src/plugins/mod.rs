@@ -1,2 +1,16 @@
-pub mod global_gizmo_plugin;
-pub mod ai_console;
\ No newline at end of file
+pub mod measure_plugin;
+pub mod snap_plugin;
+pub mod move_plugin;
+pub mod grid_plugin;
+pub mod view_plugin;
+pub mod nav_cube_plugin;
+pub mod navigation_plugin;
+pub mod display_plugin;
+pub mod section_plugin;
+pub mod viewport_plugin;
+pub mod region_select_plugin;
+pub mod part_selection_plugin;
+pub mod smart_select_plugin;
+pub mod ai_settings_plugin;
+pub mod ai_command_plugin;
+pub mod ai_preview_plugin;
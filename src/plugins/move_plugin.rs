@@ -0,0 +1,113 @@
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::part::components::{Face, Part, PartSelection};
+use crate::tools::units::DocumentUnits;
+use crate::ui::{output_console::OutputConsole, EditorMode};
+use super::snap_plugin::{update_snap, SnapState};
+
+/// Move tool: click a part to pick it up at the snapped point, it follows the snapped
+/// cursor until the next click drops it. Clicking one of the selected parts moves the whole
+/// part selection. Escape puts them back where they were.
+pub struct MovePlugin;
+
+impl Plugin for MovePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MoveState>()
+            .add_systems(Update, (
+                cancel_move.run_if(resource_changed::<EditorMode>.or(input_just_pressed(KeyCode::Escape))),
+                handle_move_clicks.run_if(resource_equals(EditorMode::MovePart)),
+                follow_cursor.run_if(resource_equals(EditorMode::MovePart)),
+            ).chain().after(update_snap));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MoveState {
+    grab: Option<Grab>,
+}
+
+struct Grab {
+    /// Parts that move and their translation when they were picked up
+    parts: Vec<(Entity, Vec3)>,
+    /// Snapped point the part was picked up at
+    anchor: Vec3,
+}
+
+fn cancel_move(
+    mut state: ResMut<MoveState>,
+    mut snap: ResMut<SnapState>,
+    mut parts: Query<&mut Transform, With<Part>>,
+) {
+    let Some(grab) = state.grab.take() else {
+        return;
+    };
+    for (part, start) in grab.parts {
+        if let Ok(mut transform) = parts.get_mut(part) {
+            transform.translation = start;
+        }
+    }
+    snap.reference = None;
+    snap.exclude.clear();
+}
+
+fn handle_move_clicks(
+    mouse: Res<ButtonInput<MouseButton>>,
+    units: Res<DocumentUnits>,
+    faces: Query<&Parent, With<Face>>,
+    parts: Query<(Entity, &Transform, Has<PartSelection>), With<Part>>,
+    mut state: ResMut<MoveState>,
+    mut snap: ResMut<SnapState>,
+    mut console: ResMut<OutputConsole>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = snap.cursor else {
+        return;
+    };
+
+    match state.grab.take() {
+        Some(grab) => {
+            let delta = cursor.point - grab.anchor;
+            let snapped = cursor.kind.map(|kind| format!(" to {}", kind.label())).unwrap_or_default();
+            let moved = if grab.parts.len() == 1 { "part".to_string() } else { format!("{} parts", grab.parts.len()) };
+            console.add_log(format!("Moved {} by {}{}", moved, units.format_point(delta), snapped));
+            snap.reference = None;
+            snap.exclude.clear();
+        }
+        None => {
+            let Some(part) = snap.hovered_face.and_then(|face| faces.get(face).ok()).map(|parent| parent.get()) else {
+                return;
+            };
+            let Ok((_, transform, selected)) = parts.get(part) else {
+                return;
+            };
+            let moving: Vec<(Entity, Vec3)> = if selected {
+                parts.iter()
+                    .filter(|(_, _, selected)| *selected)
+                    .map(|(entity, transform, _)| (entity, transform.translation))
+                    .collect()
+            } else {
+                vec![(part, transform.translation)]
+            };
+            snap.reference = Some(cursor.point);
+            snap.exclude = moving.iter().map(|(entity, _)| *entity).collect();
+            state.grab = Some(Grab { parts: moving, anchor: cursor.point });
+        }
+    }
+}
+
+fn follow_cursor(
+    state: Res<MoveState>,
+    snap: Res<SnapState>,
+    mut parts: Query<&mut Transform, With<Part>>,
+) {
+    let (Some(grab), Some(cursor)) = (&state.grab, snap.cursor) else {
+        return;
+    };
+    for (part, start) in &grab.parts {
+        if let Ok(mut transform) = parts.get_mut(*part) {
+            transform.translation = *start + cursor.point - grab.anchor;
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::view::nav_cube::{cube_regions, polygon_contains, region_name, yaw_pitch_from_direction, CubeRegion, CUBE_FACES};
+use crate::view::standard_views::{OrbitPose, ViewTransition, TRANSITION_SECONDS};
+use crate::view::PanOrbitCamera;
+
+const CUBE_SIZE: f32 = 110.0;
+const MARGIN: f32 = 12.0;
+
+/// Navigation cube in the top right corner of each viewport. It turns with the camera,
+/// shows the name of the face, edge or corner under the pointer and a click on it turns
+/// the camera to look from that side.
+pub struct NavCubePlugin;
+
+impl Plugin for NavCubePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NavCube { regions: cube_regions() })
+            .add_systems(Update, nav_cube_ui);
+    }
+}
+
+#[derive(Resource)]
+struct NavCube {
+    regions: Vec<CubeRegion>,
+}
+
+fn nav_cube_ui(
+    cube: Res<NavCube>,
+    mut commands: Commands,
+    mut egui_contexts: EguiContexts,
+    camera_q: Query<(Entity, &PanOrbitCamera, &Camera, &GlobalTransform)>,
+) {
+    let ctx = egui_contexts.ctx_mut();
+    for (entity, pan_orbit, camera, camera_transform) in camera_q.iter() {
+        let Some(viewport) = camera.logical_viewport_rect() else {
+            continue;
+        };
+        let top_left = egui::pos2(viewport.max.x - CUBE_SIZE - MARGIN, viewport.min.y + MARGIN);
+        show_nav_cube(ctx, &cube, &mut commands, entity, pan_orbit, camera_transform, top_left);
+    }
+}
+
+fn show_nav_cube(
+    ctx: &egui::Context,
+    cube: &NavCube,
+    commands: &mut Commands,
+    entity: Entity,
+    pan_orbit: &PanOrbitCamera,
+    camera: &GlobalTransform,
+    top_left: egui::Pos2,
+) {
+    // Orthographic projection of the cube with the camera rotation, y up on screen
+    let view = camera.rotation().inverse();
+    let center = top_left + egui::vec2(CUBE_SIZE, CUBE_SIZE) * 0.5;
+    let scale = CUBE_SIZE * 0.28;
+    let project = |point: Vec3| {
+        let v = view * point;
+        egui::pos2(center.x + v.x * scale, center.y - v.y * scale)
+    };
+    let facing = |normal: Vec3| (view * normal).z > 1e-3;
+
+    egui::Area::new(egui::Id::new(("nav_cube", entity)))
+        .fixed_pos(top_left)
+        .order(egui::Order::Background)
+        .show(ctx, |ui| {
+            let (response, painter) = ui.allocate_painter(egui::vec2(CUBE_SIZE, CUBE_SIZE), egui::Sense::click());
+            let pointer = response.hover_pos().map(|pos| Vec2::new(pos.x, pos.y));
+
+            // Axis triad from the hidden corner
+            let origin = Vec3::splat(-1.0);
+            for (axis, color) in [
+                (Vec3::X, egui::Color32::from_rgb(220, 60, 60)),
+                (Vec3::Y, egui::Color32::from_rgb(80, 200, 80)),
+                (Vec3::Z, egui::Color32::from_rgb(70, 110, 230)),
+            ] {
+                painter.line_segment([project(origin), project(origin + axis * 2.6)], egui::Stroke::new(2.0, color));
+            }
+
+            let visible: Vec<&CubeRegion> = cube.regions.iter().filter(|region| facing(region.face_normal)).collect();
+            let hovered = pointer.and_then(|pointer| visible.iter().find(|region| {
+                let polygon: Vec<Vec2> = region.corners.iter().map(|c| { let p = project(*c); Vec2::new(p.x, p.y) }).collect();
+                polygon_contains(&polygon, pointer)
+            }));
+
+            for region in &visible {
+                let is_hovered = hovered.is_some_and(|hovered| hovered.direction == region.direction);
+                // Faces get darker the more they turn away
+                let light = (view * region.face_normal).z;
+                let fill = if is_hovered {
+                    egui::Color32::from_rgb(90, 150, 220)
+                } else {
+                    egui::Color32::from_gray((90.0 + 90.0 * light) as u8)
+                };
+                painter.add(egui::Shape::convex_polygon(
+                    region.corners.iter().map(|c| project(*c)).collect(),
+                    fill,
+                    egui::Stroke::new(0.5, egui::Color32::from_gray(60)),
+                ));
+            }
+            for (normal, label) in CUBE_FACES.into_iter().filter(|(normal, _)| facing(*normal)) {
+                painter.text(project(normal), egui::Align2::CENTER_CENTER, label, egui::FontId::proportional(11.0), egui::Color32::BLACK);
+            }
+
+            if let Some(region) = hovered {
+                painter.text(
+                    egui::pos2(center.x, top_left.y + CUBE_SIZE),
+                    egui::Align2::CENTER_BOTTOM,
+                    region_name(region.direction),
+                    egui::FontId::proportional(12.0),
+                    egui::Color32::from_gray(220),
+                );
+                if response.clicked() {
+                    let current = OrbitPose::from(pan_orbit);
+                    let (yaw, pitch) = yaw_pitch_from_direction(region.direction);
+                    let target = OrbitPose { yaw, pitch, ..current };
+                    commands.entity(entity).insert(ViewTransition::new(current, target, TRANSITION_SECONDS));
+                }
+            }
+        });
+}
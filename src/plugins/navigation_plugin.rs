@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::tools::preferences::Preferences;
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::view::navigation::{MouseBinding, NavAction, NavButton, NavModifier, NavigationPreset, NavigationScheme};
+
+/// Camera navigation presets (Blender, SolidWorks, Fusion 360) and a rebinding table. The
+/// choice is part of the user preferences, which are loaded at start up and saved once they
+/// have not changed for a moment, or when the app closes.
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        for preset in NavigationPreset::ALL {
+            app.add_menu_item(
+                Menu::View,
+                &format!("Navigation: {}", preset.label()),
+                None,
+                &format!("/navigation {}", preset.name()),
+            );
+        }
+        app.insert_resource(Preferences::load())
+            .add_systems(Update, (
+                navigation_console_system,
+                navigation_settings_ui,
+            ).chain())
+            // After the window close handling, so a pending change is written before exiting
+            .add_systems(Last, save_preferences);
+    }
+}
+
+fn navigation_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut preferences: ResMut<Preferences>,
+) {
+    for command in events.read().filter(|command| command.name == "navigation") {
+        match command.args.first() {
+            Some(name) => match NavigationPreset::from_name(name) {
+                Some(preset) => {
+                    preferences.navigation = NavigationScheme::preset(preset);
+                    console.add_log(format!("Navigation: {}", preset.label()));
+                }
+                None => console.add_log(format!("Unknown navigation preset '{}'", name)),
+            },
+            None => console.add_log(format!("Navigation: {}", preferences.navigation.preset.label())),
+        }
+    }
+}
+
+// Seconds without changes before the preferences are written, so dragging a slider does not
+// write the file every frame
+const SAVE_DELAY_SECS: f32 = 1.0;
+
+fn save_preferences(
+    preferences: Res<Preferences>,
+    time: Res<Time>,
+    mut exit: EventReader<AppExit>,
+    mut changed_at: Local<Option<f32>>,
+    mut console: ResMut<OutputConsole>,
+) {
+    // Just loaded, nothing new to write
+    if preferences.is_changed() && !preferences.is_added() {
+        *changed_at = Some(time.elapsed_secs());
+    }
+    let closing = exit.read().count() > 0;
+    let Some(changed) = *changed_at else {
+        return;
+    };
+    if !closing && time.elapsed_secs() - changed < SAVE_DELAY_SECS {
+        return;
+    }
+    *changed_at = None;
+    if let Err(err) = preferences.save() {
+        console.add_log(format!("Could not save preferences: {}", err));
+    }
+}
+
+fn navigation_settings_ui(mut egui_contexts: EguiContexts, mut preferences: ResMut<Preferences>) {
+    egui::Window::new("Navigation")
+        .resizable(false)
+        .collapsible(true)
+        .default_open(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            // Copy so the resource is only marked changed on an actual edit
+            let mut edited = preferences.navigation.clone();
+            egui::Grid::new("navigation_settings_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Preset");
+                let mut preset = edited.preset;
+                egui::ComboBox::from_id_salt("navigation_preset")
+                    .selected_text(preset.label())
+                    .show_ui(ui, |ui| {
+                        for option in NavigationPreset::ALL {
+                            ui.selectable_value(&mut preset, option, option.label());
+                        }
+                    });
+                if preset != edited.preset {
+                    edited = NavigationScheme::preset(preset);
+                }
+                ui.end_row();
+
+                let before = (edited.orbit, edited.pan, edited.zoom);
+                binding_row(ui, NavAction::Orbit, &mut edited.orbit);
+                binding_row(ui, NavAction::Pan, &mut edited.pan);
+
+                let mut drag_zoom = edited.zoom.is_some();
+                ui.checkbox(&mut drag_zoom, "Zoom");
+                match (drag_zoom, edited.zoom) {
+                    (true, None) => edited.zoom = Some(MouseBinding::new(NavButton::Middle, NavModifier::Ctrl)),
+                    (false, Some(_)) => edited.zoom = None,
+                    _ => {}
+                }
+                match edited.zoom.as_mut() {
+                    Some(zoom) => binding_combos(ui, "zoom", zoom),
+                    None => { ui.label("wheel only"); }
+                }
+                ui.end_row();
+                if (edited.orbit, edited.pan, edited.zoom) != before {
+                    edited.preset = NavigationPreset::Custom;
+                }
+            });
+
+            for (a, b) in edited.conflicts() {
+                ui.colored_label(egui::Color32::YELLOW, format!("{} and {} use the same binding", a.label(), b.label()));
+            }
+
+            ui.checkbox(&mut edited.orbit_around_cursor, "Orbit around the point under the cursor");
+            ui.checkbox(&mut edited.zoom_to_cursor, "Zoom to the cursor");
+            ui.checkbox(&mut edited.invert_zoom, "Invert zoom direction");
+            ui.checkbox(&mut edited.trackpad_orbit, "Trackpad scrolling orbits, pinch zooms");
+            egui::Grid::new("navigation_speed_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Orbit speed");
+                ui.add(egui::Slider::new(&mut edited.orbit_sensitivity, 0.001..=0.02).logarithmic(true));
+                ui.end_row();
+
+                ui.label("Pan speed");
+                ui.add(egui::Slider::new(&mut edited.pan_sensitivity, 0.0005..=0.01).logarithmic(true));
+                ui.end_row();
+
+                ui.label("Zoom speed");
+                ui.add(egui::Slider::new(&mut edited.zoom_sensitivity, 0.02..=0.5));
+                ui.end_row();
+            });
+
+            if edited != preferences.navigation {
+                preferences.navigation = edited;
+            }
+        });
+}
+
+fn binding_row(ui: &mut egui::Ui, action: NavAction, binding: &mut MouseBinding) {
+    ui.label(action.label());
+    binding_combos(ui, action.label(), binding);
+    ui.end_row();
+}
+
+fn binding_combos(ui: &mut egui::Ui, id: &str, binding: &mut MouseBinding) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt(format!("{}_modifier", id))
+            .width(60.0)
+            .selected_text(binding.modifier.label())
+            .show_ui(ui, |ui| {
+                for modifier in NavModifier::ALL {
+                    ui.selectable_value(&mut binding.modifier, modifier, modifier.label());
+                }
+            });
+        egui::ComboBox::from_id_salt(format!("{}_button", id))
+            .selected_text(binding.button.label())
+            .show_ui(ui, |ui| {
+                for button in NavButton::ALL {
+                    ui.selectable_value(&mut binding.button, button, button.label());
+                }
+            });
+    });
+}
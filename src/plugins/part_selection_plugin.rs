@@ -0,0 +1,243 @@
+use bevy::prelude::*;
+
+use crate::part::components::{Face, FaceMaterial, Part, PartColor, PartGroup, PartId, PartSelection};
+use crate::part::csg::BooleanOp;
+use crate::part::naming::FeatureId;
+use crate::part::mouse_part_systems::rotate_on_drag;
+use crate::part::{create_part_from_polygons, PartAssets};
+use crate::tools::export::stl_ascii;
+use crate::tools::units::DocumentUnits;
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::ui::{EditorMode, ToolbarAction};
+
+const DEFAULT_EXPORT_FILE: &str = "selection.stl";
+
+/// What a duplicate or the result of a boolean takes over from its original besides the geometry
+type PartLooks<'a> = (Option<&'a Name>, Option<&'a PartColor>, Option<&'a PartGroup>);
+
+type SelectedParts<'a> = (Entity, &'a Part, &'a mut Transform, &'a Children, PartLooks<'a>, Option<&'a PartId>);
+
+type PartFaces<'a> = (&'a Face, &'a Mesh3d, &'a Transform, &'a GlobalTransform, &'a FaceMaterial);
+
+/// Operations on the parts selected in part selection mode: delete, duplicate, move, rotate,
+/// booleans and STL export from the Edit menu or the `/part` command. The move tool takes the
+/// whole selection along when one of its parts is picked up.
+pub struct PartSelectionPlugin;
+
+impl Plugin for PartSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_menu_item(Menu::Edit, "Duplicate parts", None, "/part duplicate")
+            .add_menu_item(Menu::Edit, "Delete parts", None, "/part delete")
+            .add_menu_item(Menu::Edit, "Union of parts", None, "/part union")
+            .add_menu_item(Menu::Edit, "Subtract parts from the first", None, "/part difference")
+            .add_menu_item(Menu::Edit, "Intersection of parts", None, "/part intersection")
+            .add_menu_item(Menu::Edit, "Export selected parts", None, "/part export");
+        app.add_systems(Update, (
+            clear_part_selection,
+            toolbar_delete_parts,
+            part_console_system,
+        ).chain());
+    }
+}
+
+// Face and edge modes work inside parts, Escape clears the part selection
+fn clear_part_selection(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    selected: Query<Entity, With<PartSelection>>,
+) {
+    let left_part_modes = mode.is_changed() && matches!(*mode, EditorMode::SelectFace | EditorMode::SelectEdge | EditorMode::Measure);
+    let escape = *mode == EditorMode::SelectPart && keyboard.just_pressed(KeyCode::Escape);
+    if left_part_modes || escape {
+        for entity in selected.iter() {
+            commands.entity(entity).remove::<PartSelection>();
+        }
+    }
+}
+
+fn toolbar_delete_parts(
+    mut events: EventReader<ToolbarAction>,
+    mode: Res<EditorMode>,
+    mut console_commands: EventWriter<ConsoleCommand>,
+) {
+    for _ in events.read().filter(|action| matches!(action, ToolbarAction::Delete)) {
+        if *mode == EditorMode::SelectPart {
+            console_commands.send_batch(ConsoleCommand::parse("/part delete"));
+        }
+    }
+}
+
+fn part_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut commands: Commands,
+    mut assets: PartAssets,
+    units: Res<DocumentUnits>,
+    mut selected: Query<SelectedParts, With<PartSelection>>,
+    faces: Query<PartFaces, Without<PartSelection>>,
+) {
+    for command in events.read().filter(|command| command.name == "part") {
+        let count = selected.iter().count();
+        let action = command.args.first().map(String::as_str);
+        if count == 0 && action.is_some() {
+            console.add_log("No parts selected, select parts in part selection mode");
+            continue;
+        }
+        match action {
+            None => console.add_log(format!("{} parts selected", count)),
+            Some("delete") => {
+                for (entity, ..) in selected.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                console.add_log(format!("Deleted {} parts", count));
+            }
+            Some("duplicate") => {
+                for (entity, part, transform, children, looks, _) in selected.iter() {
+                    let part_faces: Vec<_> = faces.iter_many(children).collect();
+                    // Next to the original, one part width further along x
+                    let (min, max) = part_faces.iter()
+                        .flat_map(|(face, _, _, global, _)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)))
+                        .fold((Vec3::MAX, Vec3::MIN), |(min, max), p| (min.min(p), max.max(p)));
+                    let offset = Vec3::X * ((max.x - min.x) * 1.25).max(0.5);
+
+                    // The copy is a new feature, its names must not match the original's
+                    let feature = FeatureId::next();
+                    let mut parent = commands.spawn((
+                        transform.with_translation(transform.translation + offset),
+                        Visibility::default(),
+                        part.copied(feature),
+                        PartSelection,
+                    ));
+                    parent.observe(rotate_on_drag);
+                    let parent = parent.id();
+                    insert_looks(&mut commands, parent, looks);
+                    for (face, mesh, face_transform, _, material) in part_faces {
+                        commands.spawn((
+                            mesh.clone(),
+                            MeshMaterial3d(material.0.clone()),
+                            *face_transform,
+                            Visibility::default(),
+                            face.copied(feature),
+                        ))
+                        .set_parent(parent);
+                    }
+                    // The selection moves over to the copies
+                    commands.entity(entity).remove::<PartSelection>();
+                }
+                console.add_log(format!("Duplicated {} parts", count));
+            }
+            Some("move") => {
+                let lengths: Result<Vec<f32>, _> = command.args[1..].iter().map(|arg| units.parse_length(arg)).collect();
+                let offset = match lengths.as_deref() {
+                    Ok([x, y, z]) => Vec3::new(*x, *y, *z),
+                    Ok(_) => {
+                        console.add_log("Usage: /part move <dx> <dy> <dz>");
+                        continue;
+                    }
+                    Err(error) => {
+                        console.add_log(format!("Invalid distance: {}", error));
+                        continue;
+                    }
+                };
+                for (_, _, mut transform, ..) in selected.iter_mut() {
+                    transform.translation += offset;
+                }
+                console.add_log(format!(
+                    "Moved {} parts by {}, {}, {}",
+                    count, units.format_length(offset.x), units.format_length(offset.y), units.format_length(offset.z),
+                ));
+            }
+            Some("rotate") => {
+                let axis = match command.args.get(1).map(String::as_str) {
+                    Some("x") => Vec3::X,
+                    Some("y") => Vec3::Y,
+                    Some("z") => Vec3::Z,
+                    _ => {
+                        console.add_log("Usage: /part rotate <x|y|z> <degrees>");
+                        continue;
+                    }
+                };
+                let Some(degrees) = command.args.get(2).and_then(|arg| arg.parse::<f32>().ok()) else {
+                    console.add_log("Usage: /part rotate <x|y|z> <degrees>");
+                    continue;
+                };
+                // The selection turns as a whole about the centre of its bounds
+                let (min, max) = selected.iter()
+                    .flat_map(|(_, _, _, children, ..)| world_polygons(&faces, children))
+                    .flatten()
+                    .fold((Vec3::MAX, Vec3::MIN), |(min, max), p| (min.min(p), max.max(p)));
+                let centre = (min + max) / 2.0;
+                let rotation = Quat::from_axis_angle(axis, degrees.to_radians());
+                for (_, _, mut transform, ..) in selected.iter_mut() {
+                    transform.rotate_around(centre, rotation);
+                }
+                console.add_log(format!("Rotated {} parts by {}° about {}", count, degrees, command.args[1]));
+            }
+            Some(name @ ("union" | "difference" | "intersection")) => {
+                if count < 2 {
+                    console.add_log(format!("Select at least two parts for the {}", name));
+                    continue;
+                }
+                let operation = BooleanOp::from_name(name).expect("boolean names are matched above");
+                // The part made first is kept, a difference takes the others away from it
+                let mut operands: Vec<_> = selected.iter()
+                    .map(|(entity, _, _, children, looks, id)| (id.map(|id| id.0), entity, looks, world_polygons(&faces, children)))
+                    .collect();
+                operands.sort_by_key(|(id, entity, ..)| (id.is_none(), *id, *entity));
+                let (_, _, looks, mut result) = operands[0].clone();
+                for (_, _, _, polygons) in &operands[1..] {
+                    result = operation.apply(&result, polygons);
+                }
+                if result.is_empty() {
+                    console.add_log(format!("The {} is empty, the parts were left unchanged", name));
+                    continue;
+                }
+                let entity = create_part_from_polygons(&mut commands, &mut assets.meshes, &mut assets.materials, &result);
+                commands.entity(entity).insert(PartSelection);
+                insert_looks(&mut commands, entity, looks);
+                for (_, operand, ..) in &operands {
+                    commands.entity(*operand).despawn_recursive();
+                }
+                console.add_log(format!("Combined {} parts into their {}", count, name));
+            }
+            Some("export") => {
+                let path = command.args.get(1).map_or(DEFAULT_EXPORT_FILE, String::as_str);
+                let scale = units.length.model_to_unit(1.0);
+                let polygons: Vec<Vec<Vec3>> = selected.iter()
+                    .flat_map(|(_, _, _, children, ..)| world_polygons(&faces, children))
+                    .map(|polygon| polygon.into_iter().map(|point| point * scale).collect())
+                    .collect();
+                match std::fs::write(path, stl_ascii("selection", &polygons)) {
+                    Ok(()) => console.add_log(format!("Exported {} parts to {} in {}", count, path, units.length.symbol())),
+                    Err(error) => console.add_log(format!("Could not write {}: {}", path, error)),
+                }
+            }
+            Some(other) => console.add_log(format!("Unknown part operation '{}'", other)),
+        }
+    }
+}
+
+// The faces of a part in world coordinates
+fn world_polygons(
+    faces: &Query<PartFaces, Without<PartSelection>>,
+    children: &Children,
+) -> Vec<Vec<Vec3>> {
+    faces.iter_many(children)
+        .map(|(face, _, _, global, _)| face.vertices.iter().map(|v| global.transform_point(v.coordinates)).collect())
+        .collect()
+}
+
+fn insert_looks(commands: &mut Commands, entity: Entity, (name, color, group): PartLooks) {
+    let mut entity = commands.entity(entity);
+    if let Some(name) = name {
+        entity.insert(name.clone());
+    }
+    if let Some(color) = color {
+        entity.insert(*color);
+    }
+    if let Some(group) = group {
+        entity.insert(group.clone());
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::part::components::{Edge, Face, Part, PartSelection};
+use crate::part::mouse_part_systems::{handle_face_selection, handle_part_selection, set_part_selected};
+use crate::part::selection::SelectionOp;
+use crate::tools::colors::PRESSED_COLOR;
+use crate::tools::preferences::Preferences;
+use crate::tools::region_select::{apply_selection, RegionMode, RegionShape, SelectOp, SelectionRegion};
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::ui::EditorMode;
+use crate::view::navigation::{NavButton, NavModifier};
+use crate::view::viewports::{viewport_origin, ActiveViewport};
+use super::section_plugin::drag_section_plane;
+
+/// The pointer has to move this many pixels before a press becomes a region drag
+const DRAG_THRESHOLD: f32 = 5.0;
+/// Distance between recorded lasso points
+const LASSO_SPACING: f32 = 3.0;
+
+/// Box and lasso selection of faces, edges or whole parts, depending on the editor mode.
+/// Dragging to the right (or a lasso drawn clockwise) selects what is completely inside,
+/// dragging to the left also what the region touches. Shift adds to the selection, Ctrl
+/// removes from it.
+pub struct RegionSelectPlugin;
+
+impl Plugin for RegionSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_menu_item(Menu::Edit, "Box selection", None, "/select box")
+            .add_menu_item(Menu::Edit, "Lasso selection", None, "/select lasso");
+        app.init_resource::<RegionSelect>()
+            .add_systems(Update, (
+                select_console_system,
+                (
+                    track_region_drag,
+                    finish_region_drag,
+                ).chain()
+                    .after(drag_section_plane)
+                    .before(handle_face_selection)
+                    .before(handle_part_selection)
+                    .run_if(region_select_mode),
+                draw_selection_region,
+                draw_selected_edges.run_if(resource_equals(EditorMode::SelectEdge)),
+            ));
+    }
+}
+
+#[derive(Resource, Default)]
+struct RegionSelect {
+    shape: RegionShape,
+    drag: Option<RegionDrag>,
+}
+
+struct RegionDrag {
+    shape: RegionShape,
+    op: SelectOp,
+    /// Window positions, the start and then the pointer path
+    points: Vec<Vec2>,
+    /// Moved far enough to be a region rather than a click
+    dragging: bool,
+    /// Selection of every part when the button went down, the click selection changes it
+    before: Vec<PartSnapshot>,
+}
+
+impl RegionDrag {
+    fn region(&self) -> SelectionRegion {
+        match self.shape {
+            RegionShape::Box => SelectionRegion::rectangle(self.points[0], *self.points.last().unwrap()),
+            RegionShape::Lasso => SelectionRegion::lasso(self.points.clone()),
+        }
+    }
+}
+
+struct PartSnapshot {
+    part: Entity,
+    faces: Vec<Face>,
+    edges: Vec<Edge>,
+    selected: bool,
+}
+
+fn region_select_mode(mode: Res<EditorMode>) -> bool {
+    matches!(*mode, EditorMode::SelectFace | EditorMode::SelectEdge | EditorMode::SelectPart)
+}
+
+#[derive(SystemParam)]
+struct PointerInput<'w, 's> {
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    preferences: Res<'w, Preferences>,
+    windows: Query<'w, 's, &'static Window>,
+    egui_contexts: EguiContexts<'w, 's>,
+}
+
+fn select_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut select: ResMut<RegionSelect>,
+) {
+    for command in events.read().filter(|command| command.name == "select") {
+        match command.args.first().map(String::as_str) {
+            Some("box") => select.shape = RegionShape::Box,
+            Some("lasso") => select.shape = RegionShape::Lasso,
+            // Selection commands are handled by the smart selection
+            Some(other) if SelectionOp::from_name(other).is_some() => continue,
+            Some(other) => {
+                console.add_log(format!("Unknown selection option '{}'", other));
+                continue;
+            }
+            None => {}
+        }
+        console.add_log(format!("Region selection: {}", select.shape.name()));
+    }
+}
+
+fn track_region_drag(
+    mut input: PointerInput,
+    mut commands: Commands,
+    mut parts: Query<(Entity, &mut Part, Has<PartSelection>)>,
+    mut select: ResMut<RegionSelect>,
+) {
+    let Some(cursor) = input.windows.get_single().ok().and_then(|window| window.cursor_position()) else {
+        return;
+    };
+
+    if input.mouse.just_pressed(MouseButton::Left) {
+        select.drag = None;
+        let modifier = NavModifier::ALL.into_iter()
+            .skip(1)
+            .find(|modifier| input.keyboard.any_pressed(modifier.keys().iter().copied()))
+            .unwrap_or_default();
+        // Left drags bound to navigation stay navigation
+        let navigation = input.preferences.navigation.drag_action(NavButton::Left, modifier).is_some();
+        if navigation || input.egui_contexts.ctx_mut().is_pointer_over_area() {
+            return;
+        }
+        let op = match modifier {
+            NavModifier::Shift => SelectOp::Add,
+            NavModifier::Ctrl => SelectOp::Subtract,
+            _ => SelectOp::Replace,
+        };
+        select.drag = Some(RegionDrag {
+            shape: select.shape,
+            op,
+            points: vec![cursor],
+            dragging: false,
+            before: parts.iter()
+                .map(|(part, selection, selected)| PartSnapshot {
+                    part,
+                    faces: selection.selected_faces.clone(),
+                    edges: selection.selected_edges.clone(),
+                    selected,
+                })
+                .collect(),
+        });
+        return;
+    }
+
+    let Some(drag) = select.drag.as_mut() else {
+        return;
+    };
+    if !drag.dragging && cursor.distance(drag.points[0]) > DRAG_THRESHOLD {
+        drag.dragging = true;
+        // Undo what the press selected as a click
+        for snapshot in &drag.before {
+            if let Ok((_, mut part, selected)) = parts.get_mut(snapshot.part) {
+                part.selected_faces = snapshot.faces.clone();
+                part.selected_edges = snapshot.edges.clone();
+                if selected != snapshot.selected {
+                    set_part_selected(&mut commands, snapshot.part, snapshot.selected);
+                }
+            }
+        }
+    }
+    match drag.shape {
+        RegionShape::Box => {
+            drag.points.truncate(1);
+            drag.points.push(cursor);
+        }
+        RegionShape::Lasso => {
+            if drag.points.last().is_some_and(|last| last.distance(cursor) > LASSO_SPACING) {
+                drag.points.push(cursor);
+            }
+        }
+    }
+}
+
+fn finish_region_drag(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mode: Res<EditorMode>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<ActiveViewport>>,
+    faces: Query<(&Face, &GlobalTransform, &Parent)>,
+    mut parts: Query<(Entity, &mut Part, Has<PartSelection>)>,
+    mut select: ResMut<RegionSelect>,
+) {
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(drag) = select.drag.take().filter(|drag| drag.dragging) else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_q.get_single() else {
+        return;
+    };
+    let region = drag.region();
+    let origin = viewport_origin(camera);
+    let project = |point: Vec3| camera.world_to_viewport(camera_transform, point).ok().map(|position| position + origin);
+
+    let mut picked_faces: HashMap<Entity, Vec<Face>> = HashMap::new();
+    let mut picked_edges: HashMap<Entity, Vec<Edge>> = HashMap::new();
+    // Whether all and whether any of the faces of each part were picked
+    let mut picked_parts: HashMap<Entity, (bool, bool)> = HashMap::new();
+    for (face, transform, parent) in faces.iter() {
+        match *mode {
+            EditorMode::SelectEdge => {
+                for edge in &face.edges {
+                    let outline = [edge.start, edge.end].map(|vertex| project(transform.transform_point(vertex.coordinates)));
+                    let picked = picked_edges.entry(parent.get()).or_default();
+                    if region.selects(&outline, false) && !picked.contains(edge) {
+                        picked.push(edge.clone());
+                    }
+                }
+            }
+            _ => {
+                let outline: Vec<Option<Vec2>> = face.vertices.iter()
+                    .map(|vertex| project(transform.transform_point(vertex.coordinates)))
+                    .collect();
+                let picked = region.selects(&outline, true);
+                if picked {
+                    picked_faces.entry(parent.get()).or_default().push(face.clone());
+                }
+                let (all, any) = picked_parts.entry(parent.get()).or_insert((true, false));
+                *all &= picked;
+                *any |= picked;
+            }
+        }
+    }
+
+    for (entity, mut part, selected) in parts.iter_mut() {
+        match *mode {
+            EditorMode::SelectFace => {
+                let picked = picked_faces.remove(&entity).unwrap_or_default();
+                apply_selection(&mut part.selected_faces, &picked, drag.op);
+            }
+            EditorMode::SelectEdge => {
+                let picked = picked_edges.remove(&entity).unwrap_or_default();
+                apply_selection(&mut part.selected_edges, &picked, drag.op);
+            }
+            _ => {
+                // A window has to hold the whole part, a crossing region any of its faces
+                let picked = picked_parts.get(&entity).is_some_and(|(all, any)| match region.mode {
+                    RegionMode::Window => *all,
+                    RegionMode::Crossing => *any,
+                });
+                let mut selection = if selected { vec![entity] } else { Vec::new() };
+                let picked = if picked { vec![entity] } else { Vec::new() };
+                apply_selection(&mut selection, &picked, drag.op);
+                if selection.is_empty() == selected {
+                    set_part_selected(&mut commands, entity, !selected);
+                }
+            }
+        }
+    }
+}
+
+fn draw_selection_region(select: Res<RegionSelect>, mut egui_contexts: EguiContexts) {
+    let Some(drag) = select.drag.as_ref().filter(|drag| drag.dragging) else {
+        return;
+    };
+    let region = drag.region();
+    // Window regions solid blue, crossing ones dashed green
+    let (stroke_color, fill) = match region.mode {
+        RegionMode::Window => (egui::Color32::from_rgb(70, 130, 230), egui::Color32::from_rgba_unmultiplied(70, 130, 230, 30)),
+        RegionMode::Crossing => (egui::Color32::from_rgb(70, 190, 100), egui::Color32::from_rgba_unmultiplied(70, 190, 100, 30)),
+    };
+    let stroke = egui::Stroke::new(1.0, stroke_color);
+    let points: Vec<egui::Pos2> = region.points.iter().map(|p| egui::pos2(p.x, p.y)).collect();
+    let painter = egui_contexts.ctx_mut().layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("selection_region")));
+
+    if drag.shape == RegionShape::Box {
+        painter.rect_filled(egui::Rect::from_two_pos(points[0], points[2]), 0.0, fill);
+    }
+    let mut closed = points.clone();
+    closed.push(points[0]);
+    match region.mode {
+        RegionMode::Window => {
+            painter.add(egui::Shape::line(closed, stroke));
+        }
+        RegionMode::Crossing => {
+            painter.extend(egui::Shape::dashed_line(&closed, stroke, 6.0, 4.0));
+        }
+    }
+}
+
+// Faces show their selection through their material, edges are drawn on top
+fn draw_selected_edges(
+    faces: Query<(&Face, &GlobalTransform, &Parent)>,
+    parts: Query<&Part>,
+    mut gizmos: Gizmos,
+) {
+    for (face, transform, parent) in faces.iter() {
+        let Ok(part) = parts.get(parent.get()) else {
+            continue;
+        };
+        for edge in face.edges.iter().filter(|edge| part.selected_edges.contains(edge)) {
+            gizmos.line(
+                transform.transform_point(edge.start.coordinates),
+                transform.transform_point(edge.end.coordinates),
+                PRESSED_COLOR,
+            );
+        }
+    }
+}
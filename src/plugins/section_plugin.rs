@@ -0,0 +1,377 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes::tailwind::*,
+    image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        view::RenderLayers,
+    },
+};
+
+use crate::part::components::{Face, Part};
+use crate::part::mouse_part_systems::handle_face_selection;
+use crate::tools::units::DocumentUnits;
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::view::display_mode::SHADED_LAYER;
+use crate::view::section::{
+    cap_triangles, chain_loops, closest_on_axis, cut_polygon, section_material, SectionMaterial, SectionPlane,
+    SectionPlanes, MAX_SECTION_PLANES,
+};
+use crate::view::viewports::{viewport_cursor, ActiveViewport};
+use crate::view::PanOrbitCamera;
+
+/// Model length of one hatch texture repeat on the cap faces
+const HATCH_SPACING: f32 = 0.08;
+/// Caps sit this far behind their plane so it does not clip them
+const CAP_OFFSET: f32 = 1e-4;
+/// Pixels around the drag handle that grab it
+const HANDLE_PICK_PIXELS: f32 = 10.0;
+
+/// Section planes that clip all part rendering, with hatched caps on the cut. Planes are added
+/// from the View menu or `/section`, through the focus or on the selected face, and dragged
+/// along their normal by the arrow handle.
+pub struct SectionPlugin;
+
+impl Plugin for SectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_menu_item(Menu::View, "Add section plane", None, "/section add view")
+            .add_menu_item(Menu::View, "Section plane on selected face", None, "/section face")
+            .add_menu_item(Menu::View, "Flip section plane", None, "/section flip")
+            .add_menu_item(Menu::View, "Clear section planes", None, "/section clear");
+        app.init_resource::<SectionPlanes>()
+            .init_resource::<SectionDrag>()
+            .add_systems(Startup, setup_hatch_material)
+            .add_systems(Update, (
+                section_console_system,
+                drag_section_plane.before(handle_face_selection),
+                update_clip_planes.run_if(resource_changed::<SectionPlanes>),
+                update_section_caps,
+                draw_section_planes,
+            ).chain());
+    }
+}
+
+#[derive(Resource)]
+struct HatchMaterial(Handle<SectionMaterial>);
+
+/// Section cap of the plane with this index
+#[derive(Component)]
+struct SectionCap {
+    plane: usize,
+    triangles: Vec<[Vec3; 3]>,
+}
+
+#[derive(Resource, Default)]
+pub struct SectionDrag {
+    /// Plane being dragged, its origin and the axis parameter where it was grabbed
+    grab: Option<(usize, Vec3, f32)>,
+}
+
+fn setup_hatch_material(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<SectionMaterial>>,
+    planes: Res<SectionPlanes>,
+) {
+    // Diagonal stripes, repeated across the cap
+    let size = 16;
+    let mut data = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let value = if (x + y) % 8 < 2 { 40 } else { 210 };
+            data.extend([value, value, value, 255]);
+        }
+    }
+    let mut image = Image::new(
+        Extent3d { width: size as u32, height: size as u32, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    });
+    let base = StandardMaterial {
+        base_color: Color::srgb(0.95, 0.55, 0.45),
+        base_color_texture: Some(images.add(image)),
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    };
+    commands.insert_resource(HatchMaterial(materials.add(section_material(base, &planes))));
+}
+
+fn section_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut sections: ResMut<SectionPlanes>,
+    units: Res<DocumentUnits>,
+    camera_q: Query<(&PanOrbitCamera, &GlobalTransform), With<ActiveViewport>>,
+    faces: Query<(&Face, &GlobalTransform, &Parent)>,
+    parts: Query<&Part>,
+) {
+    for command in events.read().filter(|command| command.name == "section") {
+        let args: Vec<&str> = command.args.iter().map(String::as_str).collect();
+        let new_plane = match args.as_slice() {
+            ["add", rest @ ..] => {
+                let Ok((pan_orbit, camera)) = camera_q.get_single() else {
+                    continue;
+                };
+                // A view plane hides the half between the camera and the focus
+                let normal = match rest.first().copied().unwrap_or("view") {
+                    "x" => Vec3::X,
+                    "y" => Vec3::Y,
+                    "z" => Vec3::Z,
+                    "view" => camera.back().into(),
+                    other => {
+                        console.add_log(format!("Unknown direction '{}', use x, y, z or view", other));
+                        continue;
+                    }
+                };
+                Some(SectionPlane::new(pan_orbit.focus, normal))
+            }
+            ["face"] => {
+                let selected = faces.iter().find(|(face, _, parent)| {
+                    parts.get(parent.get()).is_ok_and(|part| part.selected_faces.contains(face))
+                });
+                let Some((face, transform, _)) = selected else {
+                    console.add_log("Select a face to put the section plane on");
+                    continue;
+                };
+                let points: Vec<Vec3> = face.vertices.iter().map(|v| transform.transform_point(v.coordinates)).collect();
+                let center = points.iter().sum::<Vec3>() / points.len().max(1) as f32;
+                // Hides what is in front of the face, drag it inwards to cut the part
+                Some(SectionPlane::new(center, transform.affine().transform_vector3(face.normal)))
+            }
+            _ => None,
+        };
+        if let Some(plane) = new_plane {
+            if sections.planes.len() >= MAX_SECTION_PLANES {
+                console.add_log(format!("At most {} section planes", MAX_SECTION_PLANES));
+                continue;
+            }
+            sections.planes.push(plane);
+            sections.active = sections.planes.len() - 1;
+            console.add_log(format!("Section plane {} added", sections.planes.len()));
+            continue;
+        }
+
+        let active = sections.active;
+        match args.as_slice() {
+            [] | ["list"] => {
+                if sections.planes.is_empty() {
+                    console.add_log("No section planes");
+                }
+                for (i, plane) in sections.planes.iter().enumerate() {
+                    console.add_log(format!(
+                        "{}{} at {} normal ({:.2}, {:.2}, {:.2}){}",
+                        i + 1,
+                        if i == active { "*" } else { "" },
+                        units.format_point(plane.origin),
+                        plane.normal.x, plane.normal.y, plane.normal.z,
+                        if plane.enabled { "" } else { " (off)" },
+                    ));
+                }
+            }
+            ["select", index] => match index.parse::<usize>() {
+                Ok(index) if (1..=sections.planes.len()).contains(&index) => sections.active = index - 1,
+                _ => console.add_log(format!("No section plane {}", index)),
+            },
+            ["clear"] => {
+                sections.planes.clear();
+                sections.active = 0;
+            }
+            [action, rest @ ..] => {
+                let Some(plane) = sections.active_mut() else {
+                    console.add_log("No section plane, add one with /section add");
+                    continue;
+                };
+                match (*action, rest) {
+                    ("flip", []) => *plane = plane.flipped(),
+                    ("toggle", []) => plane.enabled = !plane.enabled,
+                    ("offset", distance) if !distance.is_empty() => match units.parse_length(&distance.join(" ")) {
+                        Ok(distance) => plane.origin += plane.normal * distance,
+                        Err(e) => console.add_log(format!("Invalid offset: {}", e)),
+                    },
+                    ("remove", []) => {
+                        sections.planes.remove(active);
+                        sections.active = active.saturating_sub(1);
+                    }
+                    _ => console.add_log("Usage: /section [add [x|y|z|view]|face|select <n>|flip|toggle|offset <length>|remove|clear]"),
+                }
+            }
+        }
+    }
+}
+
+// Every clipping material follows the planes
+fn update_clip_planes(sections: Res<SectionPlanes>, mut materials: ResMut<Assets<SectionMaterial>>) {
+    let planes = sections.clip_vec4s();
+    for (_, material) in materials.iter_mut() {
+        material.extension.planes = planes;
+    }
+}
+
+fn update_section_caps(
+    mut commands: Commands,
+    sections: Res<SectionPlanes>,
+    hatch: Option<Res<HatchMaterial>>,
+    faces: Query<(&Face, &GlobalTransform, &Parent)>,
+    mut caps: Query<(Entity, &mut SectionCap, &Mesh3d)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Some(hatch) = hatch else {
+        return;
+    };
+    for (entity, cap, _) in caps.iter() {
+        if sections.planes.get(cap.plane).is_none_or(|plane| !plane.enabled) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for (index, plane) in sections.planes.iter().enumerate().filter(|(_, plane)| plane.enabled) {
+        // Cut each part on its own so loops of different parts are not taken for holes
+        let mut parts: Vec<(Entity, Vec<[Vec3; 2]>)> = Vec::new();
+        for (face, transform, parent) in faces.iter() {
+            let points: Vec<Vec3> = face.vertices.iter().map(|v| transform.transform_point(v.coordinates)).collect();
+            let segments = cut_polygon(&points, plane);
+            match parts.iter_mut().find(|(part, _)| *part == parent.get()) {
+                Some((_, part_segments)) => part_segments.extend(segments),
+                None => parts.push((parent.get(), segments)),
+            }
+        }
+        let triangles: Vec<[Vec3; 3]> = parts.iter()
+            .flat_map(|(_, segments)| cap_triangles(&chain_loops(segments), plane))
+            .map(|triangle| triangle.map(|p| p - plane.normal * CAP_OFFSET))
+            .collect();
+
+        match caps.iter_mut().find(|(_, cap, _)| cap.plane == index) {
+            Some((_, mut cap, mesh)) => {
+                if cap.triangles != triangles {
+                    if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                        *mesh = cap_mesh(&triangles, plane);
+                    }
+                    cap.triangles = triangles;
+                }
+            }
+            None => {
+                commands.spawn((
+                    Mesh3d(meshes.add(cap_mesh(&triangles, plane))),
+                    MeshMaterial3d(hatch.0.clone()),
+                    RenderLayers::layer(SHADED_LAYER),
+                    PickingBehavior::IGNORE,
+                    SectionCap { plane: index, triangles },
+                ));
+            }
+        }
+    }
+}
+
+fn cap_mesh(triangles: &[[Vec3; 3]], plane: &SectionPlane) -> Mesh {
+    let work_plane = plane.work_plane();
+    // A degenerate triangle keeps the vertex buffer from being empty
+    let positions: Vec<Vec3> = if triangles.is_empty() {
+        vec![plane.origin; 3]
+    } else {
+        triangles.iter().flatten().copied().collect()
+    };
+    let uvs: Vec<Vec2> = positions.iter().map(|p| work_plane.to_local(*p) / HATCH_SPACING).collect();
+    let normals = vec![plane.normal; positions.len()];
+    let indices = (0..positions.len() as u32).collect();
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+// Arrow handle of a plane in world space, scaled with the view distance
+fn handle_segment(plane: &SectionPlane, pan_orbit: &PanOrbitCamera) -> (Vec3, Vec3) {
+    (plane.origin, plane.origin + plane.normal * pan_orbit.radius * 0.15)
+}
+
+pub fn drag_section_plane(
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    mut sections: ResMut<SectionPlanes>,
+    mut drag: ResMut<SectionDrag>,
+    camera_q: Query<(&Camera, &GlobalTransform, &PanOrbitCamera), With<ActiveViewport>>,
+    windows: Query<&Window>,
+) {
+    let (Ok((camera, camera_transform, pan_orbit)), Ok(window)) = (camera_q.get_single(), windows.get_single()) else {
+        return;
+    };
+    // Viewport coordinates, like the projected handles
+    let Some(cursor) = window.cursor_position().map(|cursor| viewport_cursor(camera, cursor)) else {
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+
+    if !mouse.pressed(MouseButton::Left) {
+        drag.grab = None;
+    }
+    if mouse.just_pressed(MouseButton::Left) {
+        let hovered = sections.planes.iter().enumerate().find(|(_, plane)| {
+            let (start, end) = handle_segment(plane, pan_orbit);
+            let (Ok(a), Ok(b)) = (camera.world_to_viewport(camera_transform, start), camera.world_to_viewport(camera_transform, end)) else {
+                return false;
+            };
+            let t = ((cursor - a).dot(b - a) / (b - a).length_squared().max(1e-6)).clamp(0.0, 1.0);
+            cursor.distance(a + (b - a) * t) < HANDLE_PICK_PIXELS
+        });
+        if let Some((index, plane)) = hovered {
+            if let Some(t) = closest_on_axis(plane.origin, plane.normal, ray.origin, *ray.direction) {
+                drag.grab = Some((index, plane.origin, t));
+                sections.active = index;
+                // The click belongs to the handle, not to face selection
+                mouse.clear_just_pressed(MouseButton::Left);
+            }
+        }
+    }
+
+    let Some((index, start, grabbed_at)) = drag.grab else {
+        return;
+    };
+    let Some(plane) = sections.planes.get(index).copied() else {
+        drag.grab = None;
+        return;
+    };
+    if let Some(t) = closest_on_axis(start, plane.normal, ray.origin, *ray.direction) {
+        let origin = start + plane.normal * (t - grabbed_at);
+        if origin != plane.origin {
+            sections.planes[index].origin = origin;
+        }
+    }
+}
+
+fn draw_section_planes(
+    sections: Res<SectionPlanes>,
+    camera_q: Query<&PanOrbitCamera, With<ActiveViewport>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok(pan_orbit) = camera_q.get_single() else {
+        return;
+    };
+    for (index, plane) in sections.planes.iter().enumerate() {
+        let color = match (index == sections.active, plane.enabled) {
+            (_, false) => GRAY_500,
+            (true, true) => ORANGE_400,
+            (false, true) => AMBER_200,
+        };
+        let work_plane = plane.work_plane();
+        let half = pan_orbit.radius * 0.2;
+        let corners = [Vec2::new(-half, -half), Vec2::new(half, -half), Vec2::new(half, half), Vec2::new(-half, half)]
+            .map(|corner| work_plane.to_world(corner));
+        gizmos.linestrip(corners.into_iter().chain([corners[0]]), color);
+        let (start, end) = handle_segment(plane, pan_orbit);
+        gizmos.arrow(start, end, color);
+    }
+}
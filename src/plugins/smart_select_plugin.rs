@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::part::components::{Part, PartSelection};
+use crate::part::mouse_part_systems::set_part_selected;
+use crate::part::selection::{SelectionOp, Topology};
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::ui::EditorMode;
+
+// Command, key, needs Shift and shortcut text, all with Ctrl held
+const SELECT_SHORTCUTS: [(SelectionOp, KeyCode, bool, &str); 6] = [
+    (SelectionOp::All, KeyCode::KeyA, false, "Ctrl+A"),
+    (SelectionOp::None, KeyCode::KeyA, true, "Ctrl+Shift+A"),
+    (SelectionOp::Invert, KeyCode::KeyI, false, "Ctrl+I"),
+    (SelectionOp::Grow, KeyCode::NumpadAdd, false, "Ctrl+Numpad +"),
+    (SelectionOp::Shrink, KeyCode::NumpadSubtract, false, "Ctrl+Numpad -"),
+    (SelectionOp::Connected, KeyCode::KeyL, false, "Ctrl+L"),
+];
+
+/// Selection commands working over the topology of each part: select all, none or the
+/// inverse, grow and shrink the selection, select connected, coplanar or same-normal faces,
+/// and edge loops and rings. From the Edit menu, Ctrl shortcuts or `/select <command>`.
+pub struct SmartSelectPlugin;
+
+impl Plugin for SmartSelectPlugin {
+    fn build(&self, app: &mut App) {
+        for op in SelectionOp::ALL {
+            let shortcut = SELECT_SHORTCUTS.iter()
+                .find(|(shortcut_op, ..)| *shortcut_op == op)
+                .map(|(_, _, _, text)| *text);
+            app.add_menu_item(Menu::Edit, &format!("Select: {}", op.label()), shortcut, &format!("/select {}", op.name()));
+        }
+        app.add_systems(Update, (
+            select_shortcuts,
+            smart_select_console_system,
+        ).chain());
+    }
+}
+
+fn select_shortcuts(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+    mut console_commands: EventWriter<ConsoleCommand>,
+) {
+    if egui_contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    if !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    for (op, key, needs_shift, _) in SELECT_SHORTCUTS {
+        if keyboard.just_pressed(key) && shift == needs_shift {
+            console_commands.send_batch(ConsoleCommand::parse(&format!("/select {}", op.name())));
+        }
+    }
+}
+
+fn smart_select_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    mut parts: Query<(Entity, &mut Part, Has<PartSelection>)>,
+) {
+    for command in events.read().filter(|command| command.name == "select") {
+        // Region shapes belong to the region selection
+        let Some(op) = command.args.first().and_then(|arg| SelectionOp::from_name(arg)) else {
+            continue;
+        };
+        match *mode {
+            EditorMode::SelectFace if op.works_on_faces() => {
+                let mut count = 0;
+                for (_, mut part, _) in parts.iter_mut() {
+                    let topology = Topology::new(&part.faces);
+                    if let Some(faces) = topology.select_faces(op, &part.selected_faces) {
+                        count += faces.len();
+                        if faces != part.selected_faces {
+                            part.selected_faces = faces;
+                        }
+                    }
+                }
+                console.add_log(format!("{} faces selected", count));
+            }
+            EditorMode::SelectEdge if op.works_on_edges() => {
+                let mut count = 0;
+                for (_, mut part, _) in parts.iter_mut() {
+                    let topology = Topology::new(&part.faces);
+                    if let Some(edges) = topology.select_edges(op, &part.selected_edges) {
+                        count += edges.len();
+                        if edges != part.selected_edges {
+                            part.selected_edges = edges;
+                        }
+                    }
+                }
+                console.add_log(format!("{} edges selected", count));
+            }
+            EditorMode::SelectPart if matches!(op, SelectionOp::All | SelectionOp::None | SelectionOp::Invert) => {
+                let mut count = 0;
+                for (entity, _, selected) in parts.iter() {
+                    let select = match op {
+                        SelectionOp::All => true,
+                        SelectionOp::None => false,
+                        _ => !selected,
+                    };
+                    if select != selected {
+                        set_part_selected(&mut commands, entity, select);
+                    }
+                    count += select as usize;
+                }
+                console.add_log(format!("{} parts selected", count));
+            }
+            EditorMode::SelectFace | EditorMode::SelectEdge | EditorMode::SelectPart => {
+                let works_on = match (op.works_on_faces(), op.works_on_edges()) {
+                    (true, true) => "faces and edges",
+                    (true, false) => "faces",
+                    _ => "edges",
+                };
+                console.add_log(format!("Select {} works on {}", op.name(), works_on));
+            }
+            _ => console.add_log("Switch to face, edge or part selection mode to use the selection commands"),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use bevy::{color::palettes::tailwind::*, input::common_conditions::input_just_pressed, picking::pointer::PointerInteraction, prelude::*};
+
+use crate::part::components::Face;
+use crate::tools::grid::WorkPlane;
+use crate::tools::snapping::{face_targets, snap_point, Snap, SnapKind, SnapSettings, SNAP_TOLERANCE};
+use crate::tools::units::DocumentUnits;
+use crate::ui::{output_console::{ConsoleCommand, OutputConsole}, EditorMode};
+use crate::view::viewports::{viewport_cursor, ActiveViewport};
+use super::grid_plugin::{update_grid_state, GridState};
+
+// Cosine above which a face counts as parallel to the grid plane
+const PARALLEL_COS: f32 = 0.999;
+
+/// Snapping service for the tools that place or move geometry. Each frame it snaps the
+/// cursor to vertices, edge midpoints, face centres, axes and the grid, and draws an
+/// indicator for the kind of snap. F9 turns snapping on and off.
+pub struct SnapPlugin;
+
+impl Plugin for SnapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapSettings>()
+            .init_resource::<SnapState>()
+            .add_systems(Update, (
+                toggle_snapping.run_if(input_just_pressed(KeyCode::F9)),
+                snap_console_system,
+                clear_snap.run_if(resource_changed::<EditorMode>),
+                update_snap.after(update_grid_state).run_if(placement_mode),
+                draw_snap_indicator.run_if(placement_mode),
+            ).chain());
+    }
+}
+
+/// Snapped cursor of the current frame and the context tools give for it
+#[derive(Resource, Default)]
+pub struct SnapState {
+    pub cursor: Option<Snap>,
+    /// Face under the cursor, if any
+    pub hovered_face: Option<Entity>,
+    /// Start point of the current operation, used for axis snapping and to offset the work plane
+    pub reference: Option<Vec3>,
+    /// Parts whose geometry is ignored, e.g. the ones being moved
+    pub exclude: Vec<Entity>,
+}
+
+/// Modes that pick points in space
+pub fn placement_mode(mode: Res<EditorMode>) -> bool {
+    matches!(*mode, EditorMode::Measure | EditorMode::MovePart)
+}
+
+fn toggle_snapping(mut settings: ResMut<SnapSettings>, mut console: ResMut<OutputConsole>) {
+    settings.enabled = !settings.enabled;
+    console.add_log(format!("Snapping {}", if settings.enabled { "on" } else { "off" }));
+}
+
+fn clear_snap(mut state: ResMut<SnapState>) {
+    *state = SnapState::default();
+}
+
+pub fn update_snap(
+    settings: Res<SnapSettings>,
+    grid: Res<GridState>,
+    pointers: Query<&PointerInteraction>,
+    faces: Query<(&Face, &GlobalTransform, &Parent)>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<ActiveViewport>>,
+    windows: Query<&Window>,
+    mut state: ResMut<SnapState>,
+) {
+    state.cursor = None;
+    state.hovered_face = None;
+    let (Ok((camera, camera_transform)), Ok(window)) = (camera_q.get_single(), windows.get_single()) else {
+        return;
+    };
+    let Some(ray) = window.cursor_position().and_then(|cursor| camera.viewport_to_world(camera_transform, viewport_cursor(camera, cursor)).ok()) else {
+        return;
+    };
+
+    let exclude = state.exclude.clone();
+    let is_excluded = |entity: Entity| faces.get(entity).is_ok_and(|(_, _, parent)| exclude.contains(&parent.get()));
+
+    // Nearest face that is not part of the excluded part, otherwise the grid plane
+    let hit = pointers.iter()
+        .find_map(|interaction| interaction.iter().find(|(entity, _)| faces.contains(*entity) && !is_excluded(*entity)))
+        .and_then(|(entity, hit)| hit.position.zip(hit.normal).map(|(point, normal)| (*entity, point, normal)));
+    let (point, plane) = match hit {
+        // Faces parallel to the grid snap to the grid shown, offset to the face
+        Some((entity, point, normal)) if normal.dot(grid.plane.normal).abs() > PARALLEL_COS => {
+            state.hovered_face = Some(entity);
+            (point, grid.plane.offset_to(point))
+        }
+        Some((entity, point, normal)) => {
+            state.hovered_face = Some(entity);
+            (point, WorkPlane::through_point(point, normal))
+        }
+        None => {
+            // Operations that started off the grid continue on a parallel plane through their start
+            let plane = state.reference.map_or(grid.plane, |reference| grid.plane.offset_to(reference));
+            let Some(distance) = ray.intersect_plane(plane.origin, InfinitePlane3d::new(plane.normal)) else {
+                return;
+            };
+            (ray.get_point(distance), plane)
+        }
+    };
+
+    let targets: Vec<_> = faces.iter()
+        .filter(|(_, _, parent)| !exclude.contains(&parent.get()))
+        .flat_map(|(face, transform, _)| {
+            let vertices: Vec<Vec3> = face.vertices.iter().map(|v| transform.transform_point(v.coordinates)).collect();
+            face_targets(&vertices)
+        })
+        .collect();
+    let tolerance = camera_transform.translation().distance(point) * SNAP_TOLERANCE;
+
+    state.cursor = Some(snap_point(point, &plane, grid.level.minor, &targets, state.reference, &settings, tolerance));
+}
+
+fn draw_snap_indicator(
+    state: Res<SnapState>,
+    camera_q: Query<&GlobalTransform, With<ActiveViewport>>,
+    mut gizmos: Gizmos,
+) {
+    let (Some(snap), Ok(camera)) = (state.cursor, camera_q.get_single()) else {
+        return;
+    };
+    let size = camera.translation().distance(snap.point) * SNAP_TOLERANCE * 0.5;
+    // Marker outlines face the camera
+    let facing = Isometry3d::new(snap.point, camera.rotation());
+
+    match snap.kind {
+        Some(SnapKind::Vertex) => {
+            gizmos.rect(facing, Vec2::splat(size * 2.0), YELLOW_400);
+        }
+        Some(SnapKind::Midpoint) => {
+            gizmos.circle(facing, size, YELLOW_400).resolution(3);
+        }
+        Some(SnapKind::FaceCenter) => {
+            gizmos.circle(facing, size, YELLOW_400);
+        }
+        Some(SnapKind::Grid) => {
+            gizmos.cross(facing, size, CYAN_400);
+        }
+        Some(SnapKind::Axis) => {
+            let axis = snap.axis.unwrap_or(Vec3::X);
+            let color = if axis == Vec3::X { RED_500 } else if axis == Vec3::Y { GREEN_500 } else { BLUE_500 };
+            if let Some(reference) = state.reference {
+                gizmos.line(reference, snap.point, color);
+            }
+            gizmos.cross(facing, size, color);
+        }
+        None => {
+            gizmos.sphere(facing, size * 0.5, GRAY_400);
+        }
+    }
+}
+
+fn snap_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut settings: ResMut<SnapSettings>,
+    grid: Res<GridState>,
+    units: Res<DocumentUnits>,
+) {
+    for command in events.read().filter(|command| command.name == "snap") {
+        let args: Vec<&str> = command.args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["on"] => settings.enabled = true,
+            ["off"] => settings.enabled = false,
+            ["grid", setting @ ("on" | "off")] => settings.grid = *setting == "on",
+            [] => {}
+            _ => {
+                console.add_log("Usage: /snap [on|off|grid <on|off>]");
+                continue;
+            }
+        }
+        let grid_snap = if settings.grid {
+            format!("every {} (the minor grid spacing)", units.format_length(grid.level.minor))
+        } else {
+            "off".to_string()
+        };
+        console.add_log(format!("Snapping {}, grid {}", if settings.enabled { "on" } else { "off" }, grid_snap));
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_egui::EguiContexts;
+
+use crate::part::components::{Face, Part};
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::view::standard_views::{fit_bounds, OrbitPose, StandardView, ViewTransition, TRANSITION_SECONDS};
+use crate::view::viewports::ActiveViewport;
+use crate::view::PanOrbitCamera;
+
+// Label, key, needs Ctrl, shortcut text and the command it runs
+const VIEW_SHORTCUTS: [(&str, KeyCode, bool, &str, &str); 10] = [
+    ("Front", KeyCode::Numpad1, false, "Numpad 1", "/view front"),
+    ("Back", KeyCode::Numpad1, true, "Ctrl+Numpad 1", "/view back"),
+    ("Right", KeyCode::Numpad3, false, "Numpad 3", "/view right"),
+    ("Left", KeyCode::Numpad3, true, "Ctrl+Numpad 3", "/view left"),
+    ("Top", KeyCode::Numpad7, false, "Numpad 7", "/view top"),
+    ("Bottom", KeyCode::Numpad7, true, "Ctrl+Numpad 7", "/view bottom"),
+    ("Isometric", KeyCode::Numpad0, false, "Numpad 0", "/view iso"),
+    ("Perspective / Orthographic", KeyCode::Numpad5, false, "Numpad 5", "/view projection"),
+    ("Zoom to fit all", KeyCode::Home, false, "Home", "/view fit"),
+    ("Zoom to selection", KeyCode::NumpadDecimal, false, "Numpad .", "/view fit selection"),
+];
+
+/// Standard views, perspective/orthographic projection and zoom to fit, from the View menu,
+/// numpad shortcuts or the `/view` command. View changes are animated.
+pub struct ViewPlugin;
+
+impl Plugin for ViewPlugin {
+    fn build(&self, app: &mut App) {
+        for (label, _, _, shortcut, command) in VIEW_SHORTCUTS {
+            app.add_menu_item(Menu::View, label, Some(shortcut), command);
+        }
+        app.add_systems(Update, (
+            view_shortcuts,
+            view_console_system,
+            animate_view_transition,
+            sync_orthographic_zoom,
+        ).chain());
+    }
+}
+
+fn view_shortcuts(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut egui_contexts: EguiContexts,
+    mut console_commands: EventWriter<ConsoleCommand>,
+) {
+    if egui_contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    let ctrl = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    for (_, key, needs_ctrl, _, command) in VIEW_SHORTCUTS {
+        if keyboard.just_pressed(key) && ctrl == needs_ctrl {
+            console_commands.send_batch(ConsoleCommand::parse(command));
+        }
+    }
+}
+
+fn view_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut commands: Commands,
+    mut camera_q: Query<(Entity, &PanOrbitCamera, &Camera, &mut Projection), With<ActiveViewport>>,
+    faces: Query<(&Face, &GlobalTransform, &Parent)>,
+    parts: Query<&Part>,
+) {
+    for command in events.read().filter(|command| command.name == "view") {
+        let Ok((entity, pan_orbit, camera, mut projection)) = camera_q.get_single_mut() else {
+            return;
+        };
+        let current = OrbitPose::from(pan_orbit);
+        let mut target = current;
+
+        match command.args.first().map(String::as_str) {
+            Some("projection" | "persp" | "ortho") => {
+                let orthographic = match command.args[0].as_str() {
+                    "persp" => false,
+                    "ortho" => true,
+                    _ => !matches!(*projection, Projection::Orthographic(_)),
+                };
+                *projection = if orthographic { orthographic_projection(pan_orbit.radius) } else { Projection::default() };
+                console.add_log(if orthographic { "Orthographic projection" } else { "Perspective projection" });
+                continue;
+            }
+            Some("fit") => {
+                let selection_only = command.args.get(1).is_some_and(|arg| arg == "selection");
+                let points = fit_points(&faces, &parts, selection_only);
+                if points.is_empty() {
+                    console.add_log(if selection_only { "Nothing selected to zoom to" } else { "Nothing to zoom to" });
+                    continue;
+                }
+                let min = points.iter().fold(Vec3::MAX, |min, p| min.min(*p));
+                let max = points.iter().fold(Vec3::MIN, |max, p| max.max(*p));
+                let aspect = camera.logical_viewport_size().map_or(1.0, |size| size.x / size.y.max(1.0));
+                (target.focus, target.radius) = fit_bounds(min, max, perspective_fov(), aspect);
+            }
+            Some(name) => match StandardView::from_name(name) {
+                Some(view) => (target.yaw, target.pitch) = view.yaw_pitch(),
+                None => {
+                    console.add_log(format!("Unknown view '{}'", name));
+                    continue;
+                }
+            },
+            None => {
+                console.add_log("Usage: /view <front|back|top|bottom|left|right|iso|projection|fit [selection]>");
+                continue;
+            }
+        }
+        commands.entity(entity).insert(ViewTransition::new(current, target, TRANSITION_SECONDS));
+    }
+}
+
+// World space vertices of all faces, or only of the selected ones
+fn fit_points(faces: &Query<(&Face, &GlobalTransform, &Parent)>, parts: &Query<&Part>, selection_only: bool) -> Vec<Vec3> {
+    faces.iter()
+        .filter(|(face, _, parent)| {
+            !selection_only || parts.get(parent.get()).is_ok_and(|part| part.selected_faces.contains(face))
+        })
+        .flat_map(|(face, transform, _)| face.vertices.iter().map(|v| transform.transform_point(v.coordinates)).collect::<Vec<_>>())
+        .collect()
+}
+
+fn animate_view_transition(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut camera_q: Query<(Entity, &mut PanOrbitCamera, &mut Transform, &mut ViewTransition)>,
+) {
+    for (entity, mut pan_orbit, mut transform, mut transition) in camera_q.iter_mut() {
+        let (pose, finished) = transition.step(time.delta_secs());
+        pan_orbit.focus = pose.focus;
+        pan_orbit.radius = pose.radius;
+        pan_orbit.yaw = pose.yaw;
+        pan_orbit.pitch = pose.pitch;
+        pan_orbit.apply_to(&mut transform);
+        if finished {
+            commands.entity(entity).remove::<ViewTransition>();
+        }
+    }
+}
+
+// Orthographic views zoom by changing their height instead of moving the camera
+fn sync_orthographic_zoom(mut camera_q: Query<(&PanOrbitCamera, &mut Projection), Changed<PanOrbitCamera>>) {
+    for (pan_orbit, mut projection) in camera_q.iter_mut() {
+        if matches!(*projection, Projection::Orthographic(_)) {
+            *projection = orthographic_projection(pan_orbit.radius);
+        }
+    }
+}
+
+/// Shows at the focus what the perspective view shows at the same radius
+pub fn orthographic_projection(radius: f32) -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        scaling_mode: ScalingMode::FixedVertical { viewport_height: 2.0 * radius * (perspective_fov() * 0.5).tan() },
+        // Zoomed in the camera can end up inside the model, keep what is behind it
+        near: -1000.0,
+        far: 1000.0,
+        ..OrthographicProjection::default_3d()
+    })
+}
+
+fn perspective_fov() -> f32 {
+    PerspectiveProjection::default().fov
+}
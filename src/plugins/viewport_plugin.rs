@@ -0,0 +1,194 @@
+use bevy::{
+    prelude::*,
+    render::{camera::Viewport, view::RenderLayers},
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::plugins::view_plugin::orthographic_projection;
+use crate::ui::menu::{Menu, MenuAppExt};
+use crate::ui::output_console::{ConsoleCommand, OutputConsole};
+use crate::ui::ui_elements::{LEFT_TOOLBAR_WIDTH, RIGHT_PANEL_WIDTH, TOP_BAR_HEIGHT};
+use crate::view::display_mode::ViewportIndex;
+use crate::view::viewports::{initial_view, viewport_rects, ActiveViewport, ViewportLayout};
+use crate::view::{spawn_camera, PanOrbitCamera};
+
+/// Single, two and four viewport layouts. Each viewport has its own camera and display mode,
+/// the extra ones start as orthographic top, front and right views. The viewport under the
+/// cursor is the active one, navigation, picking tools and view commands work in it.
+pub struct ViewportPlugin;
+
+impl Plugin for ViewportPlugin {
+    fn build(&self, app: &mut App) {
+        for layout in ViewportLayout::ALL {
+            app.add_menu_item(Menu::Window, layout.label(), None, &format!("/viewports {}", layout.count()));
+        }
+        app.init_resource::<ViewportLayout>()
+            .add_systems(Startup, spawn_ui_camera)
+            .add_systems(Update, (
+                viewport_console_system,
+                apply_viewport_layout,
+                update_active_viewport,
+                draw_viewport_frames,
+            ).chain());
+    }
+}
+
+// bevy_ui would otherwise lay itself out in the viewport of one of the 3D cameras
+fn spawn_ui_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Camera { order: 100, clear_color: ClearColorConfig::None, ..default() },
+        // Renders nothing but the UI, which also keeps mesh picking away from it
+        RenderLayers::none(),
+        IsDefaultUiCamera,
+    ));
+}
+
+fn viewport_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut layout: ResMut<ViewportLayout>,
+) {
+    for command in events.read().filter(|command| command.name == "viewports") {
+        match command.args.first() {
+            Some(name) => match ViewportLayout::from_name(name) {
+                Some(new_layout) => {
+                    *layout = new_layout;
+                    console.add_log(new_layout.label());
+                }
+                None => console.add_log(format!("Unknown layout '{}', use 1, 2 or 4", name)),
+            },
+            None => console.add_log(layout.label()),
+        }
+    }
+}
+
+// Window area left over by the toolbars and panels, in logical pixels
+fn viewport_area(window: &Window) -> Rect {
+    Rect::new(
+        LEFT_TOOLBAR_WIDTH,
+        TOP_BAR_HEIGHT,
+        (window.width() - RIGHT_PANEL_WIDTH).max(LEFT_TOOLBAR_WIDTH + 1.0),
+        window.height().max(TOP_BAR_HEIGHT + 1.0),
+    )
+}
+
+fn apply_viewport_layout(
+    mut commands: Commands,
+    layout: Res<ViewportLayout>,
+    windows: Query<&Window>,
+    mut cameras: Query<(Entity, &ViewportIndex, &mut Camera, &PanOrbitCamera)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let rects = viewport_rects(*layout, viewport_area(window));
+
+    for (entity, index, _, _) in cameras.iter() {
+        if index.0 >= rects.len() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for (index, _) in rects.iter().enumerate() {
+        if cameras.iter().any(|(_, existing, _, _)| existing.0 == index) {
+            continue;
+        }
+        let Some(view) = initial_view(index) else {
+            continue;
+        };
+        let (camera_3d, mut transform, mut pan_orbit, display_mode, _) = spawn_camera();
+        // New views look at what the main view looks at
+        if let Some((_, _, _, main)) = cameras.iter().find(|(_, existing, _, _)| existing.0 == 0) {
+            pan_orbit.focus = main.focus;
+            pan_orbit.radius = main.radius;
+        }
+        (pan_orbit.yaw, pan_orbit.pitch) = view.yaw_pitch();
+        pan_orbit.apply_to(&mut transform);
+        commands.spawn((
+            camera_3d,
+            Camera { order: index as isize, ..default() },
+            orthographic_projection(pan_orbit.radius),
+            transform,
+            pan_orbit,
+            display_mode,
+            ViewportIndex(index),
+        ));
+    }
+
+    let scale = window.scale_factor();
+    for (_, index, mut camera, _) in cameras.iter_mut() {
+        let Some(rect) = rects.get(index.0) else {
+            continue;
+        };
+        let viewport = Viewport {
+            physical_position: (rect.min * scale).as_uvec2(),
+            physical_size: (rect.size() * scale).as_uvec2().max(UVec2::ONE),
+            ..default()
+        };
+        // Only write on a change, a changed camera recomputes its projection
+        let unchanged = camera.viewport.as_ref().is_some_and(|current| {
+            current.physical_position == viewport.physical_position && current.physical_size == viewport.physical_size
+        });
+        if !unchanged {
+            camera.viewport = Some(viewport);
+        }
+    }
+}
+
+// The active viewport follows the cursor, but not in the middle of a drag
+fn update_active_viewport(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(Entity, &Camera, &ViewportIndex, Has<ActiveViewport>)>,
+) {
+    let hovered = windows.get_single().ok()
+        .and_then(|window| window.cursor_position())
+        .filter(|_| mouse.get_pressed().next().is_none())
+        .and_then(|cursor| {
+            cameras.iter().find(|(_, camera, _, _)| {
+                camera.logical_viewport_rect().is_some_and(|rect| rect.contains(cursor))
+            })
+        });
+    let active = cameras.iter().find(|(_, _, _, active)| *active);
+    // Without a viewport under the cursor the main one takes over from a closed viewport
+    let target = match (hovered, active) {
+        (Some((entity, _, _, _)), _) => entity,
+        (None, Some((entity, _, _, _))) => entity,
+        (None, None) => match cameras.iter().find(|(_, _, index, _)| index.0 == 0) {
+            Some((entity, _, _, _)) => entity,
+            None => return,
+        },
+    };
+    if active.is_some_and(|(entity, _, _, _)| entity == target) {
+        return;
+    }
+    if let Some((entity, _, _, _)) = active {
+        commands.entity(entity).remove::<ActiveViewport>();
+    }
+    commands.entity(target).insert(ActiveViewport);
+}
+
+// Borders between the viewports with the active one highlighted
+fn draw_viewport_frames(
+    layout: Res<ViewportLayout>,
+    mut egui_contexts: EguiContexts,
+    cameras: Query<(&Camera, Has<ActiveViewport>), With<ViewportIndex>>,
+) {
+    if *layout == ViewportLayout::Single {
+        return;
+    }
+    let painter = egui_contexts.ctx_mut().layer_painter(egui::LayerId::background());
+    for (camera, active) in cameras.iter() {
+        let Some(rect) = camera.logical_viewport_rect() else {
+            continue;
+        };
+        let rect = egui::Rect::from_min_max(egui::pos2(rect.min.x, rect.min.y), egui::pos2(rect.max.x, rect.max.y)).shrink(0.5);
+        let stroke = if active {
+            egui::Stroke::new(1.5, egui::Color32::from_rgb(90, 150, 220))
+        } else {
+            egui::Stroke::new(1.0, egui::Color32::from_gray(40))
+        };
+        painter.rect_stroke(rect, 0.0, stroke);
+    }
+}
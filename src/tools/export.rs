@@ -0,0 +1,23 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use super::polygon::triangulate;
+
+/// ASCII STL of the given face polygons, concave ones included
+pub fn stl_ascii(name: &str, polygons: &[Vec<Vec3>]) -> String {
+    let mut stl = format!("solid {}\n", name);
+    for polygon in polygons {
+        for [a, b, c] in triangulate(polygon).into_iter().map(|triangle| triangle.map(|i| polygon[i])) {
+            let normal = (b - a).cross(c - a).normalize_or_zero();
+            let _ = writeln!(stl, "  facet normal {:e} {:e} {:e}", normal.x, normal.y, normal.z);
+            stl.push_str("    outer loop\n");
+            for vertex in [a, b, c] {
+                let _ = writeln!(stl, "      vertex {:e} {:e} {:e}", vertex.x, vertex.y, vertex.z);
+            }
+            stl.push_str("    endloop\n  endfacet\n");
+        }
+    }
+    let _ = writeln!(stl, "endsolid {}", name);
+    stl
+}
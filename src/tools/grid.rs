@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+/// Roughly how many minor cells span the view when the grid adapts to zoom
+const TARGET_CELLS: f32 = 40.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridPlane {
+    XZ,
+    XY,
+    YZ,
+    /// Plane of the first selected face, XZ when nothing is selected
+    SelectedFace,
+}
+
+impl GridPlane {
+    pub const ALL: [GridPlane; 4] = [GridPlane::XZ, GridPlane::XY, GridPlane::YZ, GridPlane::SelectedFace];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GridPlane::XZ => "XZ (ground)",
+            GridPlane::XY => "XY (front)",
+            GridPlane::YZ => "YZ (side)",
+            GridPlane::SelectedFace => "Selected face",
+        }
+    }
+}
+
+/// A plane with an in-plane coordinate system, the grid is drawn and snapped in it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkPlane {
+    pub origin: Vec3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub normal: Vec3,
+}
+
+impl Default for WorkPlane {
+    fn default() -> Self {
+        WorkPlane::XZ
+    }
+}
+
+impl WorkPlane {
+    pub const XZ: WorkPlane = WorkPlane { origin: Vec3::ZERO, u: Vec3::X, v: Vec3::Z, normal: Vec3::Y };
+    pub const XY: WorkPlane = WorkPlane { origin: Vec3::ZERO, u: Vec3::X, v: Vec3::Y, normal: Vec3::Z };
+    pub const YZ: WorkPlane = WorkPlane { origin: Vec3::ZERO, u: Vec3::Z, v: Vec3::Y, normal: Vec3::X };
+
+    /// Plane through `origin` with the u axis along `direction`
+    pub fn new(origin: Vec3, normal: Vec3, direction: Vec3) -> Self {
+        let normal = normal.normalize_or(Vec3::Y);
+        let u = (direction - normal * direction.dot(normal))
+            .try_normalize()
+            .unwrap_or_else(|| normal.any_orthonormal_pair().0);
+        WorkPlane { origin, u, v: normal.cross(u), normal }
+    }
+
+    /// Plane with `normal` through `point`, with its origin where the world origin projects onto it
+    /// so grids on parallel planes line up
+    pub fn through_point(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize_or(Vec3::Y);
+        let (u, v) = normal.any_orthonormal_pair();
+        WorkPlane { origin: normal * point.dot(normal), u, v, normal }
+    }
+
+    /// The same plane moved along its normal to contain `point`
+    pub fn offset_to(&self, point: Vec3) -> Self {
+        WorkPlane { origin: self.origin + self.normal * (point - self.origin).dot(self.normal), ..*self }
+    }
+
+    pub fn to_local(self, point: Vec3) -> Vec2 {
+        let relative = point - self.origin;
+        Vec2::new(relative.dot(self.u), relative.dot(self.v))
+    }
+
+    pub fn to_world(self, local: Vec2) -> Vec3 {
+        self.origin + self.u * local.x + self.v * local.y
+    }
+
+    /// Nearest grid intersection, keeping the offset of the point from the plane
+    pub fn snap_to_grid(&self, point: Vec3, spacing: f32) -> Vec3 {
+        let height = (point - self.origin).dot(self.normal);
+        let local = (self.to_local(point) / spacing).round() * spacing;
+        self.to_world(local) + self.normal * height
+    }
+}
+
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct GridSettings {
+    pub visible: bool,
+    pub plane: GridPlane,
+    /// Minor line spacing in model units, the smallest spacing when adaptive
+    pub spacing: f32,
+    /// Every n-th line is a major line
+    pub major_every: u32,
+    /// Half size of the grid in major cells
+    pub extent: u32,
+    /// Change the spacing with zoom so the line density stays about the same
+    pub adaptive: bool,
+    pub show_axes: bool,
+    pub show_labels: bool,
+    pub minor_color: Color,
+    pub major_color: Color,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        GridSettings {
+            visible: true,
+            plane: GridPlane::XZ,
+            spacing: 0.1,
+            major_every: 10,
+            extent: 5,
+            adaptive: true,
+            show_axes: true,
+            show_labels: true,
+            minor_color: Color::srgba(0.55, 0.55, 0.55, 0.35),
+            major_color: Color::srgba(0.7, 0.7, 0.7, 0.7),
+        }
+    }
+}
+
+/// Spacings of the grid at the current zoom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridLevel {
+    pub minor: f32,
+    pub major: f32,
+    /// Opacity of the minor lines, they fade out before the next level takes over
+    pub minor_alpha: f32,
+}
+
+impl GridSettings {
+    pub fn level(&self, view_distance: f32) -> GridLevel {
+        let step = self.major_every.max(2) as f32;
+        if !self.adaptive || view_distance <= 0.0 {
+            return GridLevel { minor: self.spacing, major: self.spacing * step, minor_alpha: 1.0 };
+        }
+
+        // Continuous level in powers of the major step, never finer than the base spacing
+        let level = ((view_distance / TARGET_CELLS) / self.spacing).log(step).max(0.0);
+        let minor = self.spacing * step.powi(level.floor() as i32);
+        GridLevel { minor, major: minor * step, minor_alpha: 1.0 - level.fract() }
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+
+/// Something picked in the viewport for measuring, in world coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeasurePick {
+    Vertex(Vec3),
+    Edge { start: Vec3, end: Vec3 },
+    /// A face with the point that was clicked on it
+    Face { point: Vec3, normal: Vec3, vertices: Vec<Vec3> },
+}
+
+impl MeasurePick {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MeasurePick::Vertex(_) => "vertex",
+            MeasurePick::Edge { .. } => "edge",
+            MeasurePick::Face { .. } => "face",
+        }
+    }
+
+    fn direction(&self) -> Option<Vec3> {
+        match self {
+            MeasurePick::Edge { start, end } => (*end - *start).try_normalize(),
+            MeasurePick::Face { normal, .. } => normal.try_normalize(),
+            MeasurePick::Vertex(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// Closest points of the measured entities, the dimension line runs between them
+    pub from: Vec3,
+    pub to: Vec3,
+    pub distance: f32,
+    /// Edge length when a single edge was measured
+    pub length: Option<f32>,
+    /// Angle between two edges, two faces or an edge and a face, in radians from 0 to 90°
+    pub angle: Option<f32>,
+    /// Radius of the circle through three vertices, `from` is its centre and `to` the first vertex
+    pub radius: Option<f32>,
+}
+
+impl Measurement {
+    pub fn delta(&self) -> Vec3 {
+        self.to - self.from
+    }
+}
+
+pub fn measure_single(pick: &MeasurePick) -> Option<Measurement> {
+    match pick {
+        MeasurePick::Edge { start, end } => Some(Measurement {
+            from: *start,
+            to: *end,
+            distance: start.distance(*end),
+            length: Some(start.distance(*end)),
+            angle: None,
+            radius: None,
+        }),
+        _ => None,
+    }
+}
+
+pub fn measure_pair(a: &MeasurePick, b: &MeasurePick) -> Measurement {
+    let (from, to) = closest_points(a, b);
+
+    // Between two edges or two faces the angle of their lines or planes, between an edge and
+    // a face the angle of the edge to the plane. Neither depends on which way edges run or
+    // normals point, so it is at most 90°.
+    let angle = a.direction().zip(b.direction()).map(|(da, db)| {
+        let cos = da.dot(db).abs().clamp(0.0, 1.0);
+        match (a, b) {
+            (MeasurePick::Edge { .. }, MeasurePick::Face { .. }) | (MeasurePick::Face { .. }, MeasurePick::Edge { .. }) => {
+                std::f32::consts::FRAC_PI_2 - cos.acos()
+            }
+            _ => cos.acos(),
+        }
+    });
+
+    Measurement {
+        from,
+        to,
+        distance: from.distance(to),
+        length: None,
+        angle,
+        radius: None,
+    }
+}
+
+/// Radius of the circle through three vertices, `None` for other picks or points in a line
+pub fn measure_radius(a: &MeasurePick, b: &MeasurePick, c: &MeasurePick) -> Option<Measurement> {
+    let (MeasurePick::Vertex(a), MeasurePick::Vertex(b), MeasurePick::Vertex(c)) = (a, b, c) else {
+        return None;
+    };
+    let center = circle_center(*a, *b, *c)?;
+    let radius = center.distance(*a);
+    Some(Measurement {
+        from: center,
+        to: *a,
+        distance: radius,
+        length: None,
+        angle: None,
+        radius: Some(radius),
+    })
+}
+
+/// Centre of the circle through three points, `None` when they are in a line
+pub fn circle_center(a: Vec3, b: Vec3, c: Vec3) -> Option<Vec3> {
+    let ab = b - a;
+    let ac = c - a;
+    let normal = ab.cross(ac);
+    let length_squared = normal.length_squared();
+    // The squared sine of the angle at `a`, also zero when two points coincide
+    if length_squared <= 1e-8 * ab.length_squared() * ac.length_squared() {
+        return None;
+    }
+    let offset = (normal.cross(ab) * ac.length_squared() + ac.cross(normal) * ab.length_squared()) / (2.0 * length_squared);
+    Some(a + offset)
+}
+
+/// Closest points between two picks, the first point lies on `a`
+pub fn closest_points(a: &MeasurePick, b: &MeasurePick) -> (Vec3, Vec3) {
+    match (a, b) {
+        (MeasurePick::Vertex(p), MeasurePick::Vertex(q)) => (*p, *q),
+        (MeasurePick::Vertex(p), MeasurePick::Edge { start, end }) => (*p, closest_on_segment(*p, *start, *end)),
+        (MeasurePick::Vertex(p), MeasurePick::Face { normal, vertices, .. }) => {
+            (*p, closest_on_polygon(*p, *normal, vertices))
+        }
+        (MeasurePick::Edge { start: a0, end: a1 }, MeasurePick::Edge { start: b0, end: b1 }) => {
+            closest_between_segments(*a0, *a1, *b0, *b1)
+        }
+        (MeasurePick::Edge { start, end }, MeasurePick::Face { normal, vertices, .. }) => {
+            let mut candidates = vec![
+                (*start, closest_on_polygon(*start, *normal, vertices)),
+                (*end, closest_on_polygon(*end, *normal, vertices)),
+            ];
+            candidates.extend(polygon_edges(vertices).map(|(p0, p1)| closest_between_segments(*start, *end, p0, p1)));
+            nearest(candidates)
+        }
+        (MeasurePick::Face { normal: na, vertices: va, .. }, MeasurePick::Face { normal: nb, vertices: vb, .. }) => {
+            let mut candidates: Vec<(Vec3, Vec3)> = va.iter().map(|p| (*p, closest_on_polygon(*p, *nb, vb))).collect();
+            candidates.extend(vb.iter().map(|q| (closest_on_polygon(*q, *na, va), *q)));
+            for (a0, a1) in polygon_edges(va) {
+                candidates.extend(polygon_edges(vb).map(|(b0, b1)| closest_between_segments(a0, a1, b0, b1)));
+            }
+            nearest(candidates)
+        }
+        // Remaining combinations are the ones above with the arguments swapped
+        _ => {
+            let (q, p) = closest_points(b, a);
+            (p, q)
+        }
+    }
+}
+
+fn nearest(candidates: Vec<(Vec3, Vec3)>) -> (Vec3, Vec3) {
+    candidates.into_iter()
+        .min_by(|(a0, a1), (b0, b1)| a0.distance_squared(*a1).total_cmp(&b0.distance_squared(*b1)))
+        .unwrap_or_default()
+}
+
+fn polygon_edges(vertices: &[Vec3]) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+    (0..vertices.len()).map(move |i| (vertices[i], vertices[(i + 1) % vertices.len()]))
+}
+
+pub fn closest_on_segment(point: Vec3, start: Vec3, end: Vec3) -> Vec3 {
+    let direction = end - start;
+    let length_squared = direction.length_squared();
+    if length_squared < f32::EPSILON {
+        return start;
+    }
+    let t = ((point - start).dot(direction) / length_squared).clamp(0.0, 1.0);
+    start + direction * t
+}
+
+/// Closest point on a planar convex polygon
+pub fn closest_on_polygon(point: Vec3, normal: Vec3, vertices: &[Vec3]) -> Vec3 {
+    let Some(origin) = vertices.first() else {
+        return point;
+    };
+    let normal = normal.normalize_or_zero();
+    let projected = point - normal * (point - *origin).dot(normal);
+
+    let inside = polygon_edges(vertices).all(|(p0, p1)| (p1 - p0).cross(projected - p0).dot(normal) >= -1e-6);
+    if inside {
+        return projected;
+    }
+    polygon_edges(vertices)
+        .map(|(p0, p1)| closest_on_segment(point, p0, p1))
+        .min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+        .unwrap_or(*origin)
+}
+
+pub fn closest_between_segments(a0: Vec3, a1: Vec3, b0: Vec3, b1: Vec3) -> (Vec3, Vec3) {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let r = a0 - b0;
+    let a = da.length_squared();
+    let e = db.length_squared();
+    let f = db.dot(r);
+
+    if a < f32::EPSILON && e < f32::EPSILON {
+        return (a0, b0);
+    }
+    let (s, t) = if a < f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = da.dot(r);
+        if e < f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = da.dot(db);
+            let denominator = a * e - b * b;
+            let mut s = if denominator > f32::EPSILON { ((b * f - c * e) / denominator).clamp(0.0, 1.0) } else { 0.0 };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    (a0 + da * s, b0 + db * t)
+}
@@ -7,7 +7,7 @@ use bevy::{
 };
 use std::collections::HashSet;
 
-use super::{colors, vec3_rounded::Vec3Rounded,};
+use super::{colors, units::DocumentUnits};
 
 #[derive(Resource)]
 pub struct ToolResources {
@@ -16,9 +16,10 @@ pub struct ToolResources {
 }
 
 /*
-method takes a ref of a Bevy mesh and returns a map of unique vertices
+method takes a ref of a Bevy mesh and returns its unique vertices, positions that show the same
+coordinates at the document precision count as one
 */
-pub fn get_vertices(mesh: &Mesh) -> Vec<Vec3> {
+pub fn get_vertices(mesh: &Mesh, units: &DocumentUnits) -> Vec<Vec3> {
     let Some(VertexAttributeValues::Float32x3(raw_positions)) = 
         mesh.attribute(Mesh::ATTRIBUTE_POSITION)
     else {
@@ -27,11 +28,12 @@ pub fn get_vertices(mesh: &Mesh) -> Vec<Vec3> {
 
     let mut unique = HashSet::new();
     for pos in raw_positions {
-        let rounded = Vec3Rounded::from(pos.clone());
-        unique.insert(rounded);
+        unique.insert(units.round_point(Vec3::from_array(*pos)));
     }
 
-    unique.into_iter().map(|x| x.to_vec3()).collect()
+    unique.into_iter()
+        .map(|x| x.to_vec3() / units.length.model_to_unit(1.0))
+        .collect()
 }
 
 pub fn create_vertex_dummies(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>, mut meshes: ResMut<Assets<Mesh>>, points: &Vec<Vec3>) {
@@ -3,3 +3,25 @@ pub mod colors;
 pub mod vec3_rounded;
 pub mod components;
 pub mod systems;
+pub mod measure;
+pub mod units;
+pub mod snapping;
+pub mod grid;
+pub mod preferences;
+pub mod region_select;
+pub mod export;
+pub mod polygon;
+#[cfg(test)]
+pub mod test_measure;
+#[cfg(test)]
+pub mod test_units;
+#[cfg(test)]
+pub mod test_snapping;
+#[cfg(test)]
+pub mod test_grid;
+#[cfg(test)]
+pub mod test_preferences;
+#[cfg(test)]
+pub mod test_region_select;
+#[cfg(test)]
+pub mod test_export;
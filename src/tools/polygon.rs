@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use super::grid::WorkPlane;
+
+// Polygon helpers shared by the section caps, region selection, face meshes and the exporters
+
+/// Enclosed area, positive for a counter-clockwise polygon with y up
+pub fn signed_area(polygon: &[Vec2]) -> f32 {
+    (0..polygon.len()).map(|i| polygon[i].perp_dot(polygon[(i + 1) % polygon.len()])).sum::<f32>() * 0.5
+}
+
+/// Even-odd point in polygon test, for any simple polygon in either winding
+pub fn point_in_polygon(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Whether the segments `a b` and `c d` properly cross, touching does not count
+pub fn segments_cross(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let side = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+    let (d1, d2) = (side(a, b, c), side(a, b, d));
+    let (d3, d4) = (side(c, d, a), side(c, d, b));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Ear clipping of a counter-clockwise polygon, works for concave ones too
+pub fn ear_clip(polygon: &[Vec2]) -> Vec<[Vec2; 3]> {
+    let mut remaining: Vec<Vec2> = polygon.to_vec();
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (remaining[(i + n - 1) % n], remaining[i], remaining[(i + 1) % n]);
+            if (b - a).perp_dot(c - b) <= 0.0 {
+                return false;
+            }
+            // No other vertex inside, vertices shared through a bridge do not count
+            remaining.iter().all(|p| *p == a || *p == b || *p == c || !in_triangle(*p, a, b, c))
+        });
+        // Degenerate leftovers are fanned out rather than lost
+        let i = ear.unwrap_or(0);
+        triangles.push([remaining[(i + n - 1) % n], remaining[i], remaining[(i + 1) % n]]);
+        remaining.remove(i);
+    }
+    if remaining.len() == 3 {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+    triangles.retain(|[a, b, c]| (*b - *a).perp_dot(*c - *a).abs() > 1e-9);
+    triangles
+}
+
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(p - a) >= 0.0 && (c - b).perp_dot(p - b) >= 0.0 && (a - c).perp_dot(p - c) >= 0.0
+}
+
+/// Normal of a 3D polygon following its winding, whatever the shape. Its length is twice the area.
+pub fn newell_normal(polygon: &[Vec3]) -> Vec3 {
+    (0..polygon.len()).map(|i| polygon[i].cross(polygon[(i + 1) % polygon.len()])).sum()
+}
+
+/// Triangles of a planar 3D polygon as indices into it, wound like the polygon
+pub fn triangulate(polygon: &[Vec3]) -> Vec<[usize; 3]> {
+    let normal = newell_normal(polygon);
+    if polygon.len() < 3 || normal.length_squared() < 1e-12 {
+        return Vec::new();
+    }
+    // Seen from the normal the polygon runs counter-clockwise
+    let plane = WorkPlane::new(polygon[0], normal, polygon[1] - polygon[0]);
+    let local: Vec<Vec2> = polygon.iter().map(|point| plane.to_local(*point)).collect();
+    let index = |point: Vec2| local.iter().position(|p| *p == point).unwrap_or(0);
+    ear_clip(&local)
+        .into_iter()
+        .map(|triangle| triangle.map(index))
+        .collect()
+}
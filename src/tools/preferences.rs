@@ -0,0 +1,67 @@
+use std::{env, fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::view::navigation::NavigationScheme;
+
+/// Directory name below the platform config directory
+const APP_DIR: &str = "rustcad";
+const PREFERENCES_FILE: &str = "preferences.json";
+
+/// User preferences, shared by all documents and kept between sessions
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub navigation: NavigationScheme,
+}
+
+impl Preferences {
+    /// Reads the preferences file, falls back to the defaults if it is missing or unreadable
+    pub fn load() -> Self {
+        let Some(path) = preferences_path() else {
+            return Preferences::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Preferences::from_json(&text).unwrap_or_else(|err| {
+                warn!("Ignoring invalid preferences in {}: {}", path.display(), err);
+                Preferences::default()
+            }),
+            Err(_) => Preferences::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = preferences_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_json())
+    }
+
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("preferences serialize to JSON")
+    }
+}
+
+/// Per user config directory of the application, `%APPDATA%\rustcad` on Windows and
+/// `$XDG_CONFIG_HOME/rustcad` or `~/.config/rustcad` elsewhere
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|base| base.join(APP_DIR))
+}
+
+fn preferences_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(PREFERENCES_FILE))
+}
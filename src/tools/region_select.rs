@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use super::polygon::{point_in_polygon, segments_cross, signed_area};
+
+/// Shape a selection drag draws
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegionShape {
+    #[default]
+    Box,
+    Lasso,
+}
+
+impl RegionShape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegionShape::Box => "box",
+            RegionShape::Lasso => "lasso",
+        }
+    }
+}
+
+/// Window selects what lies completely inside the region, crossing also what it touches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionMode {
+    Window,
+    Crossing,
+}
+
+/// How the picked items combine with the current selection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectOp {
+    #[default]
+    Replace,
+    Add,
+    Subtract,
+}
+
+/// Closed polygon in window coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionRegion {
+    pub points: Vec<Vec2>,
+    pub mode: RegionMode,
+}
+
+impl SelectionRegion {
+    /// Dragged to the right it is a window, to the left a crossing box
+    pub fn rectangle(start: Vec2, end: Vec2) -> Self {
+        let mode = if end.x >= start.x { RegionMode::Window } else { RegionMode::Crossing };
+        SelectionRegion {
+            points: vec![start, Vec2::new(end.x, start.y), end, Vec2::new(start.x, end.y)],
+            mode,
+        }
+    }
+
+    /// Drawn clockwise on screen it is a window, anticlockwise a crossing lasso
+    pub fn lasso(points: Vec<Vec2>) -> Self {
+        // Window y points down, so a positive area is clockwise as seen on screen
+        let mode = if signed_area(&points) >= 0.0 { RegionMode::Window } else { RegionMode::Crossing };
+        SelectionRegion { points, mode }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point_in_polygon(&self.points, point)
+    }
+
+    /// Whether an item with this outline gets selected. `closed` outlines are areas, faces,
+    /// the others polylines, edges. Points that could not be projected are `None`.
+    pub fn selects(&self, outline: &[Option<Vec2>], closed: bool) -> bool {
+        if outline.is_empty() || self.points.len() < 3 {
+            return false;
+        }
+        match self.mode {
+            RegionMode::Window => outline.iter().all(|point| point.is_some_and(|point| self.contains(point))),
+            RegionMode::Crossing => {
+                let points: Vec<Vec2> = outline.iter().flatten().copied().collect();
+                if points.iter().any(|point| self.contains(*point)) {
+                    return true;
+                }
+                let segment_count = if closed { points.len() } else { points.len().saturating_sub(1) };
+                let crosses = (0..segment_count).any(|i| {
+                    let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                    (0..self.points.len()).any(|j| {
+                        segments_cross(a, b, self.points[j], self.points[(j + 1) % self.points.len()])
+                    })
+                });
+                // A region drawn inside a face touches it without crossing its outline
+                crosses || (closed && point_in_polygon(&points, self.points[0]))
+            }
+        }
+    }
+}
+
+/// Combines the picked items with `selection`
+pub fn apply_selection<T: PartialEq + Clone>(selection: &mut Vec<T>, picked: &[T], op: SelectOp) {
+    if op == SelectOp::Replace {
+        selection.clear();
+    }
+    if op == SelectOp::Subtract {
+        selection.retain(|item| !picked.contains(item));
+        return;
+    }
+    for item in picked {
+        if !selection.contains(item) {
+            selection.push(item.clone());
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+
+use super::grid::WorkPlane;
+
+/// Snap radius as a fraction of the camera distance, so snapping feels the same at any zoom
+pub const SNAP_TOLERANCE: f32 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapKind {
+    Vertex,
+    Midpoint,
+    FaceCenter,
+    Axis,
+    Grid,
+}
+
+impl SnapKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapKind::Vertex => "vertex",
+            SnapKind::Midpoint => "midpoint",
+            SnapKind::FaceCenter => "face centre",
+            SnapKind::Axis => "axis",
+            SnapKind::Grid => "grid",
+        }
+    }
+}
+
+/// Which snaps are active, shared by every tool that places or moves geometry
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    /// Snap to the intersections of the grid, at the minor spacing currently shown
+    pub grid: bool,
+    pub vertices: bool,
+    pub midpoints: bool,
+    pub face_centers: bool,
+    pub axes: bool,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        SnapSettings {
+            enabled: true,
+            grid: true,
+            vertices: true,
+            midpoints: true,
+            face_centers: true,
+            axes: true,
+        }
+    }
+}
+
+impl SnapSettings {
+    pub fn allows(&self, kind: SnapKind) -> bool {
+        match kind {
+            SnapKind::Vertex => self.vertices,
+            SnapKind::Midpoint => self.midpoints,
+            SnapKind::FaceCenter => self.face_centers,
+            SnapKind::Axis => self.axes,
+            SnapKind::Grid => self.grid,
+        }
+    }
+}
+
+/// A point of existing geometry the cursor can snap to, in world coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapTarget {
+    pub point: Vec3,
+    pub kind: SnapKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    pub point: Vec3,
+    /// None when the point was not snapped
+    pub kind: Option<SnapKind>,
+    /// Direction of the axis for axis snaps
+    pub axis: Option<Vec3>,
+}
+
+impl Snap {
+    pub fn free(point: Vec3) -> Self {
+        Snap { point, kind: None, axis: None }
+    }
+}
+
+/// Vertices, edge midpoints and the centre of a polygon face
+pub fn face_targets(vertices: &[Vec3]) -> Vec<SnapTarget> {
+    if vertices.is_empty() {
+        return Vec::new();
+    }
+    let mut targets: Vec<SnapTarget> = vertices.iter()
+        .map(|point| SnapTarget { point: *point, kind: SnapKind::Vertex })
+        .collect();
+    targets.extend((0..vertices.len()).map(|i| SnapTarget {
+        point: vertices[i].lerp(vertices[(i + 1) % vertices.len()], 0.5),
+        kind: SnapKind::Midpoint,
+    }));
+    targets.push(SnapTarget {
+        point: vertices.iter().sum::<Vec3>() / vertices.len() as f32,
+        kind: SnapKind::FaceCenter,
+    });
+    targets
+}
+
+/// Snaps `point`, lying on `plane`, to the nearest geometry target within `tolerance`,
+/// otherwise to an axis through `reference` and then to the grid of the plane with
+/// `grid_spacing`, the minor spacing of the grid shown.
+pub fn snap_point(
+    point: Vec3,
+    plane: &WorkPlane,
+    grid_spacing: f32,
+    targets: &[SnapTarget],
+    reference: Option<Vec3>,
+    settings: &SnapSettings,
+    tolerance: f32,
+) -> Snap {
+    if !settings.enabled {
+        return Snap::free(point);
+    }
+
+    let nearest = targets.iter()
+        .filter(|target| settings.allows(target.kind))
+        .map(|target| (target, target.point.distance(point)))
+        .filter(|(_, distance)| *distance < tolerance)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((target, _)) = nearest {
+        return Snap { point: target.point, kind: Some(target.kind), axis: None };
+    }
+
+    let grid_snap = settings.allows(SnapKind::Grid) && grid_spacing > 0.0;
+    if let Some(reference) = reference.filter(|_| settings.allows(SnapKind::Axis)) {
+        if let Some((mut on_axis, axis)) = snap_to_axis(point, reference, tolerance) {
+            // Step along the axis in grid increments
+            if grid_snap {
+                let along = (on_axis - reference).dot(axis);
+                on_axis = reference + axis * (along / grid_spacing).round() * grid_spacing;
+            }
+            return Snap { point: on_axis, kind: Some(SnapKind::Axis), axis: Some(axis) };
+        }
+    }
+
+    if grid_snap {
+        return Snap {
+            point: plane.snap_to_grid(point, grid_spacing),
+            kind: Some(SnapKind::Grid),
+            axis: None,
+        };
+    }
+    Snap::free(point)
+}
+
+/// Projects onto the closest world axis through `reference` if it is within `tolerance`
+pub fn snap_to_axis(point: Vec3, reference: Vec3, tolerance: f32) -> Option<(Vec3, Vec3)> {
+    [Vec3::X, Vec3::Y, Vec3::Z].into_iter()
+        .map(|axis| (reference + axis * (point - reference).dot(axis), axis))
+        .filter(|(projected, _)| projected.distance(point) < tolerance)
+        .min_by(|(a, _), (b, _)| a.distance(point).total_cmp(&b.distance(point)))
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+    use super::super::export::stl_ascii;
+    use super::super::polygon::triangulate;
+
+    #[test]
+    fn test_quad_becomes_two_facets() {
+        let quad = vec![Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        let stl = stl_ascii("part", &[quad]);
+        assert!(stl.starts_with("solid part\n"));
+        assert!(stl.trim_end().ends_with("endsolid part"));
+        assert_eq!(stl.matches("facet normal").count(), 2);
+        assert_eq!(stl.matches("vertex ").count(), 6);
+        // Counter-clockwise in the XY plane faces +Z
+        assert!(stl.contains("facet normal 0e0 0e0 1e0"));
+    }
+
+    #[test]
+    fn test_degenerate_polygons_are_skipped() {
+        let stl = stl_ascii("empty", &[vec![Vec3::ZERO, Vec3::X], Vec::new()]);
+        assert_eq!(stl, "solid empty\nendsolid empty\n");
+    }
+
+    #[test]
+    fn test_concave_polygon_is_not_fanned() {
+        // An L shape, fanning from the first vertex would cover the notch
+        let l_shape = [
+            Vec3::ZERO, Vec3::new(2.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 2.0), Vec3::new(0.0, 0.0, 2.0),
+        ];
+        let triangles: Vec<[Vec3; 3]> = triangulate(&l_shape).into_iter().map(|triangle| triangle.map(|i| l_shape[i])).collect();
+        assert_eq!(triangles.len(), 4);
+        let area: f32 = triangles.iter().map(|[a, b, c]| (*b - *a).cross(*c - *a).length() * 0.5).sum();
+        assert!((area - 3.0).abs() < 1e-5);
+        // Every triangle faces the same way as the polygon
+        let normal = (l_shape[1] - l_shape[0]).cross(l_shape[2] - l_shape[1]);
+        assert!(triangles.iter().all(|[a, b, c]| (*b - *a).cross(*c - *a).dot(normal) > 0.0));
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::{Vec2, Vec3};
+    use super::super::grid::{GridSettings, WorkPlane};
+
+    #[test]
+    fn test_level_follows_zoom() {
+        let settings = GridSettings { spacing: 0.1, major_every: 10, adaptive: true, ..GridSettings::default() };
+
+        // Close up the base spacing is used, never anything finer
+        let close = settings.level(0.5);
+        assert_eq!((close.minor, close.minor_alpha), (0.1, 1.0));
+
+        // 40 cells of 1.0 fit a view distance of 40, halfway to the next level in log scale
+        let far = settings.level(40.0 * 10f32.sqrt());
+        assert!((far.minor - 1.0).abs() < 1e-5);
+        assert!((far.major - 10.0).abs() < 1e-4);
+        assert!((far.minor_alpha - 0.5).abs() < 1e-4);
+
+        let fixed = GridSettings { adaptive: false, ..settings };
+        assert_eq!(fixed.level(1000.0).minor, 0.1);
+    }
+
+    #[test]
+    fn test_snap_on_plane_keeps_height() {
+        // On a face at height 0.3 only the in-plane coordinates are rounded
+        let plane = WorkPlane::through_point(Vec3::new(5.0, 0.3, 2.0), Vec3::Y);
+        let snapped = plane.snap_to_grid(Vec3::new(0.74, 0.3, -1.1), 0.5);
+        assert!((snapped - Vec3::new(0.5, 0.3, -1.0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_face_plane_axes() {
+        let plane = WorkPlane::new(Vec3::new(1.0, 2.0, 3.0), Vec3::X * 2.0, Vec3::new(0.3, 0.0, 1.0));
+        assert_eq!(plane.normal, Vec3::X);
+        assert!((plane.u - Vec3::Z).length() < 1e-6);
+        assert!(plane.v.dot(plane.u).abs() < 1e-6 && plane.v.dot(plane.normal).abs() < 1e-6);
+
+        let local = Vec2::new(0.25, -4.0);
+        assert!((plane.to_local(plane.to_world(local)) - local).length() < 1e-5);
+        assert_eq!(plane.offset_to(Vec3::new(7.0, 0.0, 0.0)).origin, Vec3::new(7.0, 2.0, 3.0));
+    }
+}
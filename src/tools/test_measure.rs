@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+    use super::super::measure::{measure_pair, measure_radius, measure_single, MeasurePick};
+
+    fn unit_square(z: f32, normal: Vec3) -> MeasurePick {
+        MeasurePick::Face {
+            point: Vec3::new(0.5, 0.5, z),
+            normal,
+            vertices: vec![
+                Vec3::new(0.0, 0.0, z),
+                Vec3::new(1.0, 0.0, z),
+                Vec3::new(1.0, 1.0, z),
+                Vec3::new(0.0, 1.0, z),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_vertex_distance_and_axes() {
+        let m = measure_pair(&MeasurePick::Vertex(Vec3::ZERO), &MeasurePick::Vertex(Vec3::new(3.0, 4.0, 0.0)));
+        assert!((m.distance - 5.0).abs() < 1e-6);
+        assert_eq!(m.delta(), Vec3::new(3.0, 4.0, 0.0));
+        assert!(m.angle.is_none());
+    }
+
+    #[test]
+    fn test_edge_length() {
+        let edge = MeasurePick::Edge { start: Vec3::ZERO, end: Vec3::new(0.0, 2.0, 0.0) };
+        assert_eq!(measure_single(&edge).unwrap().length, Some(2.0));
+        assert!(measure_single(&MeasurePick::Vertex(Vec3::ZERO)).is_none());
+    }
+
+    #[test]
+    fn test_parallel_faces() {
+        let m = measure_pair(&unit_square(0.0, Vec3::NEG_Z), &unit_square(2.5, Vec3::Z));
+        assert!((m.distance - 2.5).abs() < 1e-6);
+        // Opposite faces of a slab are parallel however their normals point
+        assert!(m.angle.unwrap().abs() < 1e-6);
+        let side = MeasurePick::Face { point: Vec3::ZERO, normal: Vec3::NEG_X, vertices: vec![Vec3::ZERO, Vec3::Y, Vec3::Z] };
+        let m = measure_pair(&unit_square(0.0, Vec3::Z), &side);
+        assert!((m.angle.unwrap() - FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_edge_to_face_angle() {
+        let face = unit_square(0.0, Vec3::Z);
+        let angle = |start: Vec3, end: Vec3| measure_pair(&MeasurePick::Edge { start, end }, &face).angle.unwrap();
+        // In the plane of the face
+        assert!(angle(Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0)).abs() < 1e-6);
+        // Square to it, whichever way the edge runs
+        assert!((angle(Vec3::ZERO, Vec3::new(0.0, 0.0, 2.0)) - FRAC_PI_2).abs() < 1e-6);
+        assert!((angle(Vec3::new(0.0, 0.0, 2.0), Vec3::ZERO) - FRAC_PI_2).abs() < 1e-6);
+        let m = measure_pair(&face, &MeasurePick::Edge { start: Vec3::ZERO, end: Vec3::new(1.0, 0.0, 1.0) });
+        assert!((m.angle.unwrap() - FRAC_PI_4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_point_to_face_and_edge_angle() {
+        let m = measure_pair(&MeasurePick::Vertex(Vec3::new(2.0, 0.5, 1.0)), &unit_square(0.0, Vec3::Z));
+        assert!((m.to - Vec3::new(1.0, 0.5, 0.0)).length() < 1e-6);
+
+        let a = MeasurePick::Edge { start: Vec3::ZERO, end: Vec3::X };
+        let b = MeasurePick::Edge { start: Vec3::new(0.0, 0.0, 1.0), end: Vec3::new(0.0, 3.0, 1.0) };
+        let m = measure_pair(&a, &b);
+        assert!((m.distance - 1.0).abs() < 1e-6);
+        assert!((m.angle.unwrap() - FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_radius_through_three_vertices() {
+        // Three points of a circle of radius 2 around (1, 1, 3), tilted out of the axis planes
+        let center = Vec3::new(1.0, 1.0, 3.0);
+        let (u, v) = (Vec3::new(1.0, 0.0, 1.0).normalize(), Vec3::Y);
+        let on_circle = |angle: f32| MeasurePick::Vertex(center + 2.0 * (u * angle.cos() + v * angle.sin()));
+        let m = measure_radius(&on_circle(0.1), &on_circle(1.7), &on_circle(4.0)).unwrap();
+        assert!((m.radius.unwrap() - 2.0).abs() < 1e-4);
+        assert!((m.from - center).length() < 1e-4);
+
+        let in_line = [Vec3::ZERO, Vec3::X, Vec3::new(3.0, 0.0, 0.0)].map(MeasurePick::Vertex);
+        assert!(measure_radius(&in_line[0], &in_line[1], &in_line[2]).is_none());
+        let edge = MeasurePick::Edge { start: Vec3::ZERO, end: Vec3::X };
+        assert!(measure_radius(&edge, &on_circle(0.0), &on_circle(1.0)).is_none());
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod tests {
+    use super::super::preferences::Preferences;
+    use crate::view::navigation::{NavButton, NavModifier, NavigationPreset, NavigationScheme};
+
+    #[test]
+    fn test_preferences_round_trip() {
+        let mut preferences = Preferences { navigation: NavigationScheme::preset(NavigationPreset::SolidWorks) };
+        preferences.navigation.pan.modifier = NavModifier::Alt;
+        preferences.navigation.preset = NavigationPreset::Custom;
+
+        let loaded = Preferences::from_json(&preferences.to_json()).unwrap();
+        assert_eq!(loaded, preferences);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let loaded = Preferences::from_json(r#"{"navigation": {"preset": "Blender", "orbit": {"button": "Middle", "modifier": "None"}}}"#).unwrap();
+        assert_eq!(loaded.navigation.preset, NavigationPreset::Blender);
+        assert_eq!(loaded.navigation.orbit.button, NavButton::Middle);
+        assert_eq!(loaded.navigation.pan, NavigationScheme::default().pan);
+        assert_eq!(Preferences::from_json("{}").unwrap(), Preferences::default());
+        assert!(Preferences::from_json("not json").is_err());
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec2;
+    use super::super::region_select::{apply_selection, RegionMode, SelectOp, SelectionRegion};
+
+    fn square(min: Vec2, size: f32) -> Vec<Option<Vec2>> {
+        [min, min + Vec2::new(size, 0.0), min + Vec2::splat(size), min + Vec2::new(0.0, size)]
+            .into_iter()
+            .map(Some)
+            .collect()
+    }
+
+    #[test]
+    fn test_drag_direction_picks_mode() {
+        let right = SelectionRegion::rectangle(Vec2::new(10.0, 10.0), Vec2::new(100.0, 80.0));
+        let left = SelectionRegion::rectangle(Vec2::new(100.0, 10.0), Vec2::new(10.0, 80.0));
+        assert_eq!(right.mode, RegionMode::Window);
+        assert_eq!(left.mode, RegionMode::Crossing);
+    }
+
+    #[test]
+    fn test_window_needs_the_whole_outline_inside() {
+        let region = SelectionRegion::rectangle(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        assert!(region.selects(&square(Vec2::new(10.0, 10.0), 20.0), true));
+        // Half way out
+        assert!(!region.selects(&square(Vec2::new(90.0, 10.0), 20.0), true));
+        // A point behind the camera cannot be inside
+        assert!(!region.selects(&[Some(Vec2::new(10.0, 10.0)), None], false));
+    }
+
+    #[test]
+    fn test_crossing_picks_what_it_touches() {
+        let region = SelectionRegion::rectangle(Vec2::new(100.0, 0.0), Vec2::ZERO.with_y(100.0));
+        assert!(region.selects(&square(Vec2::new(90.0, 10.0), 20.0), true));
+        // An edge passing through without an end point inside
+        assert!(region.selects(&[Some(Vec2::new(-50.0, 50.0)), Some(Vec2::new(150.0, 50.0))], false));
+        assert!(!region.selects(&[Some(Vec2::new(-50.0, 150.0)), Some(Vec2::new(150.0, 150.0))], false));
+        // A region drawn inside a big face
+        let inside = SelectionRegion::rectangle(Vec2::new(60.0, 40.0), Vec2::new(40.0, 60.0));
+        assert!(inside.selects(&square(Vec2::ZERO, 100.0), true));
+        assert!(!inside.selects(&[Some(Vec2::ZERO), Some(Vec2::new(100.0, 0.0))], false));
+    }
+
+    #[test]
+    fn test_lasso_winding_picks_mode() {
+        // Clockwise on screen, y down
+        let clockwise = vec![Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0), Vec2::new(50.0, 40.0), Vec2::new(0.0, 100.0)];
+        let lasso = SelectionRegion::lasso(clockwise.clone());
+        assert_eq!(lasso.mode, RegionMode::Window);
+        // The notch of the concave lasso is outside
+        assert!(lasso.contains(Vec2::new(50.0, 20.0)));
+        assert!(!lasso.contains(Vec2::new(50.0, 80.0)));
+        // Beside the notch, where a convex test fails
+        assert!(lasso.contains(Vec2::new(10.0, 80.0)));
+
+        let anticlockwise: Vec<Vec2> = clockwise.into_iter().rev().collect();
+        assert_eq!(SelectionRegion::lasso(anticlockwise).mode, RegionMode::Crossing);
+    }
+
+    #[test]
+    fn test_selection_ops() {
+        let mut selection = vec![1, 2, 3];
+        apply_selection(&mut selection, &[3, 4], SelectOp::Add);
+        assert_eq!(selection, vec![1, 2, 3, 4]);
+        apply_selection(&mut selection, &[1, 4, 5], SelectOp::Subtract);
+        assert_eq!(selection, vec![2, 3]);
+        apply_selection(&mut selection, &[7, 7, 8], SelectOp::Replace);
+        assert_eq!(selection, vec![7, 8]);
+    }
+}
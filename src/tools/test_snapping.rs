@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+    use super::super::grid::WorkPlane;
+    use super::super::snapping::{face_targets, snap_point, SnapKind, SnapSettings};
+
+    fn square() -> Vec<Vec3> {
+        vec![
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+            Vec3::new(2.0, 1.0, 2.0),
+            Vec3::new(0.0, 1.0, 2.0),
+        ]
+    }
+
+    #[test]
+    fn test_face_targets() {
+        let targets = face_targets(&square());
+        assert_eq!(targets.len(), 9);
+        assert!(targets.iter().any(|t| t.kind == SnapKind::Midpoint && t.point == Vec3::new(1.0, 1.0, 0.0)));
+        assert!(targets.iter().any(|t| t.kind == SnapKind::FaceCenter && t.point == Vec3::new(1.0, 1.0, 1.0)));
+        assert!(face_targets(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_geometry_wins_over_grid() {
+        let settings = SnapSettings::default();
+        let targets = face_targets(&square());
+
+        let snap = snap_point(Vec3::new(1.95, 1.0, 0.04), &WorkPlane::XZ, 0.5, &targets, None, &settings, 0.1);
+        assert_eq!(snap.kind, Some(SnapKind::Vertex));
+        assert_eq!(snap.point, Vec3::new(2.0, 1.0, 0.0));
+
+        let snap = snap_point(Vec3::new(1.3, 1.0, 0.7), &WorkPlane::XZ, 0.5, &targets, None, &settings, 0.1);
+        assert_eq!(snap.kind, Some(SnapKind::Grid));
+        assert_eq!(snap.point, Vec3::new(1.5, 1.0, 0.5));
+
+        let disabled = SnapSettings { enabled: false, ..SnapSettings::default() };
+        assert_eq!(snap_point(Vec3::new(1.95, 1.0, 0.04), &WorkPlane::XZ, 0.5, &targets, None, &disabled, 0.1).kind, None);
+    }
+
+    #[test]
+    fn test_axis_snap_steps_in_grid_increments() {
+        let settings = SnapSettings::default();
+        let snap = snap_point(Vec3::new(3.2, 0.05, 1.03), &WorkPlane::XZ, 0.5, &[], Some(Vec3::new(0.0, 0.0, 1.0)), &settings, 0.1);
+        assert_eq!(snap.kind, Some(SnapKind::Axis));
+        assert_eq!(snap.axis, Some(Vec3::X));
+        assert!((snap.point - Vec3::new(3.0, 0.0, 1.0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_grid_snap_follows_the_grid_shown() {
+        let settings = SnapSettings::default();
+        // Zoomed in, the minor lines are closer together
+        let point = Vec3::new(1.33, 0.0, 0.71);
+        assert_eq!(snap_point(point, &WorkPlane::XZ, 1.0, &[], None, &settings, 0.01).point, Vec3::new(1.0, 0.0, 1.0));
+        let fine = snap_point(point, &WorkPlane::XZ, 0.1, &[], None, &settings, 0.01).point;
+        assert!((fine - Vec3::new(1.3, 0.0, 0.7)).length() < 1e-6);
+        // On a plane offset from the grid, e.g. a face parallel to it, the height is kept
+        let raised = WorkPlane::XZ.offset_to(Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(snap_point(Vec3::new(1.33, 2.0, 0.71), &raised, 1.0, &[], None, &settings, 0.01).point, Vec3::new(1.0, 2.0, 1.0));
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Vec3;
+
+    use super::super::units::{DocumentUnits, LengthUnit, UnitParseError};
+
+    fn units(length: LengthUnit) -> DocumentUnits {
+        DocumentUnits { length, precision: 3 }
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_parse_with_units() {
+        let mm = units(LengthUnit::Millimeter);
+        assert_close(mm.parse_length("25.4mm").unwrap(), 25.4);
+        assert_close(mm.parse_length("1in").unwrap(), 25.4);
+        assert_close(mm.parse_length("1in + 3mm").unwrap(), 28.4);
+        assert_close(mm.parse_length("2 * (10 + 5) cm").unwrap(), 300.0);
+        assert_close(mm.parse_length("-1,5 cm").unwrap(), -15.0);
+    }
+
+    #[test]
+    fn test_bare_numbers_use_document_unit() {
+        assert_close(units(LengthUnit::Inch).parse_length("2").unwrap(), 50.8);
+        assert_close(units(LengthUnit::Meter).parse_length("0.5 + 10cm").unwrap(), 600.0);
+        // A length divided by a length is a plain ratio
+        assert_close(units(LengthUnit::Centimeter).parse_length("(10mm / 5mm)").unwrap(), 20.0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mm = units(LengthUnit::Millimeter);
+        assert_eq!(mm.parse_length(""), Err(UnitParseError::Empty));
+        assert_eq!(mm.parse_length("3 ft"), Err(UnitParseError::UnknownUnit("ft".to_string())));
+        assert_eq!(mm.parse_length("1mm * 2mm"), Err(UnitParseError::InvalidDimension));
+        assert_eq!(mm.parse_length("4 / 0"), Err(UnitParseError::DivisionByZero));
+        assert_eq!(mm.parse_length("(1 + 2"), Err(UnitParseError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_format_in_document_units() {
+        let inches = DocumentUnits { length: LengthUnit::Inch, precision: 2 };
+        assert_eq!(inches.format_length(25.4), "1.00 in");
+        assert_eq!(inches.format_area(645.16), "1.00 in²");
+        assert_eq!(units(LengthUnit::Centimeter).format_volume(1000.0), "1.000 cm³");
+        assert_eq!(inches.format_number(2.0 / 3.0), "0.67");
+        assert_eq!(units(LengthUnit::Centimeter).format_inertia(250.0), "2.500");
+        assert_eq!(units(LengthUnit::Centimeter).inertia_symbol(), "mass·cm²");
+        assert_close(LengthUnit::Meter.model_to_unit(LengthUnit::Meter.unit_to_model(1.5)), 1.5);
+    }
+
+    #[test]
+    fn test_points_use_document_precision() {
+        let cm = DocumentUnits { length: LengthUnit::Centimeter, precision: 1 };
+        assert_eq!(cm.format_point(Vec3::new(12.34, -0.04, 0.0)), "1.2, 0.0, 0.0 cm");
+        // Points that show the same coordinates round to the same key
+        assert!(cm.round_point(Vec3::new(12.34, 0.0, 0.0)) == cm.round_point(Vec3::new(11.6, 0.0, 0.0)));
+        assert!(units(LengthUnit::Centimeter).round_point(Vec3::X * 12.34) != units(LengthUnit::Centimeter).round_point(Vec3::X * 11.6));
+    }
+}
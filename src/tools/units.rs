@@ -0,0 +1,353 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::vec3_rounded::Vec3Rounded;
+
+/// Lengths are stored in model space in millimetres, units only change how they are shown and typed
+pub const MODEL_UNIT: LengthUnit = LengthUnit::Millimeter;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LengthUnit {
+    #[default]
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+}
+
+impl LengthUnit {
+    pub const ALL: [LengthUnit; 4] = [
+        LengthUnit::Millimeter,
+        LengthUnit::Centimeter,
+        LengthUnit::Meter,
+        LengthUnit::Inch,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Meter => "m",
+            LengthUnit::Inch => "in",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "millimetres",
+            LengthUnit::Centimeter => "centimetres",
+            LengthUnit::Meter => "metres",
+            LengthUnit::Inch => "inches",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol.to_lowercase().as_str() {
+            "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => Some(LengthUnit::Millimeter),
+            "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => Some(LengthUnit::Centimeter),
+            "m" | "meter" | "meters" | "metre" | "metres" => Some(LengthUnit::Meter),
+            "in" | "inch" | "inches" | "\"" => Some(LengthUnit::Inch),
+            _ => None,
+        }
+    }
+
+    /// Size of one unit in millimetres
+    pub fn millimeters(&self) -> f64 {
+        match self {
+            LengthUnit::Millimeter => 1.0,
+            LengthUnit::Centimeter => 10.0,
+            LengthUnit::Meter => 1000.0,
+            LengthUnit::Inch => 25.4,
+        }
+    }
+
+    /// Converts a model space length into this unit, e.g. to scale exports
+    pub fn model_to_unit(self, model: f32) -> f32 {
+        (model as f64 * MODEL_UNIT.millimeters() / self.millimeters()) as f32
+    }
+
+    pub fn unit_to_model(self, value: f32) -> f32 {
+        (value as f64 * self.millimeters() / MODEL_UNIT.millimeters()) as f32
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Document wide unit used by every numeric input and output
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct DocumentUnits {
+    pub length: LengthUnit,
+    /// Decimal places shown for lengths
+    pub precision: usize,
+}
+
+impl Default for DocumentUnits {
+    fn default() -> Self {
+        DocumentUnits {
+            length: LengthUnit::Millimeter,
+            precision: 3,
+        }
+    }
+}
+
+impl DocumentUnits {
+    pub fn format_length(&self, model: f32) -> String {
+        format!("{:.*} {}", self.precision, self.length.model_to_unit(model), self.length.symbol())
+    }
+
+    pub fn format_area(&self, model: f32) -> String {
+        let scale = self.length.model_to_unit(1.0);
+        format!("{:.*} {}²", self.precision, model * scale * scale, self.length.symbol())
+    }
+
+    pub fn format_volume(&self, model: f32) -> String {
+        let scale = self.length.model_to_unit(1.0);
+        format!("{:.*} {}³", self.precision, model * scale * scale * scale, self.length.symbol())
+    }
+
+    /// A plain number like a mass, with the document precision
+    pub fn format_number(&self, value: f32) -> String {
+        format!("{:.*}", self.precision, value)
+    }
+
+    /// A moment of inertia in mass times squared model units, without the unit symbol so it
+    /// fits in a matrix row, see `inertia_symbol`
+    pub fn format_inertia(&self, model: f32) -> String {
+        let scale = self.length.model_to_unit(1.0);
+        self.format_number(model * scale * scale)
+    }
+
+    pub fn inertia_symbol(&self) -> String {
+        format!("mass·{}²", self.length.symbol())
+    }
+
+    /// `model` in the document unit, rounded to the decimal places coordinates are shown with
+    pub fn round_point(&self, model: Vec3) -> Vec3Rounded {
+        Vec3Rounded::with_decimals(model * self.length.model_to_unit(1.0), self.precision as u32)
+    }
+
+    pub fn format_point(&self, model: Vec3) -> String {
+        let point = self.round_point(model).to_vec3();
+        format!(
+            "{:.*}, {:.*}, {:.*} {}",
+            self.precision, point.x,
+            self.precision, point.y,
+            self.precision, point.z,
+            self.length.symbol()
+        )
+    }
+
+    /// Parses a length expression like `25.4mm`, `1in + 3mm` or `2 * (10 + 5)`, numbers without
+    /// a unit are in the document unit. Returns the length in model units.
+    pub fn parse_length(&self, input: &str) -> Result<f32, UnitParseError> {
+        let mut parser = ExpressionParser {
+            tokens: tokenize(input)?,
+            position: 0,
+            document_unit: self.length,
+        };
+        let quantity = parser.expression()?;
+        if parser.position != parser.tokens.len() {
+            return Err(UnitParseError::UnexpectedToken(parser.tokens[parser.position].to_string()));
+        }
+        Ok((quantity.as_length(self.length) / MODEL_UNIT.millimeters()) as f32)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitParseError {
+    Empty,
+    UnknownUnit(String),
+    UnexpectedToken(String),
+    UnexpectedEnd,
+    /// e.g. multiplying two lengths
+    InvalidDimension,
+    DivisionByZero,
+}
+
+impl fmt::Display for UnitParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitParseError::Empty => write!(f, "no value entered"),
+            UnitParseError::UnknownUnit(unit) => write!(f, "unknown unit '{}'", unit),
+            UnitParseError::UnexpectedToken(token) => write!(f, "unexpected '{}'", token),
+            UnitParseError::UnexpectedEnd => write!(f, "incomplete expression"),
+            UnitParseError::InvalidDimension => write!(f, "result is not a length"),
+            UnitParseError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for UnitParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Unit(LengthUnit),
+    Operator(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Unit(unit) => write!(f, "{}", unit),
+            Token::Operator(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, UnitParseError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' || c == ',' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == ',') {
+                i += 1;
+            }
+            // Accept a decimal comma as well
+            let text: String = chars[start..i].iter().collect::<String>().replace(',', ".");
+            let value = text.parse().map_err(|_| UnitParseError::UnexpectedToken(text.clone()))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '"' {
+            let start = i;
+            i += 1;
+            while c != '"' && i < chars.len() && chars[i].is_alphabetic() {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let unit = LengthUnit::from_symbol(&text).ok_or(UnitParseError::UnknownUnit(text))?;
+            tokens.push(Token::Unit(unit));
+        } else if "+-*/()".contains(c) {
+            tokens.push(Token::Operator(c));
+            i += 1;
+        } else {
+            return Err(UnitParseError::UnexpectedToken(c.to_string()));
+        }
+    }
+    if tokens.is_empty() {
+        return Err(UnitParseError::Empty);
+    }
+    Ok(tokens)
+}
+
+// A value that is either a plain number or a length in millimetres
+#[derive(Debug, Clone, Copy)]
+struct Quantity {
+    value: f64,
+    is_length: bool,
+}
+
+impl Quantity {
+    fn as_length(&self, document_unit: LengthUnit) -> f64 {
+        if self.is_length {
+            self.value
+        } else {
+            self.value * document_unit.millimeters()
+        }
+    }
+}
+
+// Recursive descent over: expression = term (('+' | '-') term)*
+//                         term       = factor (('*' | '/') factor)*
+//                         factor     = '-' factor | (number | '(' expression ')') unit?
+struct ExpressionParser {
+    tokens: Vec<Token>,
+    position: usize,
+    document_unit: LengthUnit,
+}
+
+impl ExpressionParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Result<Quantity, UnitParseError> {
+        let mut left = self.term()?;
+        while let Some(Token::Operator(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let right = self.term()?;
+            // Adding a plain number to a length treats the number as a document length
+            let is_length = left.is_length || right.is_length;
+            let (a, b) = if is_length {
+                (left.as_length(self.document_unit), right.as_length(self.document_unit))
+            } else {
+                (left.value, right.value)
+            };
+            left = Quantity {
+                value: if op == '+' { a + b } else { a - b },
+                is_length,
+            };
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Quantity, UnitParseError> {
+        let mut left = self.factor()?;
+        while let Some(Token::Operator(op @ ('*' | '/'))) = self.peek().cloned() {
+            self.position += 1;
+            let right = self.factor()?;
+            left = if op == '*' {
+                if left.is_length && right.is_length {
+                    return Err(UnitParseError::InvalidDimension);
+                }
+                Quantity { value: left.value * right.value, is_length: left.is_length || right.is_length }
+            } else {
+                if right.value == 0.0 {
+                    return Err(UnitParseError::DivisionByZero);
+                }
+                if right.is_length && !left.is_length {
+                    return Err(UnitParseError::InvalidDimension);
+                }
+                // length / length is a ratio
+                Quantity { value: left.value / right.value, is_length: left.is_length && !right.is_length }
+            };
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<Quantity, UnitParseError> {
+        let quantity = match self.next() {
+            Some(Token::Operator('-')) => {
+                let inner = self.factor()?;
+                return Ok(Quantity { value: -inner.value, ..inner });
+            }
+            Some(Token::Number(value)) => Quantity { value, is_length: false },
+            Some(Token::Operator('(')) => {
+                let inner = self.expression()?;
+                match self.next() {
+                    Some(Token::Operator(')')) => inner,
+                    Some(token) => return Err(UnitParseError::UnexpectedToken(token.to_string())),
+                    None => return Err(UnitParseError::UnexpectedEnd),
+                }
+            }
+            Some(token) => return Err(UnitParseError::UnexpectedToken(token.to_string())),
+            None => return Err(UnitParseError::UnexpectedEnd),
+        };
+
+        if let Some(Token::Unit(unit)) = self.peek().cloned() {
+            self.position += 1;
+            if quantity.is_length {
+                return Err(UnitParseError::InvalidDimension);
+            }
+            return Ok(Quantity { value: quantity.value * unit.millimeters(), is_length: true });
+        }
+        Ok(quantity)
+    }
+}
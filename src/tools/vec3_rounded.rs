@@ -1,30 +1,49 @@
 use bevy::prelude::Vec3;
 
-const SCALE: f32 = 1000.0; //num of decimal places
+/// Decimal places kept when no precision is given
+pub const DEFAULT_DECIMALS: u32 = 3;
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Vec3Rounded {
     x: i32,
     y: i32,
     z: i32,
+    decimals: u32,
 }
 
 impl From<[f32; 3]> for Vec3Rounded {
     fn from(value: [f32; 3]) -> Self {
-        Self { 
-            x: (value[0] * SCALE).round() as i32,
-            y: (value[1] * SCALE).round() as i32,
-            z: (value[2] * SCALE).round() as i32, 
-        }
+        Self::with_decimals(Vec3::from_array(value), DEFAULT_DECIMALS)
+    }
+}
+
+impl From<Vec3> for Vec3Rounded {
+    fn from(value: Vec3) -> Self {
+        Self::with_decimals(value, DEFAULT_DECIMALS)
     }
 }
 
 impl Vec3Rounded {
-    pub fn to_vec3(&self) -> Vec3 {
+    pub fn with_decimals(value: Vec3, decimals: u32) -> Self {
+        let scale = Self::scale(decimals);
+        Self {
+            x: (value.x * scale).round() as i32,
+            y: (value.y * scale).round() as i32,
+            z: (value.z * scale).round() as i32,
+            decimals,
+        }
+    }
+
+    pub fn to_vec3(self) -> Vec3 {
+        let scale = Self::scale(self.decimals);
         Vec3::new(
-            self.x as f32 / SCALE,
-            self.y as f32 / SCALE,
-            self.z as f32 / SCALE,
+            self.x as f32 / scale,
+            self.y as f32 / scale,
+            self.z as f32 / scale,
         )
     }
-}
\ No newline at end of file
+
+    fn scale(decimals: u32) -> f32 {
+        10f32.powi(decimals as i32)
+    }
+}
@@ -9,8 +9,10 @@ pub enum ToolbarButtonType {
     Delete,
     SelectFaceMode,
     SelectEdgeMode,
+    SelectPartMode,
     RotatePart,
-    MoveFace,
+    MovePart,
+    Measure,
 }
 
 #[derive(Component,)]
@@ -21,3 +23,7 @@ pub struct ToolbarButton;
 pub struct ToggleableButton {
     pub is_active: bool,
 }
+
+// Text of the properties panel
+#[derive(Component)]
+pub struct PropertiesText;
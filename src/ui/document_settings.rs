@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::part::components::ExtrusionParams;
+use crate::tools::snapping::SnapSettings;
+use crate::tools::units::{DocumentUnits, LengthUnit};
+use super::output_console::{ConsoleCommand, OutputConsole};
+
+const MAX_PRECISION: usize = 6;
+
+pub fn document_settings_ui_system(
+    mut egui_contexts: EguiContexts,
+    mut units: ResMut<DocumentUnits>,
+    mut extrusion_params: ResMut<ExtrusionParams>,
+    mut snap_settings: ResMut<SnapSettings>,
+) {
+    egui::Window::new("Document")
+        .resizable(false)
+        .collapsible(true)
+        .default_open(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("document_settings_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Units");
+                let mut length = units.length;
+                egui::ComboBox::from_id_salt("document_units")
+                    .selected_text(format!("{} ({})", length.name(), length.symbol()))
+                    .show_ui(ui, |ui| {
+                        for unit in LengthUnit::ALL {
+                            ui.selectable_value(&mut length, unit, format!("{} ({})", unit.name(), unit.symbol()));
+                        }
+                    });
+                if length != units.length {
+                    units.length = length;
+                }
+                ui.end_row();
+
+                ui.label("Precision");
+                let mut precision = units.precision;
+                ui.add(egui::Slider::new(&mut precision, 0..=MAX_PRECISION).suffix(" decimals"));
+                if precision != units.precision {
+                    units.precision = precision;
+                }
+                ui.end_row();
+
+                ui.label("Extrude distance");
+                let mut distance = extrusion_params.distance;
+                if length_input(ui, "extrude_distance", &mut distance, &units) {
+                    extrusion_params.distance = distance;
+                }
+                ui.end_row();
+            });
+
+            ui.separator();
+            // Copy so the resource is only marked changed on an actual edit
+            let mut snap = snap_settings.clone();
+            ui.checkbox(&mut snap.enabled, "Snapping (F9)");
+            ui.add_enabled_ui(snap.enabled, |ui| {
+                ui.checkbox(&mut snap.grid, "Grid (minor spacing shown)");
+                ui.checkbox(&mut snap.vertices, "Vertices");
+                ui.checkbox(&mut snap.midpoints, "Edge midpoints");
+                ui.checkbox(&mut snap.face_centers, "Face centres");
+                ui.checkbox(&mut snap.axes, "Axis directions");
+            });
+            if snap != *snap_settings {
+                *snap_settings = snap;
+            }
+        });
+}
+
+/// Text field for a length in model units. Accepts expressions with units like `1in + 3mm`,
+/// shows the value in document units and keeps invalid input in red until it is fixed.
+pub fn length_input(ui: &mut egui::Ui, id_salt: &str, value: &mut f32, units: &DocumentUnits) -> bool {
+    let id = ui.make_persistent_id(id_salt);
+    let error_id = id.with("error");
+
+    // Text being edited or rejected lives in egui memory, otherwise the current value is shown
+    let editing: Option<String> = ui.data(|data| data.get_temp(id));
+    let error: Option<String> = ui.data(|data| data.get_temp(error_id));
+    let mut text = editing.unwrap_or_else(|| units.format_length(*value));
+
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .desired_width(120.0)
+            .text_color_opt(error.as_ref().map(|_| egui::Color32::LIGHT_RED)),
+    );
+
+    let mut changed = false;
+    if response.lost_focus() {
+        match units.parse_length(&text) {
+            Ok(parsed) => {
+                *value = parsed;
+                changed = true;
+                ui.data_mut(|data| {
+                    data.remove::<String>(id);
+                    data.remove::<String>(error_id);
+                });
+            }
+            Err(e) => ui.data_mut(|data| {
+                data.insert_temp(id, text);
+                data.insert_temp(error_id, e.to_string());
+            }),
+        }
+    } else if response.has_focus() {
+        ui.data_mut(|data| data.insert_temp(id, text));
+    }
+
+    if let Some(error) = error {
+        response.on_hover_text(error);
+    }
+    changed
+}
+
+pub fn units_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut units: ResMut<DocumentUnits>,
+) {
+    for command in events.read() {
+        match command.name.as_str() {
+            "units" => match command.args.first().and_then(|arg| LengthUnit::from_symbol(arg)) {
+                Some(unit) => {
+                    units.length = unit;
+                    console.add_log(format!("Units set to {}", unit.name()));
+                }
+                None => console.add_log("Usage: /units <mm|cm|m|in>"),
+            },
+            "precision" => match command.args.first().map(|arg| arg.parse::<usize>()) {
+                Some(Ok(precision)) if precision <= MAX_PRECISION => {
+                    units.precision = precision;
+                    console.add_log(format!("Showing {} decimal places", precision));
+                }
+                _ => console.add_log(format!("Usage: /precision <0-{}>", MAX_PRECISION)),
+            },
+            "eval" => {
+                let expression = command.args.join(" ");
+                match units.parse_length(&expression) {
+                    Ok(length) => console.add_log(format!("{} = {}", expression, units.format_length(length))),
+                    Err(e) => console.add_log(format!("{}: {}", expression, e)),
+                }
+            }
+            _ => {}
+        }
+    }
+}
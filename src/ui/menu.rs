@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::output_console::ConsoleCommand;
+use super::ui_elements::{MENU_BUTTON_WIDTH, PANEL_PADDING, TOP_BAR_HEIGHT};
+
+/// Menus of the top bar
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Menu {
+    File,
+    Edit,
+    View,
+    Window,
+    Help,
+}
+
+impl Menu {
+    pub const ALL: [Menu; 5] = [Menu::File, Menu::Edit, Menu::View, Menu::Window, Menu::Help];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Menu::File => "File",
+            Menu::Edit => "Edit",
+            Menu::View => "View",
+            Menu::Window => "Window",
+            Menu::Help => "Help",
+        }
+    }
+}
+
+/// A menu entry runs a console command, so everything in the menus can also be typed
+pub struct MenuItem {
+    pub menu: Menu,
+    pub label: String,
+    pub shortcut: Option<&'static str>,
+    pub command: String,
+}
+
+#[derive(Resource, Default)]
+pub struct MenuItems {
+    items: Vec<MenuItem>,
+    open: Option<Menu>,
+}
+
+impl MenuItems {
+    pub fn add(&mut self, menu: Menu, label: &str, shortcut: Option<&'static str>, command: &str) -> &mut Self {
+        self.items.push(MenuItem {
+            menu,
+            label: label.to_string(),
+            shortcut,
+            command: command.to_string(),
+        });
+        self
+    }
+}
+
+/// Lets plugins fill the menus while the app is built
+pub trait MenuAppExt {
+    fn add_menu_item(&mut self, menu: Menu, label: &str, shortcut: Option<&'static str>, command: &str) -> &mut Self;
+}
+
+impl MenuAppExt for App {
+    fn add_menu_item(&mut self, menu: Menu, label: &str, shortcut: Option<&'static str>, command: &str) -> &mut Self {
+        self.init_resource::<MenuItems>();
+        self.world_mut().resource_mut::<MenuItems>().add(menu, label, shortcut, command);
+        self
+    }
+}
+
+pub fn menu_button_system(
+    buttons: Query<(&Interaction, &Menu), Changed<Interaction>>,
+    mut menus: ResMut<MenuItems>,
+) {
+    for (interaction, menu) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            menus.open = if menus.open == Some(*menu) { None } else { Some(*menu) };
+        }
+    }
+}
+
+pub fn menu_ui_system(
+    mut egui_contexts: EguiContexts,
+    mut menus: ResMut<MenuItems>,
+    mut console_commands: EventWriter<ConsoleCommand>,
+) {
+    let Some(open) = menus.open else {
+        return;
+    };
+    let index = Menu::ALL.iter().position(|menu| *menu == open).unwrap_or_default();
+    let position = egui::pos2(PANEL_PADDING + index as f32 * (MENU_BUTTON_WIDTH + 1.0), TOP_BAR_HEIGHT);
+
+    let mut chosen = None;
+    let area = egui::Area::new(egui::Id::new("top_bar_menu"))
+        .fixed_pos(position)
+        .order(egui::Order::Foreground)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Frame::menu(ui.style()).show(ui, |ui| {
+                ui.set_min_width(180.0);
+                let items: Vec<&MenuItem> = menus.items.iter().filter(|item| item.menu == open).collect();
+                if items.is_empty() {
+                    ui.weak("Nothing here yet");
+                }
+                for item in items {
+                    let button = egui::Button::new(&item.label).shortcut_text(item.shortcut.unwrap_or_default());
+                    if ui.add(button).clicked() {
+                        chosen = Some(item.command.clone());
+                    }
+                }
+            });
+        });
+
+    // Clicks on the top bar are handled by the menu buttons themselves
+    let clicked_outside = area.response.clicked_elsewhere()
+        && egui_contexts.ctx_mut().input(|input| input.pointer.interact_pos().is_some_and(|pos| pos.y > TOP_BAR_HEIGHT));
+    if let Some(command) = chosen {
+        console_commands.send_batch(ConsoleCommand::parse(&command));
+        menus.open = None;
+    } else if clicked_outside {
+        menus.open = None;
+    }
+}
@@ -2,8 +2,14 @@ pub mod ui_elements;
 pub mod ui_button_systems;
 pub mod components;
 pub mod output_console;
+pub mod properties_panel;
+pub mod document_settings;
+pub mod menu;
 
 pub use ui_elements::*;
 pub use ui_button_systems::*;
 pub use components::*;
-pub use output_console::*;
\ No newline at end of file
+pub use output_console::*;
+pub use properties_panel::*;
+pub use document_settings::*;
+pub use menu::*;
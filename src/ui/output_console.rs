@@ -2,8 +2,15 @@ use bevy_egui::egui;
 use std::collections::VecDeque;
 use bevy::prelude::*;
 use crate::ai::{
-    ai_client::AiClient, process_console_ai_command,
+    ai_client::AiClient,
+    commands::{parse_answer, parse_tool_call, AiAnswer, AiCommandQueue},
+    conversation::{Conversation, Message, MAX_TOOL_ROUNDS},
+    error::AiError,
+    llm_provider::ModelReply,
+    requests::{spinner, AiPrompt, AiRequests},
+    scene::SceneParts,
 };
+use crate::tools::units::DocumentUnits;
 
 
 use tokio::runtime::Runtime;
@@ -12,13 +19,50 @@ use tokio::runtime::Runtime;
 #[derive(Resource)]
 pub struct AsyncRuntime(pub Runtime);
 
-// Component to store the async task
-#[derive(Component)]
-pub struct AsyncApiTask {
-    input: String,
-    client: AiClient,
+
+/// Console commands handled locally, everything else typed into the console goes to the AI
+pub const CONSOLE_COMMANDS: &[(&str, &str)] = &[
+    ("help", "list console commands"),
+    ("mass", "print mass properties of the selected parts"),
+    ("density <value>", "set the density used for mass properties"),
+    ("units <mm|cm|m|in>", "set the document units"),
+    ("precision <decimals>", "set how many decimals lengths are shown with"),
+    ("eval <expression>", "evaluate a length expression, e.g. 1in + 3mm"),
+    ("snap [on|off|grid <on|off>]", "toggle snapping (F9) or snapping to the grid shown"),
+    ("view <front|back|top|bottom|left|right|iso|projection|fit [selection]>", "change the view"),
+    ("display [shaded|edges|wireframe|hidden|xray]", "show or set how the active viewport draws parts"),
+    ("section [add [x|y|z|view]|face|select <n>|flip|toggle|offset <length>|remove|clear]", "list or edit the section planes"),
+    ("part [delete|duplicate|export [file]]", "count, delete, duplicate or export to STL the selected parts"),
+    ("part move <dx> <dy> <dz>", "move the selected parts, distances may carry units"),
+    ("part rotate <x|y|z> <degrees>", "turn the selected parts about the centre of their bounds"),
+    ("part <union|difference|intersection>", "combine the selected parts, a difference takes the others from the first made"),
+    ("select [box|lasso]", "show or set the shape a selection drag draws"),
+    ("select <all|none|invert|grow|shrink|connected|coplanar|normal|loop|ring>", "change the selection over the faces and edges of the parts"),
+    ("viewports [1|2|4]", "show or set how many viewports the 3D area is split into"),
+    ("ai [settings|cancel|provider <gemini|openai|ollama>|model <name>|url <base url>|temperature <t>|timeout <seconds>|tools <on|off>]", "show or configure the AI backend, or cancel the AI requests"),
+    ("ai <new|history|save [file]|load [file]>", "start a new AI conversation, list it, or save or load it"),
+    ("ai record [file|off]", "record AI requests and responses to a fixture file for tests"),
+    ("ai retries [n]", "show or set how often failed AI requests are sent again"),
+    ("ai <accept|reject|regenerate|preview [on|off]>", "accept, reject or ask again for the previewed AI answer, or turn previews on or off"),
+    ("navigation [default|blender|solidworks|fusion]", "show or choose the camera navigation preset"),
+];
+
+/// A `/command arg ...` line typed into the console
+#[derive(Event, Debug, Clone)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
 }
 
+impl ConsoleCommand {
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut words = input.trim().strip_prefix('/')?.split_whitespace();
+        Some(ConsoleCommand {
+            name: words.next()?.to_lowercase(),
+            args: words.map(str::to_string).collect(),
+        })
+    }
+}
 
 #[derive(Default, Resource)]
 pub struct OutputConsole {
@@ -45,21 +89,56 @@ impl OutputConsole {
 
     pub fn show(
         &mut self,
-        mut commands: Commands,
+        requests: &mut AiRequests,
         ai_client: Res<AiClient>,
+        units: &DocumentUnits,
+        console_commands: &mut EventWriter<ConsoleCommand>,
         ui: &mut egui::Ui) {
         // ui.heading("Console Input");
         let input_field = ui.add(egui::TextEdit::singleline(&mut self.input_text).desired_width(f32::INFINITY));
         if input_field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-            println!("Sending input: {}", self.input_text);
-            let task = AsyncApiTask {
-                input: self.input_text.clone(),
-                client: ai_client.clone(),
-            };
-
-            // Spawn an entity to track the task
-            commands.spawn(task);
-            self.logs.push_back(std::mem::take(&mut self.input_text));
+            if let Some(command) = ConsoleCommand::parse(&self.input_text) {
+                console_commands.send(command);
+                let input = std::mem::take(&mut self.input_text);
+                self.add_log(input);
+                return;
+            }
+            if !ai_client.is_ready() {
+                let input = std::mem::take(&mut self.input_text);
+                self.add_log(input);
+                let provider = ai_client.config().provider;
+                self.add_log(format!(
+                    "The AI console is disabled, there is no API key for {}. Set ${} or enter a key in Window > AI settings.",
+                    provider.label(),
+                    provider.key_env_vars()[0],
+                ));
+                return;
+            }
+            let input = std::mem::take(&mut self.input_text);
+            requests.push(AiPrompt {
+                input: input.clone(),
+                unit: units.length,
+                follow_up: false,
+            });
+            self.logs.push_back(input);
+        }
+
+        if let Some(running) = requests.running() {
+            ui.horizontal(|ui| {
+                let mut status = format!(
+                    "{} Waiting for {} ({:.1} s)",
+                    spinner(running.elapsed()),
+                    ai_client.config().provider.label(),
+                    running.elapsed().as_secs_f32(),
+                );
+                if requests.queued() > 0 {
+                    status.push_str(&format!(", {} queued", requests.queued()));
+                }
+                ui.label(status);
+                if ui.button("Cancel").clicked() {
+                    console_commands.send_batch(ConsoleCommand::parse("/ai cancel"));
+                }
+            });
         }
 
         // ui.heading("Console Output");
@@ -79,7 +158,14 @@ impl OutputConsole {
     }
 }
 
-pub fn console_ui_system(mut commands: Commands, aiClient: Res<AiClient>, mut console: ResMut<OutputConsole>, mut egui_contexts: bevy_egui::EguiContexts) {
+pub fn console_ui_system(
+    mut requests: ResMut<AiRequests>,
+    ai_client: Res<AiClient>,
+    units: Res<DocumentUnits>,
+    mut console: ResMut<OutputConsole>,
+    mut console_commands: EventWriter<ConsoleCommand>,
+    mut egui_contexts: bevy_egui::EguiContexts,
+) {
     // Renders a bottom panel for the console
     egui::Window::new("Console")
         .resizable(true)
@@ -88,7 +174,7 @@ pub fn console_ui_system(mut commands: Commands, aiClient: Res<AiClient>, mut co
         .fixed_size((400.0, 300.0))                // Example fixed size
         // .anchor(egui::Align2::CENTER_BOTTOM, [0.0, 0.0]) // Center it on the screen
         .show(egui_contexts.ctx_mut(), |ui| {
-            console.show(commands, aiClient, ui);
+            console.show(&mut requests, ai_client, &units, &mut console_commands, ui);
         });
 
     // egui::TopBottomPanel::bottom("console_panel")
@@ -97,27 +183,118 @@ pub fn console_ui_system(mut commands: Commands, aiClient: Res<AiClient>, mut co
     //     });
 }
 
+pub fn console_help_system(mut events: EventReader<ConsoleCommand>, mut console: ResMut<OutputConsole>) {
+    for command in events.read() {
+        let known = CONSOLE_COMMANDS.iter()
+            .any(|(usage, _)| usage.split_whitespace().next() == Some(command.name.as_str()));
+        if command.name == "help" {
+            for (usage, description) in CONSOLE_COMMANDS {
+                console.add_log(format!("/{} - {}", usage, description));
+            }
+        } else if !known {
+            console.add_log(format!("Unknown command '/{}', type /help for available commands", command.name));
+        }
+    }
+}
+
+/// Starts queued prompts in the background and queues the commands of the answers as they
+/// come in, the app keeps running while a request is in flight
 pub fn handle_api_response(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    tasks: Query<(Entity, &AsyncApiTask)>,
+    mut console: ResMut<OutputConsole>,
+    mut requests: ResMut<AiRequests>,
+    mut ai_commands: ResMut<AiCommandQueue>,
+    ai_client: Res<AiClient>,
     runtime: Res<AsyncRuntime>,
+    scene: SceneParts,
+    mut conversation: ResMut<Conversation>,
 ) {
-    for (entity, task) in tasks.iter() {
-        let result = runtime.0.block_on(async {
-            task.client.call_llm_api(&task.input).await
-        });
-
-        match result {
-            Ok(response) => {
-                process_console_ai_command(&response, &mut commands, &mut meshes, &mut materials);
+    for notice in requests.take_notices() {
+        console.add_log(notice);
+    }
+    if let Some((prompt, handle)) = requests.take_finished() {
+        // The task is done, this does not wait
+        match runtime.0.block_on(handle) {
+            Ok(Ok(reply)) if !reply.tool_calls.is_empty() => {
+                handle_tool_calls(reply, &prompt, &mut console, &mut conversation, &mut ai_commands);
+                if conversation.tool_rounds() < MAX_TOOL_ROUNDS {
+                    // Sends the results back once the calls ran, so the model can go on
+                    requests.push_front(AiPrompt { follow_up: true, ..prompt });
+                } else {
+                    console.add_log(format!("AI: stopped after {} rounds of tool calls", MAX_TOOL_ROUNDS));
+                }
+            }
+            Ok(Ok(reply)) => {
+                conversation.push_reply(reply.text.clone());
+                match parse_answer(&reply.text) {
+                    Ok(AiAnswer::Commands(commands)) => ai_commands.extend(commands, prompt.unit),
+                    Ok(AiAnswer::Question(question)) => console.add_log(format!("AI asks: {}", question)),
+                    // With function calling, an answer without calls is the model talking
+                    Err(_) if ai_client.config().use_tools && !reply.text.is_empty() => {
+                        console.add_log(format!("AI: {}", reply.text));
+                    }
+                    Err(e) => {
+                        console.add_log(format!("AI: {}", AiError::from(e.clone())));
+                        // So the model can correct itself in the next answer
+                        conversation.record_action(format!("rejected: {}", e));
+                    }
+                }
+            }
+            Ok(Err(e)) => {
+                conversation.discard_unanswered();
+                console.add_log(format!("AI request failed: {}", e));
             }
             Err(e) => {
-                println!("Error calling LLM API: {:?}", e);
+                conversation.discard_unanswered();
+                console.add_log(format!("AI request stopped: {}", e));
             }
         }
+    }
 
-        commands.entity(entity).despawn();
+    // The next prompt sees the parts the previous answer made
+    if !ai_commands.is_empty() {
+        return;
     }
-}
\ No newline at end of file
+    requests.start_next(|prompt, notices| {
+        let client = ai_client.clone();
+        let prompt = prompt.clone();
+        let scene = scene.describe(prompt.unit);
+        if !prompt.follow_up {
+            conversation.push_user(prompt.input.clone());
+        }
+        let history = conversation.history().to_vec();
+        runtime.0.spawn(async move {
+            client.call_llm_api(&history, prompt.unit, &scene, |notice| {
+                // The console is gone when the app closes mid-request
+                let _ = notices.send(notice);
+            }).await
+        })
+    });
+}
+
+/// Queues the tool calls of an answer, or reports them all as not run when one of them
+/// breaks the protocol
+fn handle_tool_calls(
+    reply: ModelReply,
+    prompt: &AiPrompt,
+    console: &mut OutputConsole,
+    conversation: &mut Conversation,
+    ai_commands: &mut AiCommandQueue,
+) {
+    if !reply.text.is_empty() {
+        console.add_log(format!("AI: {}", reply.text));
+    }
+    conversation.push(Message::tool_calls(reply.text, reply.tool_calls.clone()));
+    let commands: Result<Vec<_>, _> = reply.tool_calls.iter()
+        .enumerate()
+        .map(|(index, call)| parse_tool_call(index, call).map(|command| (command, call.clone())))
+        .collect();
+    match commands {
+        Ok(commands) => ai_commands.extend_calls(commands, prompt.unit),
+        Err(e) => {
+            console.add_log(format!("AI: {}", AiError::from(e.clone())));
+            for call in reply.tool_calls {
+                conversation.push(Message::tool_result(call, format!("not run, {}", e)));
+            }
+        }
+    }
+}
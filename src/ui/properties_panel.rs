@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::part::components::Part;
+use crate::part::mass_properties::{compute_mass_properties, MaterialDensity};
+use crate::tools::units::DocumentUnits;
+use super::components::PropertiesText;
+use super::output_console::{ConsoleCommand, OutputConsole};
+
+// Shows the mass properties of the part owning the current face selection
+pub fn update_properties_panel(
+    parts: Query<Ref<Part>>,
+    density: Res<MaterialDensity>,
+    units: Res<DocumentUnits>,
+    mut text_query: Query<&mut Text, With<PropertiesText>>,
+) {
+    let parts_changed = parts.iter().any(|part| part.is_changed());
+    if !parts_changed && !density.is_changed() && !units.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let mut lines = vec![format!("Density: {}", density.0)];
+    match parts.iter().find(|part| !part.selected_faces.is_empty()) {
+        Some(part) => match compute_mass_properties(&part, density.0) {
+            Ok(props) => {
+                let diagonal = |i: usize| props.inertia_centroid.col(i)[i];
+                lines.push(format!("Volume: {}", units.format_volume(props.volume)));
+                lines.push(format!("Mass: {}", units.format_number(props.mass)));
+                lines.push(format!("Surface area: {}", units.format_area(props.surface_area)));
+                lines.push(format!("Centre of mass:\n  {}", units.format_point(props.centroid)));
+                lines.push(format!(
+                    "Inertia (centroid, {}):\n  Ixx {}\n  Iyy {}\n  Izz {}",
+                    units.inertia_symbol(),
+                    units.format_inertia(diagonal(0)),
+                    units.format_inertia(diagonal(1)),
+                    units.format_inertia(diagonal(2)),
+                ));
+                let selected_area: f32 = props.face_areas.iter()
+                    .filter(|(name, _)| part.selected_faces.iter().any(|face| face.name == *name))
+                    .map(|(_, area)| area)
+                    .sum();
+                lines.push(format!("Selected face area: {}", units.format_area(selected_area)));
+            }
+            Err(e) => lines.push(format!("Mass properties unavailable: {}", e)),
+        },
+        None => lines.push("Select a face to show mass properties".to_string()),
+    }
+
+    **text = lines.join("\n");
+}
+
+pub fn mass_properties_console_system(
+    mut events: EventReader<ConsoleCommand>,
+    mut console: ResMut<OutputConsole>,
+    mut density: ResMut<MaterialDensity>,
+    units: Res<DocumentUnits>,
+    parts: Query<(Entity, &Part)>,
+) {
+    for command in events.read() {
+        match command.name.as_str() {
+            "mass" => {
+                // Selected parts only, or every part when nothing is selected
+                let any_selected = parts.iter().any(|(_, part)| !part.selected_faces.is_empty());
+                for (entity, part) in parts.iter() {
+                    if any_selected && part.selected_faces.is_empty() {
+                        continue;
+                    }
+                    match compute_mass_properties(part, density.0) {
+                        Ok(props) => {
+                            console.add_log(format!("Part {}:", entity));
+                            for line in props.report(&units) {
+                                console.add_log(line);
+                            }
+                        }
+                        Err(e) => console.add_log(format!("Part {}: {}", entity, e)),
+                    }
+                }
+            }
+            "density" => match command.args.first().map(|arg| arg.parse::<f32>()) {
+                Some(Ok(value)) if value > 0.0 => {
+                    density.0 = value;
+                    console.add_log(format!("Density set to {}", value));
+                }
+                _ => console.add_log("Usage: /density <positive number>"),
+            },
+            _ => {}
+        }
+    }
+}
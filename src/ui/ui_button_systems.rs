@@ -66,8 +66,10 @@ pub fn button_highlight_system(
 pub enum EditorMode {
     SelectFace,
     SelectEdge,
-    MoveFace,
-    RotatePart
+    SelectPart,
+    MovePart,
+    RotatePart,
+    Measure,
 }
 
 impl Default for EditorMode {
@@ -89,9 +91,11 @@ pub fn button_action_system(
         if *interaction == Interaction::Pressed {
             match button_type {
                 ToolbarButtonType::SelectEdgeMode |
+                ToolbarButtonType::SelectPartMode |
                 ToolbarButtonType::SelectFaceMode |
                 ToolbarButtonType::RotatePart |
-                ToolbarButtonType::MoveFace => {
+                ToolbarButtonType::MovePart |
+                ToolbarButtonType::Measure => {
                     if let Some(mut _toggle) = toggleable {
                         match button_type {
                             ToolbarButtonType::SelectEdgeMode => {
@@ -114,6 +118,16 @@ pub fn button_action_system(
                                 }
                                 button_events.send(ToolbarAction::SelectFaceMode);
                             }
+                            ToolbarButtonType::SelectPartMode => {
+                                *mode = EditorMode::SelectPart;
+                                // Clear all selections when switching modes
+                                for mut part in part_query.iter_mut() {
+                                    part.selected_faces.clear();
+                                    part.selected_edges.clear();
+                                    part.selected_vertices.clear();
+                                }
+                                button_events.send(ToolbarAction::SelectPartMode);
+                            }
                             ToolbarButtonType::RotatePart => {
                                 *mode = EditorMode::RotatePart;
                                 // Clear all selections when switching modes
@@ -124,15 +138,25 @@ pub fn button_action_system(
                                 }
                                 button_events.send(ToolbarAction::RotatePart);
                             }
-                            ToolbarButtonType::MoveFace => {
-                                *mode = EditorMode::MoveFace;
+                            ToolbarButtonType::MovePart => {
+                                *mode = EditorMode::MovePart;
                                 // Clear all selections when switching modes
                                 for mut part in part_query.iter_mut() {
                                     part.selected_faces.clear();
                                     part.selected_edges.clear();
                                     part.selected_vertices.clear();
                                 }
-                                button_events.send(ToolbarAction::MoveFace);
+                                button_events.send(ToolbarAction::MovePart);
+                            }
+                            ToolbarButtonType::Measure => {
+                                *mode = EditorMode::Measure;
+                                // Clear all selections when switching modes
+                                for mut part in part_query.iter_mut() {
+                                    part.selected_faces.clear();
+                                    part.selected_edges.clear();
+                                    part.selected_vertices.clear();
+                                }
+                                button_events.send(ToolbarAction::Measure);
                             }
                             _ => {}
                         }
@@ -164,6 +188,7 @@ pub fn handle_toolbar_actions(
     mut commands: Commands,
     mut events: EventReader<ToolbarAction>,
     mut part_query: Query<(Entity, &mut Part)>,
+    face_query: Query<(Entity, &Face, &Parent)>,
     extrusion_params: ResMut<ExtrusionParams>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
@@ -183,7 +208,6 @@ pub fn handle_toolbar_actions(
 
                 // test extrusion parameters
                 params.direction = Vec3::Y;
-                params.distance = 1.0;
 
                 for (entity, mut part) in part_query.iter_mut() {
                     println!("Selected face: {:?}", part.selected_faces);
@@ -191,6 +215,12 @@ pub fn handle_toolbar_actions(
                         warn(Result::Err("No faces selected for extrusion"));
                         continue;
                     }
+                    // The extruded faces end up inside the solid, remove their meshes
+                    for (face_entity, face, parent) in face_query.iter() {
+                        if parent.get() == entity && part.selected_faces.contains(face) {
+                            commands.entity(face_entity).despawn_recursive();
+                        }
+                    }
                     // Handle extrusion
                     // let extrusion_vector = params.direction * params.distance;
                     part::extrude_faces(&mut part, &params, &mut commands, &mut meshes, &mut materials, entity);
@@ -215,8 +245,12 @@ pub fn handle_toolbar_actions(
             ToolbarAction::SelectEdgeMode => {
                 // Handle edge selection mode
             }
+            ToolbarAction::SelectPartMode => {
+                // Handle part selection mode
+            }
             ToolbarAction::RotatePart => {},
-            ToolbarAction::MoveFace => {},
+            ToolbarAction::MovePart => {},
+            ToolbarAction::Measure => {},
         }
     }
 }
@@ -252,6 +286,14 @@ pub fn update_selection_mode_buttons(
                     _ => NORMAL_BUTTON_COLOR.into(),
                 };
             }
+            ToolbarButtonType::SelectPartMode => {
+                toggleable.is_active = matches!(*mode, EditorMode::SelectPart);
+                *color = match (*interaction, toggleable.is_active) {
+                    (Interaction::Hovered, false) => HOVERED_BUTTON_COLOR.into(),
+                    (_, true) => PRESSED_BUTTON_COLOR.into(),
+                    _ => NORMAL_BUTTON_COLOR.into(),
+                };
+            }
             ToolbarButtonType::RotatePart => {
                 toggleable.is_active = matches!(*mode, EditorMode::RotatePart);
                 *color = match (*interaction, toggleable.is_active) {
@@ -260,8 +302,16 @@ pub fn update_selection_mode_buttons(
                     _ => NORMAL_BUTTON_COLOR.into(),
                 };
             }
-            ToolbarButtonType::MoveFace => {
-                toggleable.is_active = matches!(*mode, EditorMode::MoveFace);
+            ToolbarButtonType::MovePart => {
+                toggleable.is_active = matches!(*mode, EditorMode::MovePart);
+                *color = match (*interaction, toggleable.is_active) {
+                    (Interaction::Hovered, false) => HOVERED_BUTTON_COLOR.into(),
+                    (_, true) => PRESSED_BUTTON_COLOR.into(),
+                    _ => NORMAL_BUTTON_COLOR.into(),
+                };
+            }
+            ToolbarButtonType::Measure => {
+                toggleable.is_active = matches!(*mode, EditorMode::Measure);
                 *color = match (*interaction, toggleable.is_active) {
                     (Interaction::Hovered, false) => HOVERED_BUTTON_COLOR.into(),
                     (_, true) => PRESSED_BUTTON_COLOR.into(),
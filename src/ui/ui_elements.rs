@@ -3,19 +3,21 @@ use bevy::utils::warn;
 use crate::part::{self, primitives};
 use crate::tools::colors::*;
 use super::components::*;
+use super::menu::Menu;
 
 //UI Constants
 const BUTTON_HEIGHT: f32 = 28.0;
 const BUTTON_MARGIN: f32 = 2.0;
 const TEXT_SIZE: f32 = 14.0;
 const HEADER_TEXT_SIZE: f32 = 13.0;
-const PANEL_PADDING: f32 = 5.0;
+pub const PANEL_PADDING: f32 = 5.0;
 const SECTION_SPACING: f32 = 10.0;
 
 // Panel dimensions
-const TOP_BAR_HEIGHT: f32 = 32.0;
-const LEFT_TOOLBAR_WIDTH: f32 = 160.0;
-const RIGHT_PANEL_WIDTH: f32 = 240.0;
+pub const TOP_BAR_HEIGHT: f32 = 32.0;
+pub const MENU_BUTTON_WIDTH: f32 = 80.0;
+pub const LEFT_TOOLBAR_WIDTH: f32 = 160.0;
+pub const RIGHT_PANEL_WIDTH: f32 = 240.0;
 
 #[derive(Bundle)]
 pub struct CustomTextBundle {
@@ -108,13 +110,11 @@ pub fn setup_ui(mut commands: Commands) {
 }
 
 fn setup_top_toolbar(parent: &mut ChildBuilder) {
-    let menu_items = ["File", "Edit", "View", "Window", "Help"];
-    
-    for item in menu_items {
+    for menu in Menu::ALL {
         parent.spawn((
             Button,
             Node {
-                width: Val::Px(80.0),
+                width: Val::Px(MENU_BUTTON_WIDTH),
                 height: Val::Px(TOP_BAR_HEIGHT),
                 margin: UiRect::right(Val::Px(1.0)),
                 justify_content: JustifyContent::Center,
@@ -125,9 +125,10 @@ fn setup_top_toolbar(parent: &mut ChildBuilder) {
             BorderColor(NEAR_BLACK),
             Interaction::None,
             ToolbarButton,
+            menu,
         ))
         .with_children(|parent| {
-            parent.spawn(CustomTextBundle::new(item, HEADER_TEXT_SIZE));
+            parent.spawn(CustomTextBundle::new(menu.label(), HEADER_TEXT_SIZE));
         });
     }
 }
@@ -150,6 +151,20 @@ fn setup_properties_panel(parent: &mut ChildBuilder) {
     .with_children(|parent| {
         parent.spawn(CustomTextBundle::new("Properties", HEADER_TEXT_SIZE));
     });
+
+    parent.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            padding: UiRect::horizontal(Val::Px(8.0)),
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            CustomTextBundle::new("", TEXT_SIZE),
+            PropertiesText,
+        ));
+    });
 }
 
 fn setup_side_toolbar(parent: &mut ChildBuilder) {
@@ -166,11 +181,15 @@ fn setup_side_toolbar(parent: &mut ChildBuilder) {
         ("Select", vec![
             ("Face", ToolbarButtonType::SelectFaceMode),
             ("Edge", ToolbarButtonType::SelectEdgeMode),
+            ("Part", ToolbarButtonType::SelectPartMode),
         ]),
         ("Transform", vec![
-            ("Move", ToolbarButtonType::MoveFace),
+            ("Move", ToolbarButtonType::MovePart),
             ("Rotate", ToolbarButtonType::RotatePart),
         ]),
+        ("Inspect", vec![
+            ("Measure", ToolbarButtonType::Measure),
+        ]),
     ];
 
     // Toolbar scroll container
@@ -230,8 +249,10 @@ fn spawn_tool_button(parent: &mut ChildBuilder, label: &str, button_type: Toolba
         button_type,
         ToolbarButtonType::SelectFaceMode
             | ToolbarButtonType::SelectEdgeMode
+            | ToolbarButtonType::SelectPartMode
             | ToolbarButtonType::RotatePart
-            | ToolbarButtonType::MoveFace
+            | ToolbarButtonType::MovePart
+            | ToolbarButtonType::Measure
     );
 
     let mut button = parent.spawn((
@@ -270,8 +291,10 @@ pub enum ToolbarAction {
     Delete,
     SelectFaceMode,
     SelectEdgeMode,
+    SelectPartMode,
     RotatePart,
-    MoveFace,
+    MovePart,
+    Measure,
 }
 
 pub fn add_box(
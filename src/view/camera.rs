@@ -1,5 +1,17 @@
 use bevy::prelude::*;
-use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::ecs::system::SystemParam;
+use bevy::input::gestures::PinchGesture;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::picking::pointer::PointerInteraction;
+use bevy_egui::EguiContexts;
+
+use crate::tools::preferences::Preferences;
+use super::display_mode::{DisplayMode, ViewportIndex};
+use super::navigation::{orbit_focus_around, zoom_focus_towards, NavAction, NavButton, NavModifier};
+use super::viewports::{viewport_cursor, ActiveViewport};
+
+/// Trackpad scrolling reports pixels, this many count as one wheel step
+const PIXELS_PER_LINE: f32 = 40.0;
 
 #[derive(Component)]
 pub struct PanOrbitCamera {
@@ -20,85 +32,182 @@ impl Default for PanOrbitCamera {
     }
 }
 
-pub fn spawn_camera() -> (Camera3d, PanOrbitCamera) {
-    (
-        Camera3d::default(),
-        PanOrbitCamera {
-            focus: Vec3::new(0.0, 1.0, 0.0),
-            radius: 400.0,
-            pitch: -15.0f32.to_radians(),
-            yaw: 0.0f32.to_radians(),
-        }
-    )
+impl PanOrbitCamera {
+    /// Camera rotation for the current yaw and pitch, it looks down its local -Z at the focus
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    pub fn apply_to(&self, transform: &mut Transform) {
+        transform.rotation = self.rotation();
+        transform.translation = self.focus + transform.rotation * Vec3::new(0.0, 0.0, self.radius);
+    }
+}
+
+pub fn spawn_camera() -> (Camera3d, Transform, PanOrbitCamera, DisplayMode, ViewportIndex) {
+    let pan_orbit = PanOrbitCamera {
+        focus: Vec3::new(0.0, 1.0, 0.0),
+        radius: 6.0,
+        pitch: -35.264f32.to_radians(),
+        yaw: 45.0f32.to_radians(),
+    };
+    let mut transform = Transform::default();
+    pan_orbit.apply_to(&mut transform);
+    (Camera3d::default(), transform, pan_orbit, DisplayMode::default(), ViewportIndex(0))
+}
+
+#[derive(SystemParam)]
+pub struct NavigationInput<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    mouse_button: Res<'w, ButtonInput<MouseButton>>,
+    mouse_motion: EventReader<'w, 's, MouseMotion>,
+    mouse_wheel: EventReader<'w, 's, MouseWheel>,
+    pinch: EventReader<'w, 's, PinchGesture>,
+    pointers: Query<'w, 's, &'static PointerInteraction>,
+    windows: Query<'w, 's, &'static Window>,
+    egui_contexts: EguiContexts<'w, 's>,
+}
+
+impl NavigationInput<'_, '_> {
+    fn modifier(&self) -> NavModifier {
+        NavModifier::ALL.into_iter()
+            .skip(1)
+            .find(|modifier| self.keyboard.any_pressed(modifier.keys().iter().copied()))
+            .unwrap_or_default()
+    }
+
+    // Nearest point of the model under the pointer
+    fn pointer_hit(&self) -> Option<Vec3> {
+        self.pointers.iter()
+            .filter_map(|interaction| interaction.get_nearest_hit())
+            .find_map(|(_, hit)| hit.position)
+    }
+}
+
+/// Drag that is in progress, it keeps its action until the button is released
+#[derive(Default)]
+pub struct NavigationDrag {
+    button: Option<NavButton>,
+    action: Option<NavAction>,
+    pivot: Option<Vec3>,
 }
 
 pub fn pan_orbit_camera(
-    mut query: Query<(&mut PanOrbitCamera, &mut Transform)>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mouse_button: Res<ButtonInput<MouseButton>>,
-    mut mouse_motion: EventReader<MouseMotion>,
-    mut mouse_wheel: EventReader<MouseWheel>,
+    mut query: Query<(&mut PanOrbitCamera, &mut Transform, &Camera, &GlobalTransform), With<ActiveViewport>>,
+    mut input: NavigationInput,
+    preferences: Res<Preferences>,
+    mut drag: Local<NavigationDrag>,
 ) {
-    let orbit_button = MouseButton::Right;
-    let pan_button = MouseButton::Left;
+    let scheme = &preferences.navigation;
+    let over_ui = input.egui_contexts.ctx_mut().is_pointer_over_area();
+
+    if drag.button.is_some_and(|button| !input.mouse_button.pressed(button.mouse_button())) {
+        *drag = NavigationDrag::default();
+    }
+    if drag.button.is_none() && !over_ui {
+        let modifier = input.modifier();
+        for button in NavButton::ALL {
+            if !input.mouse_button.just_pressed(button.mouse_button()) {
+                continue;
+            }
+            if let Some(action) = scheme.drag_action(button, modifier) {
+                let pivot = if action == NavAction::Orbit && scheme.orbit_around_cursor { input.pointer_hit() } else { None };
+                *drag = NavigationDrag { button: Some(button), action: Some(action), pivot };
+                break;
+            }
+        }
+    }
 
-    let mut pan = Vec2::ZERO;
     let mut rotation_move = Vec2::ZERO;
-    let mut scroll = 0.0;
-
-    // Handle mouse motion for orbit and pan
-    for ev in mouse_motion.read() {
-        if mouse_button.pressed(orbit_button) {
-            rotation_move += ev.delta;
-        } else if mouse_button.pressed(pan_button) && keyboard.pressed(KeyCode::AltLeft) {
-            pan += ev.delta;
+    let mut pan = Vec2::ZERO;
+    // Positive zooms in, in wheel steps
+    let mut zoom = 0.0;
+
+    let motion: Vec2 = input.mouse_motion.read().map(|ev| ev.delta).sum();
+    match drag.action {
+        Some(NavAction::Orbit) => rotation_move += motion,
+        Some(NavAction::Pan) => pan += motion,
+        Some(NavAction::Zoom) => zoom -= motion.y * 0.05,
+        None => {}
+    }
+
+    for ev in input.mouse_wheel.read() {
+        if over_ui {
+            continue;
+        }
+        match ev.unit {
+            MouseScrollUnit::Line => zoom += ev.y,
+            // Two finger scrolling on a trackpad
+            MouseScrollUnit::Pixel if scheme.trackpad_orbit => rotation_move -= Vec2::new(ev.x, ev.y),
+            MouseScrollUnit::Pixel => zoom += ev.y / PIXELS_PER_LINE,
         }
     }
-    
-    // Handle scroll for zoom
-    for ev in mouse_wheel.read() {
-        scroll += ev.y;
+    for ev in input.pinch.read() {
+        zoom += ev.0 * 10.0;
     }
+    if scheme.invert_zoom {
+        zoom = -zoom;
+    }
+
+    let hit = if zoom != 0.0 && scheme.zoom_to_cursor { input.pointer_hit() } else { None };
+    let cursor = input.windows.get_single().ok().and_then(|window| window.cursor_position());
 
-    for (mut pan_orbit, mut transform) in query.iter_mut() {
+    for (mut pan_orbit, mut transform, camera, camera_transform) in query.iter_mut() {
         let mut any_change = false;
 
         // Handle orbit (rotation)
         if rotation_move.length_squared() > 0.0 {
             any_change = true;
-            let sensitivity = 0.004;
-            pan_orbit.yaw -= rotation_move.x * sensitivity;
-            pan_orbit.pitch -= rotation_move.y * sensitivity;
-            
+            let before = pan_orbit.rotation();
+            pan_orbit.yaw -= rotation_move.x * scheme.orbit_sensitivity;
+            pan_orbit.pitch -= rotation_move.y * scheme.orbit_sensitivity;
+
             // Clamp pitch to prevent camera flipping
             pan_orbit.pitch = pan_orbit.pitch.clamp(
-                -std::f32::consts::FRAC_PI_2 * 0.99,
-                std::f32::consts::FRAC_PI_2 * 0.99,
+                -std::f32::consts::FRAC_PI_2,
+                std::f32::consts::FRAC_PI_2,
             );
+            if let Some(pivot) = drag.pivot {
+                let turn = pan_orbit.rotation() * before.inverse();
+                pan_orbit.focus = orbit_focus_around(pan_orbit.focus, pivot, turn);
+            }
         }
 
         // Handle pan
         if pan.length_squared() > 0.0 {
             any_change = true;
-            let pan_sensitivity = 0.002;
-            let right = transform.right() * -pan.x * pan_orbit.radius * pan_sensitivity;
-            let up = transform.up() * pan.y * pan_orbit.radius * pan_sensitivity;
+            let right = transform.right() * -pan.x * pan_orbit.radius * scheme.pan_sensitivity;
+            let up = transform.up() * pan.y * pan_orbit.radius * scheme.pan_sensitivity;
             pan_orbit.focus += right;
             pan_orbit.focus += up;
         }
 
         // Handle zoom
-        if scroll.abs() > 0.0 {
+        if zoom != 0.0 {
             any_change = true;
-            let scroll_sensitivity = if keyboard.pressed(KeyCode::ShiftLeft) { 0.5 } else { 0.1 };
-            pan_orbit.radius *= (1.0 - scroll.signum() * scroll_sensitivity).max(0.0001);
+            let sensitivity = if drag.action.is_none() && input.keyboard.pressed(KeyCode::ShiftLeft) {
+                scheme.zoom_sensitivity * 5.0
+            } else {
+                scheme.zoom_sensitivity
+            };
+            let factor = (1.0 - sensitivity.min(0.9)).powf(zoom).max(0.0001);
+            if scheme.zoom_to_cursor {
+                // Model point under the cursor, otherwise the cursor at the depth of the focus
+                let target = hit.or_else(|| {
+                    let ray = camera.viewport_to_world(camera_transform, viewport_cursor(camera, cursor?)).ok()?;
+                    let distance = ray.intersect_plane(pan_orbit.focus, InfinitePlane3d::new(transform.forward()))?;
+                    Some(ray.get_point(distance))
+                });
+                if let Some(target) = target {
+                    pan_orbit.focus = zoom_focus_towards(pan_orbit.focus, target, factor);
+                }
+            }
+            pan_orbit.radius *= factor;
         }
 
         // Update transform if anything changed
         if any_change {
-            let rot_matrix = Mat3::from_euler(EulerRot::YXZ, pan_orbit.yaw, pan_orbit.pitch, 0.0);
-            transform.translation = pan_orbit.focus + rot_matrix.mul_vec3(Vec3::new(0.0, 0.0, pan_orbit.radius));
-            transform.look_at(pan_orbit.focus, Vec3::Y);
+            pan_orbit.apply_to(&mut transform);
         }
     }
 }
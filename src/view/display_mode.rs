@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::tools::vec3_rounded::Vec3Rounded;
+
+/// Render layer of the shaded faces
+pub const SHADED_LAYER: usize = 1;
+/// Render layer of the background coloured faces that hide edges behind them
+pub const HIDDEN_LINE_LAYER: usize = 2;
+/// Render layer of the see-through faces
+pub const XRAY_LAYER: usize = 3;
+/// Edge lines of viewport `i` are on layer `EDGE_LAYER_START + i`, silhouettes differ per view
+pub const EDGE_LAYER_START: usize = 8;
+
+/// Adjacent faces closer to coplanar than this are smooth, their shared edge is only drawn
+/// where it is part of the silhouette
+const CREASE_ANGLE_DEGREES: f32 = 30.0;
+
+/// How a viewport draws the parts
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    Shaded,
+    #[default]
+    ShadedEdges,
+    Wireframe,
+    HiddenLine,
+    XRay,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 5] = [
+        DisplayMode::Shaded,
+        DisplayMode::ShadedEdges,
+        DisplayMode::Wireframe,
+        DisplayMode::HiddenLine,
+        DisplayMode::XRay,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Shaded => "shaded",
+            DisplayMode::ShadedEdges => "edges",
+            DisplayMode::Wireframe => "wireframe",
+            DisplayMode::HiddenLine => "hidden",
+            DisplayMode::XRay => "xray",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplayMode::Shaded => "Shaded",
+            DisplayMode::ShadedEdges => "Shaded with edges",
+            DisplayMode::Wireframe => "Wireframe",
+            DisplayMode::HiddenLine => "Hidden line",
+            DisplayMode::XRay => "X-ray",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        DisplayMode::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    pub fn shows_edges(&self) -> bool {
+        *self != DisplayMode::Shaded
+    }
+
+    /// Layers a viewport camera renders, layer 0 keeps the pickable faces and the rest of the
+    /// scene visible in every mode
+    pub fn render_layers(&self, viewport: usize) -> RenderLayers {
+        let faces = match self {
+            DisplayMode::Shaded | DisplayMode::ShadedEdges => Some(SHADED_LAYER),
+            DisplayMode::Wireframe => None,
+            DisplayMode::HiddenLine => Some(HIDDEN_LINE_LAYER),
+            DisplayMode::XRay => Some(XRAY_LAYER),
+        };
+        let edges = self.shows_edges().then_some(EDGE_LAYER_START + viewport);
+        let layers: Vec<usize> = [Some(0), faces, edges].into_iter().flatten().collect();
+        RenderLayers::from_layers(&layers)
+    }
+}
+
+/// Viewport a camera renders into, picks its edge layer
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ViewportIndex(pub usize);
+
+/// Where a view looks from, decides which faces turn towards it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Eye {
+    /// Perspective camera position
+    Position(Vec3),
+    /// Orthographic view direction
+    Direction(Vec3),
+}
+
+impl Eye {
+    pub fn faces(&self, normal: Vec3, point: Vec3) -> bool {
+        match self {
+            Eye::Position(eye) => normal.dot(*eye - point) > 0.0,
+            Eye::Direction(direction) => normal.dot(*direction) < 0.0,
+        }
+    }
+}
+
+/// A face in world space as far as edge drawing is concerned
+#[derive(Debug, Clone)]
+pub struct EdgeFace {
+    pub edges: Vec<[Vec3; 2]>,
+    pub normal: Vec3,
+    pub center: Vec3,
+}
+
+/// Edges worth drawing: open boundaries, creases between faces at an angle and, between
+/// smooth faces of faceted curved surfaces, the silhouette seen from `eye`
+pub fn visible_edges(faces: &[EdgeFace], eye: Eye) -> Vec<[Vec3; 2]> {
+    // Faces on either side of each edge, whichever direction it is walked in
+    let mut adjacent: HashMap<[Vec3Rounded; 2], ([Vec3; 2], Vec<usize>)> = HashMap::new();
+    let mut order = Vec::new();
+    for (index, face) in faces.iter().enumerate() {
+        for edge in &face.edges {
+            let (a, b) = (Vec3Rounded::from(edge[0]), Vec3Rounded::from(edge[1]));
+            let key = if edge_key_order(&a, &b) { [a, b] } else { [b, a] };
+            let entry = adjacent.entry(key).or_insert_with(|| {
+                order.push(key);
+                (*edge, Vec::new())
+            });
+            if !entry.1.contains(&index) {
+                entry.1.push(index);
+            }
+        }
+    }
+
+    let crease_cos = CREASE_ANGLE_DEGREES.to_radians().cos();
+    order.iter()
+        .map(|key| &adjacent[key])
+        .filter(|(_, neighbours)| match neighbours.as_slice() {
+            [a, b] => {
+                let (a, b) = (&faces[*a], &faces[*b]);
+                // Either orientation of the normals, faces are not always wound consistently
+                let crease = a.normal.normalize_or_zero().dot(b.normal.normalize_or_zero()).abs() < crease_cos;
+                crease || eye.faces(a.normal, a.center) != eye.faces(b.normal, b.center)
+            }
+            // Open boundary or a non-manifold edge
+            _ => true,
+        })
+        .map(|(edge, _)| *edge)
+        .collect()
+}
+
+fn edge_key_order(a: &Vec3Rounded, b: &Vec3Rounded) -> bool {
+    let (a, b) = (a.to_vec3(), b.to_vec3());
+    (a.x, a.y, a.z) <= (b.x, b.y, b.z)
+}
@@ -1,5 +1,23 @@
 mod camera;
+pub mod display_mode;
+pub mod section;
+pub mod navigation;
+pub mod standard_views;
+pub mod nav_cube;
+pub mod viewports;
+#[cfg(test)]
+pub mod test_standard_views;
+#[cfg(test)]
+pub mod test_nav_cube;
 
 pub use camera::PanOrbitCamera;
 pub use camera::pan_orbit_camera;
 pub use camera::spawn_camera;
+#[cfg(test)]
+pub mod test_navigation;
+#[cfg(test)]
+pub mod test_display_mode;
+#[cfg(test)]
+pub mod test_section;
+#[cfg(test)]
+pub mod test_viewports;
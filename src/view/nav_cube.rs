@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+/// Where a face of the navigation cube is split into edge and corner strips, the cube spans -1..1
+const STRIP_START: f32 = 0.6;
+
+/// Faces of the navigation cube with their labels, front is +Z to match the standard views
+pub const CUBE_FACES: [(Vec3, &str); 6] = [
+    (Vec3::Z, "FRONT"),
+    (Vec3::NEG_Z, "BACK"),
+    (Vec3::X, "RIGHT"),
+    (Vec3::NEG_X, "LEFT"),
+    (Vec3::Y, "TOP"),
+    (Vec3::NEG_Y, "BOTTOM"),
+];
+
+/// A clickable patch on the surface of the navigation cube. Face centres, edge strips and
+/// corners each map to the view from their direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeRegion {
+    /// Outward direction, sum of the normals of the faces the region touches
+    pub direction: Vec3,
+    /// Quad on the cube surface
+    pub corners: [Vec3; 4],
+    /// Normal of the face the quad lies on
+    pub face_normal: Vec3,
+}
+
+/// All 54 patches, 9 per face. Edge and corner directions appear on two or three faces.
+pub fn cube_regions() -> Vec<CubeRegion> {
+    let ranges = [(-1.0, -STRIP_START), (-STRIP_START, STRIP_START), (STRIP_START, 1.0)];
+    let mut regions = Vec::with_capacity(54);
+    for (normal, _) in CUBE_FACES {
+        let (a, b) = face_axes(normal);
+        for (i, (a0, a1)) in ranges.iter().enumerate() {
+            for (j, (b0, b1)) in ranges.iter().enumerate() {
+                regions.push(CubeRegion {
+                    direction: normal + a * (i as f32 - 1.0) + b * (j as f32 - 1.0),
+                    corners: [
+                        normal + a * *a0 + b * *b0,
+                        normal + a * *a1 + b * *b0,
+                        normal + a * *a1 + b * *b1,
+                        normal + a * *a0 + b * *b1,
+                    ],
+                    face_normal: normal,
+                });
+            }
+        }
+    }
+    regions
+}
+
+// Two axes spanning the face with this normal
+fn face_axes(normal: Vec3) -> (Vec3, Vec3) {
+    if normal.x != 0.0 {
+        (Vec3::Z, Vec3::Y)
+    } else if normal.y != 0.0 {
+        (Vec3::X, Vec3::Z)
+    } else {
+        (Vec3::X, Vec3::Y)
+    }
+}
+
+/// Name of the view from a region direction, e.g. "Top Front Right"
+pub fn region_name(direction: Vec3) -> String {
+    let mut parts = Vec::new();
+    if direction.y > 0.5 { parts.push("Top") } else if direction.y < -0.5 { parts.push("Bottom") }
+    if direction.z > 0.5 { parts.push("Front") } else if direction.z < -0.5 { parts.push("Back") }
+    if direction.x > 0.5 { parts.push("Right") } else if direction.x < -0.5 { parts.push("Left") }
+    parts.join(" ")
+}
+
+/// Yaw and pitch of a `PanOrbitCamera` placed in `direction` from its focus
+pub fn yaw_pitch_from_direction(direction: Vec3) -> (f32, f32) {
+    let direction = direction.normalize_or(Vec3::Z);
+    let pitch = -direction.y.clamp(-1.0, 1.0).asin();
+    // Straight up or down keeps a yaw of zero so the front stays at the bottom of the view
+    let yaw = if direction.x.abs() < 1e-6 && direction.z.abs() < 1e-6 { 0.0 } else { direction.x.atan2(direction.z) };
+    (yaw, pitch)
+}
+
+/// Point in convex polygon test in screen space, for either winding
+pub fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut sign = 0.0;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        let cross = (b - a).perp_dot(point - a);
+        if cross.abs() < f32::EPSILON {
+            continue;
+        }
+        if sign == 0.0 {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+    sign != 0.0
+}
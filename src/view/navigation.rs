@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavAction {
+    Orbit,
+    Pan,
+    Zoom,
+}
+
+impl NavAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NavAction::Orbit => "Orbit",
+            NavAction::Pan => "Pan",
+            NavAction::Zoom => "Zoom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavButton {
+    Left,
+    Middle,
+    Right,
+}
+
+impl NavButton {
+    pub const ALL: [NavButton; 3] = [NavButton::Left, NavButton::Middle, NavButton::Right];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NavButton::Left => "Left button",
+            NavButton::Middle => "Middle button",
+            NavButton::Right => "Right button",
+        }
+    }
+
+    pub fn mouse_button(&self) -> MouseButton {
+        match self {
+            NavButton::Left => MouseButton::Left,
+            NavButton::Middle => MouseButton::Middle,
+            NavButton::Right => MouseButton::Right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavModifier {
+    #[default]
+    None,
+    Shift,
+    Ctrl,
+    Alt,
+}
+
+impl NavModifier {
+    pub const ALL: [NavModifier; 4] = [NavModifier::None, NavModifier::Shift, NavModifier::Ctrl, NavModifier::Alt];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NavModifier::None => "-",
+            NavModifier::Shift => "Shift",
+            NavModifier::Ctrl => "Ctrl",
+            NavModifier::Alt => "Alt",
+        }
+    }
+
+    pub fn keys(&self) -> &'static [KeyCode] {
+        match self {
+            NavModifier::None => &[],
+            NavModifier::Shift => &[KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            NavModifier::Ctrl => &[KeyCode::ControlLeft, KeyCode::ControlRight],
+            NavModifier::Alt => &[KeyCode::AltLeft, KeyCode::AltRight],
+        }
+    }
+}
+
+/// Mouse button plus the modifier that has to be held with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MouseBinding {
+    pub button: NavButton,
+    pub modifier: NavModifier,
+}
+
+impl MouseBinding {
+    pub const fn new(button: NavButton, modifier: NavModifier) -> Self {
+        MouseBinding { button, modifier }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavigationPreset {
+    Default,
+    Blender,
+    SolidWorks,
+    Fusion,
+    Custom,
+}
+
+impl NavigationPreset {
+    pub const ALL: [NavigationPreset; 4] = [
+        NavigationPreset::Default,
+        NavigationPreset::Blender,
+        NavigationPreset::SolidWorks,
+        NavigationPreset::Fusion,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NavigationPreset::Default => "default",
+            NavigationPreset::Blender => "blender",
+            NavigationPreset::SolidWorks => "solidworks",
+            NavigationPreset::Fusion => "fusion",
+            NavigationPreset::Custom => "custom",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NavigationPreset::Default => "Default",
+            NavigationPreset::Blender => "Blender",
+            NavigationPreset::SolidWorks => "SolidWorks",
+            NavigationPreset::Fusion => "Fusion 360",
+            NavigationPreset::Custom => "Custom",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        NavigationPreset::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+}
+
+/// Mouse bindings and behaviour of the viewport camera
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavigationScheme {
+    pub preset: NavigationPreset,
+    pub orbit: MouseBinding,
+    pub pan: MouseBinding,
+    /// Zoom by dragging, the wheel always zooms
+    pub zoom: Option<MouseBinding>,
+    pub invert_zoom: bool,
+    /// Orbit around the point under the cursor instead of the focus
+    pub orbit_around_cursor: bool,
+    /// Keep the point under the cursor in place while zooming
+    pub zoom_to_cursor: bool,
+    /// Two finger trackpad scrolling orbits instead of zooming, pinching zooms
+    pub trackpad_orbit: bool,
+    pub orbit_sensitivity: f32,
+    pub pan_sensitivity: f32,
+    pub zoom_sensitivity: f32,
+}
+
+impl Default for NavigationScheme {
+    fn default() -> Self {
+        NavigationScheme::preset(NavigationPreset::Default)
+    }
+}
+
+impl NavigationScheme {
+    pub fn preset(preset: NavigationPreset) -> Self {
+        use NavButton::*;
+        use NavModifier as M;
+
+        let base = NavigationScheme {
+            preset,
+            orbit: MouseBinding::new(Right, M::None),
+            pan: MouseBinding::new(Left, M::Alt),
+            zoom: None,
+            invert_zoom: false,
+            orbit_around_cursor: false,
+            zoom_to_cursor: false,
+            trackpad_orbit: false,
+            orbit_sensitivity: 0.004,
+            pan_sensitivity: 0.002,
+            zoom_sensitivity: 0.1,
+        };
+        match preset {
+            NavigationPreset::Default | NavigationPreset::Custom => base,
+            NavigationPreset::Blender => NavigationScheme {
+                orbit: MouseBinding::new(Middle, M::None),
+                pan: MouseBinding::new(Middle, M::Shift),
+                zoom: Some(MouseBinding::new(Middle, M::Ctrl)),
+                ..base
+            },
+            NavigationPreset::SolidWorks => NavigationScheme {
+                orbit: MouseBinding::new(Middle, M::None),
+                pan: MouseBinding::new(Middle, M::Ctrl),
+                zoom: Some(MouseBinding::new(Middle, M::Shift)),
+                invert_zoom: true,
+                orbit_around_cursor: true,
+                zoom_to_cursor: true,
+                ..base
+            },
+            NavigationPreset::Fusion => NavigationScheme {
+                orbit: MouseBinding::new(Middle, M::Shift),
+                pan: MouseBinding::new(Middle, M::None),
+                zoom: None,
+                orbit_around_cursor: true,
+                zoom_to_cursor: true,
+                trackpad_orbit: true,
+                ..base
+            },
+        }
+    }
+
+    /// Action for a drag with `button` while `modifier` is held, an exact modifier match wins
+    /// over a binding without modifier
+    pub fn drag_action(&self, button: NavButton, modifier: NavModifier) -> Option<NavAction> {
+        let bindings = [
+            (Some(self.orbit), NavAction::Orbit),
+            (Some(self.pan), NavAction::Pan),
+            (self.zoom, NavAction::Zoom),
+        ];
+        let matching = |modifier: NavModifier| {
+            bindings.iter()
+                .find(|(binding, _)| *binding == Some(MouseBinding::new(button, modifier)))
+                .map(|(_, action)| *action)
+        };
+        matching(modifier).or_else(|| if modifier == NavModifier::None { None } else { matching(NavModifier::None) })
+    }
+
+    /// Bindings used by more than one action, only the first of them can ever trigger
+    pub fn conflicts(&self) -> Vec<(NavAction, NavAction)> {
+        let bindings = [
+            (Some(self.orbit), NavAction::Orbit),
+            (Some(self.pan), NavAction::Pan),
+            (self.zoom, NavAction::Zoom),
+        ];
+        let mut conflicts = Vec::new();
+        for (i, (a, action_a)) in bindings.iter().enumerate() {
+            for (b, action_b) in &bindings[i + 1..] {
+                if a.is_some() && a == b {
+                    conflicts.push((*action_a, *action_b));
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// Moves `focus` so the camera turns by `rotation` around `pivot` instead of around the focus
+pub fn orbit_focus_around(focus: Vec3, pivot: Vec3, rotation: Quat) -> Vec3 {
+    pivot + rotation * (focus - pivot)
+}
+
+/// Focus after scaling the view distance by `factor` towards `target`, so the target stays
+/// at the same place on screen
+pub fn zoom_focus_towards(focus: Vec3, target: Vec3, factor: f32) -> Vec3 {
+    target + (focus - target) * factor
+}
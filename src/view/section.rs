@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+
+use crate::tools::grid::WorkPlane;
+use crate::tools::polygon::{ear_clip, point_in_polygon, segments_cross, signed_area};
+use crate::tools::vec3_rounded::Vec3Rounded;
+
+/// The clipping shader has room for this many planes
+pub const MAX_SECTION_PLANES: usize = 4;
+
+/// Plane that never clips anything, fills the unused shader slots
+const NO_CLIP: Vec4 = Vec4::new(0.0, 0.0, 0.0, 1.0);
+
+/// Standard material that discards everything in front of the section planes
+pub type SectionMaterial = ExtendedMaterial<StandardMaterial, SectionClip>;
+
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct SectionClip {
+    /// Normal and offset of each plane, see `SectionPlane::clip_vec4`
+    #[uniform(100)]
+    pub planes: [Vec4; MAX_SECTION_PLANES],
+}
+
+impl Default for SectionClip {
+    fn default() -> Self {
+        SectionClip { planes: [NO_CLIP; MAX_SECTION_PLANES] }
+    }
+}
+
+impl MaterialExtension for SectionClip {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/section.wgsl".into()
+    }
+}
+
+pub fn section_material(base: StandardMaterial, planes: &SectionPlanes) -> SectionMaterial {
+    SectionMaterial { base, extension: SectionClip { planes: planes.clip_vec4s() } }
+}
+
+/// A cut through the model, everything on the side the normal points to is hidden
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SectionPlane {
+    pub origin: Vec3,
+    pub normal: Vec3,
+    pub enabled: bool,
+}
+
+impl SectionPlane {
+    pub fn new(origin: Vec3, normal: Vec3) -> Self {
+        SectionPlane { origin, normal: normal.normalize_or(Vec3::X), enabled: true }
+    }
+
+    /// Signed distance, positive on the hidden side
+    pub fn distance(&self, point: Vec3) -> f32 {
+        (point - self.origin).dot(self.normal)
+    }
+
+    /// `xyz` normal and `w` offset, the shader discards points with `dot(xyz, p) > w`
+    pub fn clip_vec4(&self) -> Vec4 {
+        if self.enabled { self.normal.extend(self.normal.dot(self.origin)) } else { NO_CLIP }
+    }
+
+    pub fn flipped(&self) -> Self {
+        SectionPlane { normal: -self.normal, ..*self }
+    }
+
+    pub fn work_plane(&self) -> WorkPlane {
+        WorkPlane::new(self.origin, self.normal, self.normal.any_orthonormal_pair().0)
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct SectionPlanes {
+    pub planes: Vec<SectionPlane>,
+    /// Plane the console commands and the drag handle work on
+    pub active: usize,
+}
+
+impl SectionPlanes {
+    pub fn clip_vec4s(&self) -> [Vec4; MAX_SECTION_PLANES] {
+        let mut planes = [NO_CLIP; MAX_SECTION_PLANES];
+        for (slot, plane) in planes.iter_mut().zip(&self.planes) {
+            *slot = plane.clip_vec4();
+        }
+        planes
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut SectionPlane> {
+        self.planes.get_mut(self.active)
+    }
+}
+
+/// Segments where `plane` crosses the polygon, pairs the crossings along the cut line so
+/// concave polygons give more than one segment
+pub fn cut_polygon(polygon: &[Vec3], plane: &SectionPlane) -> Vec<[Vec3; 2]> {
+    let mut crossings = Vec::new();
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        let (da, db) = (plane.distance(a), plane.distance(b));
+        // Half open so a vertex on the plane is counted once
+        if (da < 0.0) != (db < 0.0) {
+            crossings.push(a + (b - a) * (da / (da - db)));
+        }
+    }
+    let Some(&first) = crossings.first() else {
+        return Vec::new();
+    };
+    let direction = crossings.iter().map(|p| *p - first).fold(Vec3::ZERO, |longest, d| {
+        if d.length_squared() > longest.length_squared() { d } else { longest }
+    });
+    crossings.sort_by(|a, b| (*a - first).dot(direction).total_cmp(&(*b - first).dot(direction)));
+    crossings.chunks_exact(2).map(|pair| [pair[0], pair[1]]).collect()
+}
+
+/// Joins segments sharing end points into closed loops, open chains are dropped
+pub fn chain_loops(segments: &[[Vec3; 2]]) -> Vec<Vec<Vec3>> {
+    let key = |p: Vec3| Vec3Rounded::with_decimals(p, 4);
+    let mut at_point: HashMap<Vec3Rounded, Vec<usize>> = HashMap::new();
+    for (i, [a, b]) in segments.iter().enumerate() {
+        if key(*a) == key(*b) {
+            continue;
+        }
+        at_point.entry(key(*a)).or_default().push(i);
+        at_point.entry(key(*b)).or_default().push(i);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut loops = Vec::new();
+    for start in 0..segments.len() {
+        if used[start] || !at_point.contains_key(&key(segments[start][0])) {
+            continue;
+        }
+        used[start] = true;
+        let mut points = vec![segments[start][0]];
+        let mut current = segments[start][1];
+        let closed = loop {
+            if key(current) == key(points[0]) {
+                break true;
+            }
+            points.push(current);
+            let next = at_point.get(&key(current))
+                .and_then(|candidates| candidates.iter().find(|i| !used[**i]).copied());
+            let Some(next) = next else {
+                break false;
+            };
+            used[next] = true;
+            let [a, b] = segments[next];
+            current = if key(a) == key(current) { b } else { a };
+        };
+        if closed && points.len() >= 3 {
+            loops.push(points);
+        }
+    }
+    loops
+}
+
+/// Triangles filling the cut loops on `plane`, wound counter-clockwise seen from the hidden
+/// side. Loops nested an odd number of times are holes.
+pub fn cap_triangles(loops: &[Vec<Vec3>], plane: &SectionPlane) -> Vec<[Vec3; 3]> {
+    let work_plane = plane.work_plane();
+    let polygons: Vec<Vec<Vec2>> = loops.iter()
+        .map(|points| points.iter().map(|p| work_plane.to_local(*p)).collect())
+        .collect();
+
+    let depth = |i: usize| {
+        polygons.iter().enumerate()
+            .filter(|(j, other)| *j != i && point_in_polygon(other, polygons[i][0]))
+            .count()
+    };
+    let depths: Vec<usize> = (0..polygons.len()).map(depth).collect();
+
+    let mut triangles = Vec::new();
+    for (outer_index, outer) in polygons.iter().enumerate().filter(|(i, _)| depths[*i].is_multiple_of(2)) {
+        let mut outer = with_winding(outer, true);
+        let mut holes: Vec<Vec<Vec2>> = polygons.iter().enumerate()
+            .filter(|(i, hole)| depths[*i] == depths[outer_index] + 1 && point_in_polygon(&outer, hole[0]))
+            .map(|(_, hole)| with_winding(hole, false))
+            .collect();
+        // Rightmost holes first so earlier bridges do not block later ones
+        holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+        for hole in &holes {
+            outer = bridge_hole(&outer, hole);
+        }
+        for [a, b, c] in ear_clip(&outer) {
+            triangles.push([work_plane.to_world(a), work_plane.to_world(b), work_plane.to_world(c)]);
+        }
+    }
+    triangles
+}
+
+fn with_winding(polygon: &[Vec2], counter_clockwise: bool) -> Vec<Vec2> {
+    let mut polygon = polygon.to_vec();
+    if (signed_area(&polygon) > 0.0) != counter_clockwise {
+        polygon.reverse();
+    }
+    polygon
+}
+
+fn max_x(polygon: &[Vec2]) -> f32 {
+    polygon.iter().fold(f32::MIN, |max, p| max.max(p.x))
+}
+
+// Cuts the outer polygon open to the hole with a zero width channel, giving one polygon
+fn bridge_hole(outer: &[Vec2], hole: &[Vec2]) -> Vec<Vec2> {
+    let (hole_index, &m) = hole.iter().enumerate().max_by(|a, b| a.1.x.total_cmp(&b.1.x)).unwrap();
+    let mut candidates: Vec<usize> = (0..outer.len()).collect();
+    candidates.sort_by(|a, b| outer[*a].distance_squared(m).total_cmp(&outer[*b].distance_squared(m)));
+    let visible = |p: Vec2| {
+        let blocks = |polygon: &[Vec2]| {
+            (0..polygon.len()).any(|i| segments_cross(m, p, polygon[i], polygon[(i + 1) % polygon.len()]))
+        };
+        !blocks(outer) && !blocks(hole)
+    };
+    let outer_index = candidates.iter().copied().find(|i| visible(outer[*i])).unwrap_or(candidates[0]);
+
+    let mut bridged = outer[..=outer_index].to_vec();
+    bridged.extend(hole[hole_index..].iter().chain(&hole[..=hole_index]));
+    bridged.extend(&outer[outer_index..]);
+    bridged
+}
+
+/// Parameter along the line `origin + axis * t` of the point closest to the ray, used to drag
+/// a plane along its normal. None when the ray runs parallel to the axis.
+pub fn closest_on_axis(origin: Vec3, axis: Vec3, ray_origin: Vec3, ray_direction: Vec3) -> Option<f32> {
+    let (b, c) = (axis.dot(ray_direction), ray_direction.dot(ray_direction));
+    let a = axis.dot(axis);
+    let w = origin - ray_origin;
+    let (d, e) = (axis.dot(w), ray_direction.dot(w));
+    let denominator = a * c - b * b;
+    (denominator.abs() > 1e-6).then(|| (b * e - c * d) / denominator)
+}
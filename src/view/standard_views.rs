@@ -0,0 +1,128 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use bevy::prelude::*;
+
+use super::camera::PanOrbitCamera;
+
+/// Duration of animated view changes
+pub const TRANSITION_SECONDS: f32 = 0.35;
+
+/// Room left around the fitted bounds, as a factor of their size
+const FIT_MARGIN: f32 = 1.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandardView {
+    Front,
+    Back,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    Isometric,
+}
+
+impl StandardView {
+    pub const ALL: [StandardView; 7] = [
+        StandardView::Front,
+        StandardView::Back,
+        StandardView::Top,
+        StandardView::Bottom,
+        StandardView::Left,
+        StandardView::Right,
+        StandardView::Isometric,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StandardView::Front => "front",
+            StandardView::Back => "back",
+            StandardView::Top => "top",
+            StandardView::Bottom => "bottom",
+            StandardView::Left => "left",
+            StandardView::Right => "right",
+            StandardView::Isometric => "iso",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "isometric" => Some(StandardView::Isometric),
+            name => StandardView::ALL.into_iter().find(|view| view.name() == name),
+        }
+    }
+
+    /// Yaw and pitch of a camera looking at the focus from this side
+    pub fn yaw_pitch(&self) -> (f32, f32) {
+        match self {
+            StandardView::Front => (0.0, 0.0),
+            StandardView::Back => (PI, 0.0),
+            StandardView::Top => (0.0, -FRAC_PI_2),
+            StandardView::Bottom => (0.0, FRAC_PI_2),
+            StandardView::Left => (-FRAC_PI_2, 0.0),
+            StandardView::Right => (FRAC_PI_2, 0.0),
+            // Looks down the diagonal of a cube, all three axes foreshortened the same
+            StandardView::Isometric => (FRAC_PI_4, -(1.0 / 2f32.sqrt()).atan()),
+        }
+    }
+}
+
+/// Focus and radius that fit the box from `min` to `max` into a view with vertical field of
+/// view `fov` and `aspect` ratio
+pub fn fit_bounds(min: Vec3, max: Vec3, fov: f32, aspect: f32) -> (Vec3, f32) {
+    let center = (min + max) * 0.5;
+    let sphere_radius = ((max - min).length() * 0.5).max(0.01);
+    // The narrower of the two fields of view decides
+    let half_fov = (fov * 0.5).min(((fov * 0.5).tan() * aspect).atan());
+    (center, sphere_radius / half_fov.sin() * FIT_MARGIN)
+}
+
+/// Smooth move of a `PanOrbitCamera` to a new view
+#[derive(Component, Debug, Clone)]
+pub struct ViewTransition {
+    from: OrbitPose,
+    to: OrbitPose,
+    elapsed: f32,
+    duration: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitPose {
+    pub focus: Vec3,
+    pub radius: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl From<&PanOrbitCamera> for OrbitPose {
+    fn from(camera: &PanOrbitCamera) -> Self {
+        OrbitPose { focus: camera.focus, radius: camera.radius, yaw: camera.yaw, pitch: camera.pitch }
+    }
+}
+
+impl ViewTransition {
+    pub fn new(from: OrbitPose, mut to: OrbitPose, duration: f32) -> Self {
+        // Turn the short way round
+        to.yaw = from.yaw + wrap_angle(to.yaw - from.yaw);
+        ViewTransition { from, to, elapsed: 0.0, duration }
+    }
+
+    /// Advances the transition, returns the pose and whether it has finished
+    pub fn step(&mut self, delta: f32) -> (OrbitPose, bool) {
+        self.elapsed += delta;
+        let t = if self.duration > 0.0 { (self.elapsed / self.duration).min(1.0) } else { 1.0 };
+        // Ease in and out
+        let s = t * t * (3.0 - 2.0 * t);
+        let pose = OrbitPose {
+            focus: self.from.focus.lerp(self.to.focus, s),
+            // Zoom geometrically so near and far views change at the same pace
+            radius: self.from.radius * (self.to.radius / self.from.radius).powf(s),
+            yaw: self.from.yaw + (self.to.yaw - self.from.yaw) * s,
+            pitch: self.from.pitch + (self.to.pitch - self.from.pitch) * s,
+        };
+        (pose, t >= 1.0)
+    }
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
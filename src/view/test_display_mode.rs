@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use bevy::math::Vec3;
+    use bevy::render::view::RenderLayers;
+    use super::super::display_mode::{visible_edges, DisplayMode, EdgeFace, Eye, EDGE_LAYER_START, SHADED_LAYER};
+
+    fn quad(corners: [Vec3; 4]) -> EdgeFace {
+        let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]).normalize();
+        EdgeFace {
+            edges: (0..4).map(|i| [corners[i], corners[(i + 1) % 4]]).collect(),
+            normal,
+            center: corners.iter().sum::<Vec3>() / 4.0,
+        }
+    }
+
+    #[test]
+    fn test_box_shows_every_edge() {
+        let p = |x: f32, y: f32, z: f32| Vec3::new(x, y, z);
+        let faces = [
+            quad([p(0., 0., 0.), p(0., 1., 0.), p(1., 1., 0.), p(1., 0., 0.)]),
+            quad([p(0., 0., 1.), p(1., 0., 1.), p(1., 1., 1.), p(0., 1., 1.)]),
+            quad([p(1., 0., 0.), p(1., 1., 0.), p(1., 1., 1.), p(1., 0., 1.)]),
+            quad([p(0., 0., 1.), p(0., 1., 1.), p(0., 1., 0.), p(0., 0., 0.)]),
+            quad([p(0., 1., 1.), p(1., 1., 1.), p(1., 1., 0.), p(0., 1., 0.)]),
+            quad([p(0., 0., 0.), p(1., 0., 0.), p(1., 0., 1.), p(0., 0., 1.)]),
+        ];
+        // Shared edges are drawn once, all of them are creases
+        assert_eq!(visible_edges(&faces, Eye::Position(Vec3::splat(5.0))).len(), 12);
+        // A lone face only has boundary edges
+        assert_eq!(visible_edges(&faces[..1], Eye::Direction(Vec3::NEG_Z)).len(), 4);
+    }
+
+    #[test]
+    fn test_faceted_cylinder_silhouette() {
+        // 32 sided open tube around the Y axis, neighbouring facets are nearly coplanar
+        let sides = 32;
+        let ring = |i: usize, y: f32| {
+            let angle = TAU * i as f32 / sides as f32;
+            Vec3::new(angle.cos(), y, angle.sin())
+        };
+        let faces: Vec<EdgeFace> = (0..sides)
+            .map(|i| quad([ring(i, 0.0), ring(i, 1.0), ring(i + 1, 1.0), ring(i + 1, 0.0)]))
+            .collect();
+
+        let edges = visible_edges(&faces, Eye::Direction(Vec3::NEG_Z));
+        // Top and bottom rims plus the two vertical silhouette lines
+        let vertical: Vec<_> = edges.iter().filter(|[a, b]| (a.y - b.y).abs() > 0.5).collect();
+        assert_eq!(edges.len(), 2 * sides + 2);
+        assert_eq!(vertical.len(), 2);
+        assert!(vertical.iter().all(|[a, _]| a.x.abs() > 0.99));
+    }
+
+    #[test]
+    fn test_render_layers() {
+        assert_eq!(DisplayMode::Shaded.render_layers(0), RenderLayers::from_layers(&[0, SHADED_LAYER]));
+        assert_eq!(DisplayMode::Wireframe.render_layers(2), RenderLayers::from_layers(&[0, EDGE_LAYER_START + 2]));
+        for mode in DisplayMode::ALL {
+            assert_eq!(DisplayMode::from_name(mode.name()), Some(mode));
+        }
+    }
+}
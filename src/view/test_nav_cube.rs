@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::f32::consts::FRAC_PI_2;
+    use bevy::math::{Vec2, Vec3};
+    use super::super::nav_cube::{cube_regions, polygon_contains, region_name, yaw_pitch_from_direction};
+    use super::super::standard_views::StandardView;
+
+    #[test]
+    fn test_regions_cover_all_views() {
+        let regions = cube_regions();
+        assert_eq!(regions.len(), 54);
+        // 6 faces, 12 edges and 8 corners
+        let directions: HashSet<[i32; 3]> = regions.iter()
+            .map(|region| region.direction.round().as_ivec3().to_array())
+            .collect();
+        assert_eq!(directions.len(), 26);
+        assert_eq!(region_name(Vec3::new(1.0, 1.0, 1.0)), "Top Front Right");
+        assert_eq!(region_name(Vec3::NEG_Z), "Back");
+    }
+
+    #[test]
+    fn test_directions_match_standard_views() {
+        for (direction, view) in [
+            (Vec3::Z, StandardView::Front),
+            (Vec3::X, StandardView::Right),
+            (Vec3::Y, StandardView::Top),
+            (Vec3::ONE, StandardView::Isometric),
+        ] {
+            let (yaw, pitch) = yaw_pitch_from_direction(direction);
+            let (expected_yaw, expected_pitch) = view.yaw_pitch();
+            assert!((yaw - expected_yaw).abs() < 1e-5 && (pitch - expected_pitch).abs() < 1e-5, "{:?}", view);
+        }
+        assert!((yaw_pitch_from_direction(Vec3::NEG_Y).1 - FRAC_PI_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        let square = [Vec2::ZERO, Vec2::new(0.0, 2.0), Vec2::new(2.0, 2.0), Vec2::new(2.0, 0.0)];
+        assert!(polygon_contains(&square, Vec2::ONE));
+        assert!(!polygon_contains(&square, Vec2::new(3.0, 1.0)));
+    }
+}
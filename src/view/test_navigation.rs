@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::{Quat, Vec3};
+    use super::super::navigation::{
+        orbit_focus_around, zoom_focus_towards, MouseBinding, NavAction, NavButton, NavModifier, NavigationPreset,
+        NavigationScheme,
+    };
+
+    #[test]
+    fn test_preset_bindings() {
+        let blender = NavigationScheme::preset(NavigationPreset::Blender);
+        assert_eq!(blender.drag_action(NavButton::Middle, NavModifier::None), Some(NavAction::Orbit));
+        assert_eq!(blender.drag_action(NavButton::Middle, NavModifier::Shift), Some(NavAction::Pan));
+        assert_eq!(blender.drag_action(NavButton::Middle, NavModifier::Ctrl), Some(NavAction::Zoom));
+        // Left clicks stay free for selection
+        assert_eq!(blender.drag_action(NavButton::Left, NavModifier::None), None);
+
+        let fusion = NavigationScheme::preset(NavigationPreset::Fusion);
+        assert_eq!(fusion.drag_action(NavButton::Middle, NavModifier::None), Some(NavAction::Pan));
+        assert_eq!(fusion.drag_action(NavButton::Middle, NavModifier::Shift), Some(NavAction::Orbit));
+        // An unbound modifier falls back to the plain binding
+        assert_eq!(fusion.drag_action(NavButton::Middle, NavModifier::Alt), Some(NavAction::Pan));
+
+        let default = NavigationScheme::default();
+        assert_eq!(default.drag_action(NavButton::Right, NavModifier::None), Some(NavAction::Orbit));
+        assert_eq!(default.drag_action(NavButton::Left, NavModifier::Alt), Some(NavAction::Pan));
+
+        for preset in NavigationPreset::ALL {
+            assert!(NavigationScheme::preset(preset).conflicts().is_empty(), "{:?}", preset);
+            assert_eq!(NavigationPreset::from_name(preset.name()), Some(preset));
+        }
+    }
+
+    #[test]
+    fn test_conflicting_bindings() {
+        let mut scheme = NavigationScheme::preset(NavigationPreset::SolidWorks);
+        scheme.pan = MouseBinding::new(NavButton::Middle, NavModifier::None);
+        assert_eq!(scheme.conflicts(), vec![(NavAction::Orbit, NavAction::Pan)]);
+        assert_eq!(scheme.drag_action(NavButton::Middle, NavModifier::None), Some(NavAction::Orbit));
+    }
+
+    #[test]
+    fn test_orbit_and_zoom_around_points() {
+        // A quarter turn around a pivot beside the focus swings the focus around it
+        let turn = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let focus = orbit_focus_around(Vec3::new(2.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), turn);
+        assert!(focus.abs_diff_eq(Vec3::new(1.0, 0.0, -1.0), 1e-5));
+        assert_eq!(orbit_focus_around(Vec3::ONE, Vec3::ONE, turn), Vec3::ONE);
+
+        // Halving the distance moves the focus halfway to the target
+        let focus = zoom_focus_towards(Vec3::ZERO, Vec3::new(4.0, 2.0, 0.0), 0.5);
+        assert!(focus.abs_diff_eq(Vec3::new(2.0, 1.0, 0.0), 1e-6));
+    }
+}
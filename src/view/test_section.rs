@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::{Vec3, Vec4};
+    use super::super::section::{cap_triangles, chain_loops, closest_on_axis, cut_polygon, SectionPlane, SectionPlanes};
+
+    fn area(triangles: &[[Vec3; 3]]) -> f32 {
+        triangles.iter().map(|[a, b, c]| (*b - *a).cross(*c - *a).length() * 0.5).sum()
+    }
+
+    // Side faces of an axis aligned box, as the cut through them is all that matters
+    fn box_sides(min: Vec3, max: Vec3) -> Vec<Vec<Vec3>> {
+        let corners = [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, min.y, max.z),
+        ];
+        (0..4).map(|i| {
+            let (a, b) = (corners[i], corners[(i + 1) % 4]);
+            vec![a, b, b.with_y(max.y), a.with_y(max.y)]
+        }).collect()
+    }
+
+    #[test]
+    fn test_cut_box_cap() {
+        let plane = SectionPlane::new(Vec3::new(0.0, 0.5, 0.0), Vec3::Y);
+        let segments: Vec<[Vec3; 2]> = box_sides(Vec3::ZERO, Vec3::new(2.0, 1.0, 1.0)).iter()
+            .flat_map(|face| cut_polygon(face, &plane))
+            .collect();
+        assert_eq!(segments.len(), 4);
+
+        let loops = chain_loops(&segments);
+        assert_eq!(loops.len(), 1);
+        let triangles = cap_triangles(&loops, &plane);
+        assert!((area(&triangles) - 2.0).abs() < 1e-4);
+        // Facing the hidden side
+        for [a, b, c] in &triangles {
+            assert!((*b - *a).cross(*c - *a).dot(plane.normal) > 0.0);
+            assert!((a.y - 0.5).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_cap_with_pocket() {
+        let plane = SectionPlane::new(Vec3::new(0.0, 0.5, 0.0), Vec3::NEG_Y);
+        let mut faces = box_sides(Vec3::ZERO, Vec3::splat(3.0));
+        faces.extend(box_sides(Vec3::splat(1.0), Vec3::new(2.0, 3.0, 2.0)));
+        let plane_through_both = SectionPlane::new(Vec3::new(0.0, 1.5, 0.0), Vec3::NEG_Y);
+        let segments: Vec<[Vec3; 2]> = faces.iter().flat_map(|face| cut_polygon(face, &plane_through_both)).collect();
+        let loops = chain_loops(&segments);
+        assert_eq!(loops.len(), 2);
+        // Outer square minus the pocket
+        assert!((area(&cap_triangles(&loops, &plane_through_both)) - 8.0).abs() < 1e-3);
+        // Below the pocket only the outer wall is cut
+        let segments: Vec<[Vec3; 2]> = faces.iter().flat_map(|face| cut_polygon(face, &plane)).collect();
+        assert!((area(&cap_triangles(&chain_loops(&segments), &plane)) - 9.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_clip_planes_and_drag_axis() {
+        let mut sections = SectionPlanes::default();
+        sections.planes.push(SectionPlane::new(Vec3::new(0.0, 0.0, 2.0), Vec3::Z));
+        sections.planes.push(SectionPlane { enabled: false, ..SectionPlane::new(Vec3::ZERO, Vec3::X) });
+        let planes = sections.clip_vec4s();
+        assert_eq!(planes[0], Vec4::new(0.0, 0.0, 1.0, 2.0));
+        // Disabled and unused slots never clip
+        assert_eq!(planes[1], Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(planes[3], planes[1]);
+
+        // A ray crossing the Z axis at z = 3
+        let t = closest_on_axis(Vec3::ZERO, Vec3::Z, Vec3::new(5.0, 0.0, 3.0), Vec3::NEG_X).unwrap();
+        assert!((t - 3.0).abs() < 1e-5);
+        assert_eq!(closest_on_axis(Vec3::ZERO, Vec3::Z, Vec3::X, Vec3::Z), None);
+    }
+}
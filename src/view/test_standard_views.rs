@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+    use bevy::prelude::*;
+    use super::super::camera::PanOrbitCamera;
+    use super::super::standard_views::{fit_bounds, OrbitPose, StandardView, ViewTransition};
+
+    #[test]
+    fn test_views_look_along_axes() {
+        let mut transform = Transform::default();
+        for (view, direction) in [
+            (StandardView::Front, Vec3::NEG_Z),
+            (StandardView::Top, Vec3::NEG_Y),
+            (StandardView::Right, Vec3::NEG_X),
+        ] {
+            let (yaw, pitch) = view.yaw_pitch();
+            PanOrbitCamera { focus: Vec3::ZERO, radius: 3.0, yaw, pitch }.apply_to(&mut transform);
+            assert!((transform.forward().as_vec3() - direction).length() < 1e-5, "{:?}", view);
+            assert!((transform.translation + direction * 3.0).length() < 1e-5);
+        }
+
+        // Isometric looks down the cube diagonal
+        let (yaw, pitch) = StandardView::Isometric.yaw_pitch();
+        PanOrbitCamera { focus: Vec3::ZERO, radius: 1.0, yaw, pitch }.apply_to(&mut transform);
+        assert!((transform.translation - Vec3::ONE.normalize()).length() < 1e-5);
+        assert_eq!(StandardView::from_name("isometric"), Some(StandardView::Isometric));
+    }
+
+    #[test]
+    fn test_fit_bounds() {
+        let (center, radius) = fit_bounds(Vec3::ZERO, Vec3::new(2.0, 2.0, 0.0), FRAC_PI_2, 1.0);
+        assert_eq!(center, Vec3::new(1.0, 1.0, 0.0));
+        // Bounding sphere of radius sqrt(2) inside a 90 degree cone, plus the margin
+        assert!((radius - 2.0 * 1.15).abs() < 1e-4);
+        // A narrow window needs more distance
+        assert!(fit_bounds(Vec3::ZERO, Vec3::new(2.0, 2.0, 0.0), FRAC_PI_2, 0.5).1 > radius);
+    }
+
+    #[test]
+    fn test_transition_turns_short_way() {
+        let from = OrbitPose { focus: Vec3::ZERO, radius: 1.0, yaw: PI * 0.9, pitch: 0.0 };
+        let to = OrbitPose { focus: Vec3::X, radius: 4.0, yaw: -PI * 0.9, pitch: -FRAC_PI_2 };
+        let mut transition = ViewTransition::new(from, to, 1.0);
+
+        let (middle, finished) = transition.step(0.5);
+        assert!(!finished);
+        assert!((middle.yaw - PI).abs() < 1e-5);
+        assert!((middle.radius - 2.0).abs() < 1e-5);
+
+        let (end, finished) = transition.step(0.6);
+        assert!(finished);
+        assert_eq!(end.focus, Vec3::X);
+        assert!((end.yaw - PI * 1.1).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use bevy::math::{Rect, Vec2};
+    use super::super::standard_views::StandardView;
+    use super::super::viewports::{initial_view, viewport_rects, ViewportLayout};
+
+    #[test]
+    fn test_single_layout_fills_the_area() {
+        let area = Rect::new(160.0, 32.0, 1040.0, 800.0);
+        assert_eq!(viewport_rects(ViewportLayout::Single, area), vec![area]);
+    }
+
+    #[test]
+    fn test_two_layout_splits_side_by_side() {
+        let rects = viewport_rects(ViewportLayout::Two, Rect::new(0.0, 0.0, 800.0, 600.0));
+        assert_eq!(rects, vec![Rect::new(0.0, 0.0, 400.0, 600.0), Rect::new(400.0, 0.0, 800.0, 600.0)]);
+    }
+
+    #[test]
+    fn test_four_layout_keeps_perspective_top_right() {
+        let rects = viewport_rects(ViewportLayout::Four, Rect::new(100.0, 50.0, 900.0, 650.0));
+        assert_eq!(rects.len(), 4);
+        assert_eq!(rects[0], Rect::new(500.0, 50.0, 900.0, 350.0));
+        assert_eq!(rects[1], Rect::new(100.0, 50.0, 500.0, 350.0));
+        assert_eq!(rects[2], Rect::new(100.0, 350.0, 500.0, 650.0));
+        assert_eq!(rects[3], Rect::new(500.0, 350.0, 900.0, 650.0));
+        // Together they cover the area without overlap
+        let total: f32 = rects.iter().map(|rect| rect.width() * rect.height()).sum();
+        assert_eq!(total, 800.0 * 600.0);
+        assert!(rects[1].contains(Vec2::new(120.0, 60.0)) && !rects[0].contains(Vec2::new(120.0, 60.0)));
+    }
+
+    #[test]
+    fn test_initial_views_and_layout_names() {
+        assert_eq!(initial_view(0), None);
+        assert_eq!(initial_view(1), Some(StandardView::Top));
+        assert_eq!(initial_view(2), Some(StandardView::Front));
+        assert_eq!(initial_view(3), Some(StandardView::Right));
+        assert_eq!(ViewportLayout::from_name("quad"), Some(ViewportLayout::Four));
+        assert_eq!(ViewportLayout::from_name("2"), Some(ViewportLayout::Two));
+        assert_eq!(ViewportLayout::from_name("3"), None);
+        assert!(ViewportLayout::ALL.iter().all(|layout| ViewportLayout::from_name(&layout.count().to_string()) == Some(*layout)));
+    }
+}
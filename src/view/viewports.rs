@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use super::standard_views::StandardView;
+
+/// How the 3D area is split into viewports
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ViewportLayout {
+    #[default]
+    Single,
+    Two,
+    Four,
+}
+
+impl ViewportLayout {
+    pub const ALL: [ViewportLayout; 3] = [ViewportLayout::Single, ViewportLayout::Two, ViewportLayout::Four];
+
+    pub fn count(&self) -> usize {
+        match self {
+            ViewportLayout::Single => 1,
+            ViewportLayout::Two => 2,
+            ViewportLayout::Four => 4,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViewportLayout::Single => "Single viewport",
+            ViewportLayout::Two => "Two viewports",
+            ViewportLayout::Four => "Four viewports",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "1" | "single" => Some(ViewportLayout::Single),
+            "2" | "two" => Some(ViewportLayout::Two),
+            "4" | "four" | "quad" => Some(ViewportLayout::Four),
+            _ => None,
+        }
+    }
+}
+
+/// Area of each viewport, by viewport index. Viewport 0 keeps the perspective view and sits
+/// top right in the quad view, with top, front and right views around it.
+pub fn viewport_rects(layout: ViewportLayout, area: Rect) -> Vec<Rect> {
+    let center = area.center();
+    match layout {
+        ViewportLayout::Single => vec![area],
+        ViewportLayout::Two => vec![
+            Rect::new(area.min.x, area.min.y, center.x, area.max.y),
+            Rect::new(center.x, area.min.y, area.max.x, area.max.y),
+        ],
+        ViewportLayout::Four => vec![
+            Rect::new(center.x, area.min.y, area.max.x, center.y),
+            Rect::new(area.min.x, area.min.y, center.x, center.y),
+            Rect::new(area.min.x, center.y, center.x, area.max.y),
+            Rect::new(center.x, center.y, area.max.x, area.max.y),
+        ],
+    }
+}
+
+/// View a newly opened viewport starts with, orthographic. Viewport 0 is the perspective one.
+pub fn initial_view(index: usize) -> Option<StandardView> {
+    match index {
+        0 => None,
+        1 => Some(StandardView::Top),
+        2 => Some(StandardView::Front),
+        _ => Some(StandardView::Right),
+    }
+}
+
+/// The viewport under the cursor, or the one it was last over. Tools, view commands and
+/// camera navigation act on it.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct ActiveViewport;
+
+/// Window cursor position in the coordinates `Camera::viewport_to_world` expects
+pub fn viewport_cursor(camera: &Camera, window_cursor: Vec2) -> Vec2 {
+    window_cursor - viewport_origin(camera)
+}
+
+/// Window position of the top left corner of the camera's viewport, add it to positions from
+/// `Camera::world_to_viewport` to draw in window coordinates
+pub fn viewport_origin(camera: &Camera) -> Vec2 {
+    camera.logical_viewport_rect().map_or(Vec2::ZERO, |rect| rect.min)
+}